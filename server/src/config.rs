@@ -0,0 +1,291 @@
+use std::{
+    env,
+    fs::{self, File},
+    io::{self, BufReader},
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+
+use serde::Deserialize;
+use snafu::prelude::*;
+use tokio_rustls::{rustls::ServerConfig, TlsAcceptor};
+
+static DEFAULT_CONFIG_PATH: &str = "rustopoly.json";
+static DEFAULT_SERVER_PORT: u16 = 3600;
+static DEFAULT_STATIC_DIR: &str = "ui/dist";
+static DEFAULT_DATA_DIR: &str = "data";
+
+static USAGE: &str = "\
+Usage: server [OPTIONS]
+
+Options:
+    --config <PATH>           Path to the JSON config file [env: SERVER_CONFIG] [default: rustopoly.json]
+    --address <IP>            IPv4 or IPv6 address to listen on [env: SERVER_ADDRESS]
+    --port <PORT>             Port to listen on [env: SERVER_PORT]
+    --tls-cert <PATH>         PEM file with TLS certificate chain [env: SERVER_TLS_CERT]
+    --tls-key <PATH>          PEM file with TLS private key [env: SERVER_TLS_KEY]
+    --allowed-origin <ORIGIN> Origin allowed to open WebSocket connection. Can be repeated
+                              [env: SERVER_ALLOWED_ORIGINS, comma separated]
+    --static-dir <PATH>       Directory with the built UI [env: SERVER_STATIC_DIR] [default: ui/dist]
+    --data-dir <PATH>         Directory to save players and games to [env: SERVER_DATA_DIR] [default: data]
+    --help                    Print this message";
+
+/// Server configuration.
+///
+/// Values are taken from (in order of increasing priority): the config file, environment
+/// variables and command line flags.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub address: IpAddr,
+    pub port: u16,
+    pub tls: Option<TlsConfig>,
+    /// Values of the `Origin` header that are allowed to open a WebSocket connection.
+    /// Empty list allows any origin.
+    pub allowed_origins: Vec<String>,
+    /// Directory with the built UI, served over HTTP on the same port as the WebSocket.
+    pub static_dir: PathBuf,
+    /// Directory the server state is saved to on shutdown and loaded from on startup.
+    pub data_dir: PathBuf,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TlsConfig {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+}
+
+#[derive(Debug, Snafu)]
+pub enum ConfigError {
+    #[snafu(display("Unable to read config file \"{}\": {source}", path.display()))]
+    ReadFile { path: PathBuf, source: io::Error },
+    #[snafu(display("Unable to parse config file \"{}\": {source}", path.display()))]
+    ParseFile {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[snafu(display("Invalid value \"{value}\" for {name}"))]
+    InvalidValue { name: String, value: String },
+    #[snafu(display("Missing value for {flag}"))]
+    MissingValue { flag: String },
+    #[snafu(display("Unknown flag {flag}\n\n{USAGE}"))]
+    UnknownFlag { flag: String },
+    #[snafu(display("Both TLS certificate and key should be provided"))]
+    IncompleteTls,
+}
+
+#[derive(Debug, Snafu)]
+pub enum TlsError {
+    #[snafu(display("Unable to read \"{}\": {source}", path.display()))]
+    ReadPem { path: PathBuf, source: io::Error },
+    #[snafu(display("\"{}\" does not contain a private key", path.display()))]
+    NoPrivateKey { path: PathBuf },
+    #[snafu(display("Invalid TLS certificate or key: {source}"))]
+    InvalidCertificate { source: tokio_rustls::rustls::Error },
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            address: Ipv4Addr::LOCALHOST.into(),
+            port: DEFAULT_SERVER_PORT,
+            tls: None,
+            allowed_origins: Vec::new(),
+            static_dir: DEFAULT_STATIC_DIR.into(),
+            data_dir: DEFAULT_DATA_DIR.into(),
+        }
+    }
+}
+
+impl Config {
+    pub fn load() -> Result<Self, ConfigError> {
+        let args = Args::parse(env::args().skip(1))?;
+
+        let mut config = match args
+            .config
+            .clone()
+            .or_else(|| env::var_os("SERVER_CONFIG").map(PathBuf::from))
+        {
+            Some(path) => Self::from_file(&path)?,
+            None if Path::new(DEFAULT_CONFIG_PATH).exists() => {
+                Self::from_file(Path::new(DEFAULT_CONFIG_PATH))?
+            }
+            None => Self::default(),
+        };
+
+        config.apply_env()?;
+        config.apply_args(args)?;
+        Ok(config)
+    }
+
+    pub fn socket_address(&self) -> SocketAddr {
+        SocketAddr::new(self.address, self.port)
+    }
+
+    pub fn is_origin_allowed(&self, origin: Option<&str>) -> bool {
+        self.allowed_origins.is_empty()
+            || origin.is_some_and(|origin| self.allowed_origins.iter().any(|o| o == origin))
+    }
+
+    fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let content = fs::read_to_string(path).context(ReadFileSnafu { path })?;
+        serde_json::from_str(&content).context(ParseFileSnafu { path })
+    }
+
+    fn apply_env(&mut self) -> Result<(), ConfigError> {
+        if let Ok(address) = env::var("SERVER_ADDRESS") {
+            self.address = parse_value("$SERVER_ADDRESS", address)?;
+        }
+
+        if let Ok(port) = env::var("SERVER_PORT") {
+            self.port = parse_value("$SERVER_PORT", port)?;
+        }
+
+        if let Ok(origins) = env::var("SERVER_ALLOWED_ORIGINS") {
+            self.allowed_origins = origins
+                .split(',')
+                .map(str::trim)
+                .filter(|origin| !origin.is_empty())
+                .map(ToOwned::to_owned)
+                .collect();
+        }
+
+        if let Some(static_dir) = env::var_os("SERVER_STATIC_DIR") {
+            self.static_dir = static_dir.into();
+        }
+
+        if let Some(data_dir) = env::var_os("SERVER_DATA_DIR") {
+            self.data_dir = data_dir.into();
+        }
+
+        self.override_tls(
+            env::var_os("SERVER_TLS_CERT").map(PathBuf::from),
+            env::var_os("SERVER_TLS_KEY").map(PathBuf::from),
+        )
+    }
+
+    fn apply_args(&mut self, args: Args) -> Result<(), ConfigError> {
+        if let Some(address) = args.address {
+            self.address = parse_value("--address", address)?;
+        }
+
+        if let Some(port) = args.port {
+            self.port = parse_value("--port", port)?;
+        }
+
+        if !args.allowed_origins.is_empty() {
+            self.allowed_origins = args.allowed_origins;
+        }
+
+        if let Some(static_dir) = args.static_dir {
+            self.static_dir = static_dir;
+        }
+
+        if let Some(data_dir) = args.data_dir {
+            self.data_dir = data_dir;
+        }
+
+        self.override_tls(args.tls_cert, args.tls_key)
+    }
+
+    fn override_tls(
+        &mut self,
+        cert_path: Option<PathBuf>,
+        key_path: Option<PathBuf>,
+    ) -> Result<(), ConfigError> {
+        self.tls = match (cert_path, key_path, self.tls.take()) {
+            (None, None, tls) => tls,
+            (cert_path, key_path, Some(tls)) => Some(TlsConfig {
+                cert_path: cert_path.unwrap_or(tls.cert_path),
+                key_path: key_path.unwrap_or(tls.key_path),
+            }),
+            (Some(cert_path), Some(key_path), None) => Some(TlsConfig {
+                cert_path,
+                key_path,
+            }),
+            (_, _, None) => return IncompleteTlsSnafu.fail(),
+        };
+
+        Ok(())
+    }
+}
+
+impl TlsConfig {
+    pub fn acceptor(&self) -> Result<TlsAcceptor, TlsError> {
+        let certs = rustls_pemfile::certs(&mut open_pem(&self.cert_path)?)
+            .collect::<Result<Vec<_>, _>>()
+            .context(ReadPemSnafu {
+                path: &self.cert_path,
+            })?;
+
+        let key = rustls_pemfile::private_key(&mut open_pem(&self.key_path)?)
+            .context(ReadPemSnafu {
+                path: &self.key_path,
+            })?
+            .context(NoPrivateKeySnafu {
+                path: &self.key_path,
+            })?;
+
+        let config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .context(InvalidCertificateSnafu)?;
+
+        Ok(TlsAcceptor::from(Arc::new(config)))
+    }
+}
+
+fn open_pem(path: &Path) -> Result<BufReader<File>, TlsError> {
+    File::open(path)
+        .map(BufReader::new)
+        .context(ReadPemSnafu { path })
+}
+
+fn parse_value<T: FromStr>(name: &str, value: String) -> Result<T, ConfigError> {
+    value
+        .parse()
+        .ok()
+        .context(InvalidValueSnafu { name, value })
+}
+
+#[derive(Debug, Default)]
+struct Args {
+    config: Option<PathBuf>,
+    address: Option<String>,
+    port: Option<String>,
+    tls_cert: Option<PathBuf>,
+    tls_key: Option<PathBuf>,
+    allowed_origins: Vec<String>,
+    static_dir: Option<PathBuf>,
+    data_dir: Option<PathBuf>,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, ConfigError> {
+        let mut parsed = Self::default();
+
+        while let Some(flag) = args.next() {
+            if flag == "--help" {
+                println!("{USAGE}");
+                std::process::exit(0);
+            }
+
+            let value = args.next().context(MissingValueSnafu { flag: &flag })?;
+            match flag.as_str() {
+                "--config" => parsed.config = Some(value.into()),
+                "--address" => parsed.address = Some(value),
+                "--port" => parsed.port = Some(value),
+                "--tls-cert" => parsed.tls_cert = Some(value.into()),
+                "--tls-key" => parsed.tls_key = Some(value.into()),
+                "--allowed-origin" => parsed.allowed_origins.push(value),
+                "--static-dir" => parsed.static_dir = Some(value.into()),
+                "--data-dir" => parsed.data_dir = Some(value.into()),
+                _ => return UnknownFlagSnafu { flag }.fail(),
+            }
+        }
+
+        Ok(parsed)
+    }
+}
@@ -0,0 +1,244 @@
+use std::{
+    io,
+    path::{Component, Path, PathBuf},
+    time::Duration,
+};
+
+use snafu::prelude::*;
+use tokio::{
+    fs,
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    time::timeout,
+};
+
+const MAX_HEAD_SIZE: usize = 8 * 1024;
+const MAX_HEADERS: usize = 64;
+/// Time a client has to send the whole request head, so that idle connections do not hold
+/// handshake tasks forever.
+const READ_HEAD_TIMEOUT: Duration = Duration::from_secs(10);
+/// The only WebSocket protocol version there is (RFC 6455).
+pub const WEBSOCKET_VERSION: &str = "13";
+
+/// Request line and headers of an HTTP/1.1 request.
+#[derive(Debug)]
+pub struct RequestHead {
+    pub method: String,
+    pub path: String,
+    headers: Vec<(String, String)>,
+}
+
+#[derive(Debug, Snafu)]
+pub enum RequestError {
+    #[snafu(display("Unable to read request: {source}"))]
+    Read { source: io::Error },
+    #[snafu(display("Connection closed before request was received"))]
+    Closed,
+    #[snafu(display("Request was not received in {READ_HEAD_TIMEOUT:?}"))]
+    TimedOut,
+    #[snafu(display("Request head is larger than {MAX_HEAD_SIZE} bytes"))]
+    TooLarge,
+    #[snafu(display("Malformed request: {source}"))]
+    Malformed { source: httparse::Error },
+}
+
+impl RequestHead {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Path without query string and fragment.
+    pub fn route(&self) -> &str {
+        self.path.split(['?', '#']).next().unwrap_or_default()
+    }
+
+    /// Whether the request asks to upgrade the connection to a WebSocket. The version of the
+    /// protocol is checked separately, because it has its own response.
+    pub fn is_websocket_upgrade(&self) -> bool {
+        let is_upgrade = self.header("connection").is_some_and(|connection| {
+            connection
+                .split(',')
+                .any(|option| option.trim().eq_ignore_ascii_case("upgrade"))
+        });
+        let is_websocket = self
+            .header("upgrade")
+            .is_some_and(|upgrade| upgrade.eq_ignore_ascii_case("websocket"));
+
+        self.method == "GET" && is_upgrade && is_websocket
+    }
+}
+
+pub async fn read_request_head<S>(stream: &mut S) -> Result<RequestHead, RequestError>
+where
+    S: AsyncRead + Unpin,
+{
+    timeout(READ_HEAD_TIMEOUT, read_request_head_untimed(stream))
+        .await
+        .map_err(|_| TimedOutSnafu.build())?
+}
+
+async fn read_request_head_untimed<S>(stream: &mut S) -> Result<RequestHead, RequestError>
+where
+    S: AsyncRead + Unpin,
+{
+    let mut buffer = Vec::with_capacity(1024);
+    let mut chunk = [0; 1024];
+
+    loop {
+        let read = stream.read(&mut chunk).await.context(ReadSnafu)?;
+        ensure!(read != 0, ClosedSnafu);
+        buffer.extend_from_slice(&chunk[..read]);
+
+        let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+        let mut request = httparse::Request::new(&mut headers);
+        if request
+            .parse(&buffer)
+            .context(MalformedSnafu)?
+            .is_complete()
+        {
+            return Ok(RequestHead {
+                method: request.method.unwrap_or_default().to_owned(),
+                path: request.path.unwrap_or_default().to_owned(),
+                headers: request
+                    .headers
+                    .iter()
+                    .map(|header| {
+                        (
+                            header.name.to_owned(),
+                            String::from_utf8_lossy(header.value).into_owned(),
+                        )
+                    })
+                    .collect(),
+            });
+        }
+
+        ensure!(buffer.len() < MAX_HEAD_SIZE, TooLargeSnafu);
+    }
+}
+
+/// Writes a complete response and closes the connection.
+pub async fn write_response<S>(
+    stream: &mut S,
+    status: &str,
+    headers: &[(&str, &str)],
+    body: &[u8],
+    include_body: bool,
+) -> io::Result<()>
+where
+    S: AsyncWrite + Unpin,
+{
+    let mut head = format!(
+        "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        body.len()
+    );
+    for (name, value) in headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str("\r\n");
+
+    stream.write_all(head.as_bytes()).await?;
+    if include_body {
+        stream.write_all(body).await?;
+    }
+    stream.shutdown().await
+}
+
+/// Serves files of the built UI from `root`.
+pub async fn serve_static<S>(stream: &mut S, root: &Path, request: &RequestHead) -> io::Result<()>
+where
+    S: AsyncWrite + Unpin,
+{
+    let is_head = request.method == "HEAD";
+    if request.method != "GET" && !is_head {
+        return write_response(
+            stream,
+            "405 Method Not Allowed",
+            &[("Allow", "GET, HEAD")],
+            b"",
+            false,
+        )
+        .await;
+    }
+
+    let Some(path) = resolve_path(root, request.route()).await else {
+        return write_response(stream, "404 Not Found", &[], b"Not Found", !is_head).await;
+    };
+
+    match fs::read(&path).await {
+        Ok(body) => {
+            let content_type = content_type(&path);
+            // Trunk puts content hash into names of all assets, except for "index.html".
+            let cache_control = if content_type.starts_with("text/html") {
+                "no-cache"
+            } else {
+                "public, max-age=31536000, immutable"
+            };
+
+            write_response(
+                stream,
+                "200 OK",
+                &[
+                    ("Content-Type", content_type),
+                    ("Cache-Control", cache_control),
+                ],
+                &body,
+                !is_head,
+            )
+            .await
+        }
+        Err(error) => {
+            tracing::error!("Unable to read \"{}\": {}", path.display(), error);
+            write_response(
+                stream,
+                "500 Internal Server Error",
+                &[],
+                b"Internal Server Error",
+                !is_head,
+            )
+            .await
+        }
+    }
+}
+
+/// Maps the route onto a file inside `root`.
+/// Missing routes without an extension are client-side routes, so they resolve to "index.html".
+async fn resolve_path(root: &Path, route: &str) -> Option<PathBuf> {
+    let relative = Path::new(route.trim_start_matches('/'));
+
+    // Forbid "..", absolute paths and other ways to escape the root.
+    if !relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return None;
+    }
+
+    let path = root.join(relative);
+    if fs::metadata(&path)
+        .await
+        .is_ok_and(|metadata| metadata.is_file())
+    {
+        Some(path)
+    } else if relative.extension().is_none() {
+        Some(root.join("index.html"))
+    } else {
+        None
+    }
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("js") => "text/javascript; charset=utf-8",
+        Some("wasm") => "application/wasm",
+        Some("css") => "text/css; charset=utf-8",
+        Some("json" | "map") => "application/json",
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        Some("ico") => "image/x-icon",
+        Some("txt") => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
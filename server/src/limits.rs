@@ -0,0 +1,216 @@
+use std::{
+    collections::HashMap,
+    io,
+    net::IpAddr,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use snafu::prelude::*;
+use tarpc::{context, server::Serve, ServerError};
+
+/// Maximum amount of simultaneous connections (including plain HTTP ones) from one IP address.
+static MAX_CONNECTIONS_PER_IP: usize = 8;
+/// Amount of malformed frames after which the IP address is banned.
+static MAX_STRIKES: usize = 3;
+/// Strikes older than this are forgotten.
+static STRIKE_WINDOW: Duration = Duration::from_secs(10 * 60);
+static BAN_DURATION: Duration = Duration::from_secs(15 * 60);
+
+/// Burst of RPC calls a session can make at once.
+static RPC_BURST: u32 = 20;
+/// Sustained rate of RPC calls per session, per second.
+static RPC_RATE: f64 = 5.;
+
+/// Burst of chat messages a session can send at once.
+pub static CHAT_BURST: u32 = 5;
+/// Sustained rate of chat messages per session, per second.
+pub static CHAT_RATE: f64 = 0.5;
+
+/// Classic token bucket: holds up to `capacity` tokens and refills at a constant rate.
+#[derive(Debug)]
+pub struct TokenBucket {
+    capacity: f64,
+    refill_per_second: f64,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(capacity: u32, refill_per_second: f64) -> Self {
+        Self {
+            capacity: capacity.into(),
+            refill_per_second,
+            tokens: capacity.into(),
+            refilled_at: Instant::now(),
+        }
+    }
+
+    /// Takes a token, if there is one.
+    pub fn try_take(&mut self) -> bool {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_second).min(self.capacity);
+        self.refilled_at = now;
+
+        if self.tokens >= 1. {
+            self.tokens -= 1.;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum LimitError {
+    #[snafu(display("Banned for {} more seconds", remaining.as_secs()))]
+    Banned { remaining: Duration },
+    #[snafu(display("Too many connections"))]
+    TooManyConnections,
+    #[snafu(display("Too many requests"))]
+    TooManyRequests,
+}
+
+impl From<LimitError> for ServerError {
+    fn from(error: LimitError) -> Self {
+        let kind = match error {
+            LimitError::Banned { .. } => io::ErrorKind::PermissionDenied,
+            LimitError::TooManyConnections | LimitError::TooManyRequests => {
+                io::ErrorKind::WouldBlock
+            }
+        };
+        ServerError::new(kind, error.to_string())
+    }
+}
+
+/// Tracks connections and misbehavior of every client IP address.
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionLimiter(Arc<Mutex<HashMap<IpAddr, ClientRecord>>>);
+
+#[derive(Debug, Default)]
+struct ClientRecord {
+    connections: usize,
+    /// Times the client sent malformed frames at, within the `STRIKE_WINDOW`.
+    strikes: Vec<Instant>,
+    banned_until: Option<Instant>,
+}
+
+/// Keeps a connection slot of the IP address occupied until dropped.
+#[derive(Debug)]
+pub struct ConnectionGuard {
+    limiter: ConnectionLimiter,
+    ip: IpAddr,
+}
+
+impl ClientRecord {
+    fn forget_expired(&mut self, now: Instant) {
+        self.strikes
+            .retain(|struck_at| now.duration_since(*struck_at) < STRIKE_WINDOW);
+        if self.banned_until.is_some_and(|until| until <= now) {
+            self.banned_until = None;
+        }
+    }
+
+    fn is_idle(&self) -> bool {
+        self.connections == 0 && self.strikes.is_empty() && self.banned_until.is_none()
+    }
+}
+
+impl ConnectionLimiter {
+    /// Occupies a connection slot of the IP address, unless it is banned or has too many
+    /// connections already.
+    pub fn try_connect(&self, ip: IpAddr) -> Result<ConnectionGuard, LimitError> {
+        let now = Instant::now();
+        let mut clients = self.lock();
+        clients.retain(|_, client| {
+            client.forget_expired(now);
+            !client.is_idle()
+        });
+
+        let client = clients.entry(ip).or_default();
+        if let Some(until) = client.banned_until {
+            return BannedSnafu {
+                remaining: until - now,
+            }
+            .fail();
+        }
+        ensure!(
+            client.connections < MAX_CONNECTIONS_PER_IP,
+            TooManyConnectionsSnafu
+        );
+
+        client.connections += 1;
+        Ok(ConnectionGuard {
+            limiter: self.clone(),
+            ip,
+        })
+    }
+
+    /// Records that the IP address sent a malformed frame, banning it after too many of them.
+    pub fn strike(&self, ip: IpAddr) {
+        let now = Instant::now();
+        let mut clients = self.lock();
+        let client = clients.entry(ip).or_default();
+        client.forget_expired(now);
+        client.strikes.push(now);
+
+        if client.strikes.len() >= MAX_STRIKES {
+            tracing::warn!("Banning {} for {:?}", ip, BAN_DURATION);
+            client.strikes.clear();
+            client.banned_until = Some(now + BAN_DURATION);
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<IpAddr, ClientRecord>> {
+        self.0
+            .lock()
+            .expect("ConnectionLimiter mutex should not be poisoned")
+    }
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        let mut clients = self.limiter.lock();
+        if let Some(client) = clients.get_mut(&self.ip) {
+            client.connections -= 1;
+            if client.is_idle() {
+                clients.remove(&self.ip);
+            }
+        }
+    }
+}
+
+/// Rejects requests of a session that exceeds its RPC rate limit.
+#[derive(Debug, Clone)]
+pub struct Throttled<S> {
+    serve: S,
+    bucket: Arc<Mutex<TokenBucket>>,
+}
+
+impl<S> Throttled<S> {
+    pub fn new(serve: S) -> Self {
+        Self {
+            serve,
+            bucket: Arc::new(Mutex::new(TokenBucket::new(RPC_BURST, RPC_RATE))),
+        }
+    }
+}
+
+impl<S: Serve> Serve for Throttled<S> {
+    type Req = S::Req;
+    type Resp = S::Resp;
+
+    async fn serve(self, ctx: context::Context, req: Self::Req) -> Result<Self::Resp, ServerError> {
+        let allowed = self
+            .bucket
+            .lock()
+            .expect("TokenBucket mutex should not be poisoned")
+            .try_take();
+        if !allowed {
+            return Err(TooManyRequestsSnafu.build().into());
+        }
+
+        self.serve.serve(ctx, req).await
+    }
+}
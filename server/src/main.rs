@@ -2,48 +2,197 @@
 #![feature(async_closure)]
 
 use std::{
-    convert::identity,
-    env, future,
-    net::{Ipv4Addr, SocketAddrV4},
+    future, process,
+    time::{Duration, Instant},
 };
 
 use any_spawner::Executor;
-use futures::StreamExt;
-use shared::{backend::Backend, frontend::FrontendClient, spawn_two_way::spawn_two_way};
+use futures::{stream, FutureExt, Stream, StreamExt};
+use shared::{
+    backend::Backend,
+    frontend::FrontendClient,
+    spawn_two_way::{spawn_two_way, Heartbeat},
+};
 use tarpc::{client, server::Channel};
+use tokio::task::LocalSet;
 
-use crate::server::BackendServer;
+use crate::{
+    config::Config, limits::Throttled, server::BackendServer, state::ServerState, storage::Storage,
+};
 
+mod config;
+mod http;
+mod limits;
 mod server;
+mod state;
+mod storage;
 mod ws;
 
-static DEFAULT_SERVER_PORT: &str = "3600";
+static HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+static MAX_MISSED_PONGS: usize = 3;
+static MAX_CONCURRENT_REQUESTS: usize = 16;
+static STATE_DOCUMENT: &str = "state";
+/// How long to wait for clients to receive the shutdown notice and for in-flight requests to
+/// finish.
+static SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[tokio::main(flavor = "current_thread")]
 pub async fn main() {
     Executor::init_tokio().unwrap();
 
-    let server_address = SocketAddrV4::new(
-        Ipv4Addr::LOCALHOST,
-        env::var("SERVER_PORT")
-            .unwrap_or(DEFAULT_SERVER_PORT.into())
-            .parse()
-            .expect("$SERVER_PORT should be a valid port"),
+    // Transports are driven by tasks spawned with `Executor::spawn_local`, which need a `LocalSet`.
+    LocalSet::new().run_until(run()).await;
+}
+
+async fn run() {
+    let config = Config::load().unwrap_or_else(|error| {
+        eprintln!("Invalid configuration: {}", error);
+        process::exit(1);
+    });
+
+    let storage = Storage::new(&config.data_dir);
+    let state = match storage.load(STATE_DOCUMENT).await {
+        Ok(saved) => ServerState::restore(saved.unwrap_or_default()),
+        Err(error) => {
+            eprintln!("Unable to load saved state: {}", error);
+            process::exit(1);
+        }
+    };
+
+    println!(
+        "Starting server on {}://{}",
+        if config.tls.is_some() {
+            "https"
+        } else {
+            "http"
+        },
+        config.socket_address()
     );
 
-    println!("Starting server on {}", server_address);
+    let started_at = Instant::now();
 
-    ws::bind(server_address)
+    let shutdown_requested = shutdown_signal().shared();
+    let connections = ws::bind(&config, shutdown_requested.clone())
         .await
-        .filter_map(|r| future::ready(r.ok()))
-        .map(spawn_two_way)
-        .map(|(server_transport, client_transport)| {
+        .unwrap_or_else(|error| {
+            eprintln!("Unable to start server: {}", error);
+            process::exit(1);
+        });
+
+    connections
+        .take_until(shutdown_requested)
+        .for_each(|(transport, connection_guard)| {
+            let (server_transport, client_transport, latency) = spawn_two_way(
+                transport,
+                Heartbeat {
+                    interval: heartbeat_interval(),
+                    clock: move || started_at.elapsed(),
+                    max_missed_pongs: MAX_MISSED_PONGS,
+                },
+            );
             let player_client =
                 FrontendClient::new(client::Config::default(), client_transport).spawn();
-            tarpc::server::BaseChannel::with_defaults(server_transport)
-                .execute(BackendServer::new(player_client).serve())
-                .for_each_concurrent(None, identity)
+            let session_id = state.connect(player_client);
+            let rpc = tarpc::server::BaseChannel::with_defaults(server_transport)
+                .max_concurrent_requests(MAX_CONCURRENT_REQUESTS)
+                .execute(Throttled::new(
+                    BackendServer::new(state.clone(), session_id).serve(),
+                ))
+                .for_each_concurrent(None, {
+                    let state = state.clone();
+                    move |response| {
+                        let request = state.start_request();
+                        async move {
+                            response.await;
+                            drop(request);
+                        }
+                    }
+                });
+            let latency = latency.for_each({
+                let state = state.clone();
+                move |latency| {
+                    state.update_latency(session_id, latency);
+                    future::ready(())
+                }
+            });
+            let state = state.clone();
+
+            // Sessions are spawned, rather than polled here, so that they outlive the accept loop
+            // during shutdown.
+            Executor::spawn_local(async move {
+                futures::join!(rpc, latency);
+                state.disconnect(session_id);
+                drop(connection_guard);
+            });
+            future::ready(())
         })
-        .for_each_concurrent(None, identity)
         .await;
+
+    println!("Shutting down");
+    shutdown(&state).await;
+
+    match storage.save(STATE_DOCUMENT, &state.save()).await {
+        Ok(()) => println!("Saved state to \"{}\"", config.data_dir.display()),
+        Err(error) => eprintln!("Unable to save state: {}", error),
+    }
+}
+
+/// Notifies clients and waits for in-flight requests, for at most `SHUTDOWN_TIMEOUT`.
+async fn shutdown(state: &ServerState) {
+    let drain = async {
+        state.notify_restarting().await;
+        while state.in_flight_requests() > 0 {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    };
+
+    if tokio::time::timeout(SHUTDOWN_TIMEOUT, drain).await.is_err() {
+        eprintln!(
+            "{} requests did not finish in {:?}",
+            state.in_flight_requests(),
+            SHUTDOWN_TIMEOUT
+        );
+    }
+}
+
+/// Resolves on SIGINT or SIGTERM.
+async fn shutdown_signal() {
+    let interrupt = async {
+        if let Err(error) = tokio::signal::ctrl_c().await {
+            eprintln!("Unable to listen for SIGINT: {}", error);
+            future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(error) => {
+                eprintln!("Unable to listen for SIGTERM: {}", error);
+                future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = future::pending::<()>();
+
+    tokio::select! {
+        () = interrupt => {}
+        () = terminate => {}
+    }
+}
+
+fn heartbeat_interval() -> impl Stream<Item = ()> {
+    stream::unfold(
+        tokio::time::interval(HEARTBEAT_INTERVAL),
+        |mut interval| async move {
+            interval.tick().await;
+            Some(((), interval))
+        },
+    )
 }
@@ -1,21 +1,97 @@
-use shared::{backend::Backend, frontend::FrontendClient};
+use shared::{
+    backend::Backend,
+    chat::ChatError,
+    game::{BidRequest, GameError, GameSnapshot, LogEntry},
+    money::Money,
+    player::PlayerId,
+    room::{
+        CreateRoomError, JoinRoomError, JoinedRoom, RematchError, RoomId, RoomInfo, RoomRole,
+        RulesPreset,
+    },
+    timer::{Countdown, CountdownError},
+};
 use tarpc::context;
 
+use crate::state::{ServerState, SessionId};
+
 #[derive(Debug, Clone)]
 pub struct BackendServer {
-    player_client: FrontendClient,
+    state: ServerState,
+    session_id: SessionId,
 }
 
 impl Backend for BackendServer {
-    async fn authenticate(self, ctx: context::Context, name: String) {
+    async fn authenticate(self, _: context::Context, name: String) -> PlayerId {
         println!("Authenticate: {}", name);
-        // TODO: Do not unwrap here - client can disconnect mid request...
-        self.player_client.event(ctx).await.unwrap();
+        self.state.authenticate(self.session_id, name)
+    }
+
+    async fn list_rooms(self, _: context::Context) -> Vec<RoomInfo> {
+        self.state.list_rooms(self.session_id)
+    }
+
+    async fn create_room(
+        self,
+        _: context::Context,
+        name: String,
+        rules: RulesPreset,
+    ) -> Result<RoomId, CreateRoomError> {
+        self.state.create_room(self.session_id, name, rules)
+    }
+
+    async fn join_room(
+        self,
+        _: context::Context,
+        room_id: RoomId,
+        role: RoomRole,
+    ) -> Result<JoinedRoom, JoinRoomError> {
+        self.state.join_room(self.session_id, room_id, role)
+    }
+
+    async fn send_chat_message(self, _: context::Context, text: String) -> Result<(), ChatError> {
+        self.state.send_chat_message(self.session_id, text)
+    }
+
+    async fn update_game(
+        self,
+        _: context::Context,
+        snapshot: GameSnapshot,
+        events: Vec<LogEntry>,
+    ) -> Result<(), GameError> {
+        self.state.update_game(self.session_id, snapshot, events)
+    }
+
+    async fn surrender(self, _: context::Context) -> Result<(), GameError> {
+        self.state.surrender(self.session_id)
+    }
+
+    async fn vote_draw(self, _: context::Context, agree: bool) -> Result<(), GameError> {
+        self.state.vote_draw(self.session_id, agree)
+    }
+
+    async fn request_bid(self, _: context::Context, request: BidRequest) -> Result<(), GameError> {
+        self.state.request_bid(self.session_id, request)
+    }
+
+    async fn place_bid(self, _: context::Context, bid: Option<Money>) -> Result<(), GameError> {
+        self.state.place_bid(self.session_id, bid)
+    }
+
+    async fn rematch(self, _: context::Context) -> Result<RoomId, RematchError> {
+        self.state.rematch(self.session_id)
+    }
+
+    async fn start_countdown(self, _: context::Context) -> Result<Countdown, CountdownError> {
+        self.state.start_countdown(self.session_id)
+    }
+
+    async fn stop_countdown(self, _: context::Context) -> Result<(), CountdownError> {
+        self.state.stop_countdown(self.session_id)
     }
 }
 
 impl BackendServer {
-    pub fn new(player_client: FrontendClient) -> BackendServer {
-        BackendServer { player_client }
+    pub fn new(state: ServerState, session_id: SessionId) -> BackendServer {
+        BackendServer { state, session_id }
     }
 }
@@ -0,0 +1,1311 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    future::Future,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant, SystemTime},
+};
+
+use any_spawner::Executor;
+use futures::{future, TryFutureExt};
+use serde::{Deserialize, Serialize};
+use shared::{
+    chat::{self, ChatChannel, ChatError, ChatMessage, MAX_CHAT_MESSAGE_LENGTH},
+    frontend::FrontendClient,
+    game::{self, BidRequest, DrawVote, GameError, GameSnapshot, LogEntry, MAX_GAME_LOG_LENGTH},
+    money::Money,
+    player::PlayerId,
+    room::{
+        self, CreateRoomError, JoinRoomError, JoinedRoom, RematchError, RoomId, RoomInfo, RoomRole,
+        RoomStatus, RulesPreset, DEFAULT_ROOM, MAX_PLAYERS, MAX_ROOM_NAME_LENGTH,
+    },
+    timer::{self, Countdown, CountdownError, CountdownId, TimerKind, TurnTimers},
+};
+use snafu::prelude::*;
+use tarpc::{client::RpcError, context};
+
+use crate::limits::{TokenBucket, CHAT_BURST, CHAT_RATE};
+
+/// Amount of chat messages kept per room.
+const MAX_CHAT_HISTORY: usize = 200;
+/// Amount of rooms that can exist at once, including finished ones.
+const MAX_ROOMS: usize = 100;
+
+pub type SessionId = u64;
+
+/// State shared between all connections.
+#[derive(Debug, Clone, Default)]
+pub struct ServerState(Arc<Mutex<ServerStateInner>>);
+
+#[derive(Debug, Default)]
+struct ServerStateInner {
+    next_session_id: SessionId,
+    next_player_id: PlayerId,
+    next_countdown_id: CountdownId,
+    next_room_id: RoomId,
+    /// Ids of all players that ever authenticated, by their names.
+    players: HashMap<String, PlayerId>,
+    sessions: HashMap<SessionId, Session>,
+    rooms: HashMap<RoomId, Room>,
+    /// Amount of RPC requests that are being handled right now.
+    in_flight_requests: usize,
+}
+
+/// Part of the state that survives server restarts.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SavedState {
+    next_player_id: PlayerId,
+    players: HashMap<String, PlayerId>,
+    #[serde(default)]
+    rooms: HashMap<RoomId, Room>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Room {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    rules: RulesPreset,
+    chat: VecDeque<ChatMessage>,
+    /// Players that took a seat in the room, in the order they joined.
+    #[serde(default)]
+    seats: Vec<PlayerId>,
+    /// Latest state of the game, published by one of the seated players.
+    #[serde(default)]
+    game: Option<GameSnapshot>,
+    #[serde(default)]
+    game_log: VecDeque<LogEntry>,
+    #[serde(default)]
+    rematch: Option<RoomId>,
+    #[serde(default)]
+    timers: TurnTimers,
+    /// Countdown of the current player's turn, which runs until the turn changes.
+    #[serde(skip)]
+    turn_countdown: Option<RunningCountdown>,
+    /// Countdown of the current player's decision, which never outlasts the turn countdown.
+    #[serde(skip)]
+    decision_countdown: Option<RunningCountdown>,
+    /// Bid the current player is waiting for.
+    #[serde(skip)]
+    bid_request: Option<BidRequest>,
+    /// Countdown of the bidder, which is not necessarily the current player.
+    #[serde(skip)]
+    bid_countdown: Option<RunningCountdown>,
+    /// Amount of countdowns in a row each player let expire.
+    #[serde(skip)]
+    timeouts: HashMap<PlayerId, u32>,
+    /// Players that agree to end the game in a draw.
+    #[serde(skip)]
+    draw_votes: Vec<PlayerId>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct RunningCountdown {
+    countdown: Countdown,
+    deadline: Instant,
+    /// The countdown was restarted after the previous one expired, so stopping it does not mean
+    /// that the player acted in time.
+    is_overtime: bool,
+}
+
+/// Change of the room's countdown, that everyone in the room needs to hear about.
+#[derive(Debug, Clone, Copy)]
+enum CountdownChange {
+    Started(Countdown),
+    Stopped(CountdownId),
+}
+
+/// Marks an RPC request as in flight until dropped.
+#[derive(Debug)]
+pub struct RequestGuard {
+    state: ServerState,
+}
+
+#[derive(Debug)]
+struct Session {
+    client: FrontendClient,
+    /// Id and name of the player, once the session is authenticated.
+    player: Option<(PlayerId, String)>,
+    room_id: Option<RoomId>,
+    /// The session joined the room read-only.
+    spectating: bool,
+    /// The session receives updates of all rooms.
+    in_lobby: bool,
+    chat_bucket: TokenBucket,
+}
+
+impl Room {
+    fn new(name: String, rules: RulesPreset) -> Self {
+        Self {
+            name,
+            rules,
+            timers: rules.timers(),
+            ..Default::default()
+        }
+    }
+
+    fn status(&self) -> RoomStatus {
+        match &self.game {
+            None => RoomStatus::Open,
+            Some(game) if game.is_finished() => RoomStatus::Finished,
+            Some(_) => RoomStatus::Running,
+        }
+    }
+
+    /// Replaces the running countdown of the same kind with a new one.
+    fn start_countdown(
+        &mut self,
+        next_countdown_id: &mut CountdownId,
+        player_id: PlayerId,
+        kind: TimerKind,
+        duration: Duration,
+        is_overtime: bool,
+    ) -> Countdown {
+        let countdown = Countdown {
+            id: *next_countdown_id,
+            player_id,
+            kind,
+            duration,
+        };
+        *next_countdown_id += 1;
+        let running = Some(RunningCountdown {
+            countdown,
+            deadline: Instant::now() + duration,
+            is_overtime,
+        });
+        match kind {
+            TimerKind::Turn => self.turn_countdown = running,
+            TimerKind::Decision => self.decision_countdown = running,
+            TimerKind::Bid => self.bid_countdown = running,
+        }
+        countdown
+    }
+
+    /// Stops the running countdowns, so that they do not expire, and drops the bid request.
+    fn stop_countdowns(&mut self) -> Vec<CountdownChange> {
+        self.bid_request = None;
+        [
+            self.bid_countdown.take(),
+            self.decision_countdown.take(),
+            self.turn_countdown.take(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|running| CountdownChange::Stopped(running.countdown.id))
+        .collect()
+    }
+
+    /// Starts the countdown of the current player's turn, if it just began, or stops the running
+    /// countdowns, if the game is over.
+    fn update_turn_countdown(
+        &mut self,
+        next_countdown_id: &mut CountdownId,
+        is_new_turn: bool,
+    ) -> Vec<CountdownChange> {
+        let Some(game) = &self.game else {
+            return Vec::new();
+        };
+        if game.is_finished() {
+            return self.stop_countdowns();
+        }
+        if !is_new_turn {
+            return Vec::new();
+        }
+
+        let current_player = game.current_player;
+        let ended_turn = self.turn_countdown;
+        let mut changes = self.stop_countdowns();
+        // The player finished their turn in time.
+        if let Some(ended_turn) = ended_turn.filter(|running| !running.is_overtime) {
+            self.timeouts.remove(&ended_turn.countdown.player_id);
+        }
+        let duration = self.timers.turn;
+        let countdown = self.start_countdown(
+            next_countdown_id,
+            current_player,
+            TimerKind::Turn,
+            duration,
+            false,
+        );
+        changes.push(CountdownChange::Started(countdown));
+        changes
+    }
+
+    /// Running countdowns, with the time they have left.
+    fn countdowns(&self) -> Vec<Countdown> {
+        let now = Instant::now();
+        [
+            self.turn_countdown,
+            self.decision_countdown,
+            self.bid_countdown,
+        ]
+        .into_iter()
+        .flatten()
+        .map(|running| Countdown {
+            duration: running.deadline.saturating_duration_since(now),
+            ..running.countdown
+        })
+        .collect()
+    }
+
+    fn info(&self, id: RoomId) -> RoomInfo {
+        RoomInfo {
+            id,
+            // Rooms saved before they had names.
+            name: if self.name.is_empty() {
+                format!("Room {id}")
+            } else {
+                self.name.clone()
+            },
+            players: self.seats.len(),
+            rules: self.rules,
+            status: self.status(),
+        }
+    }
+}
+
+impl Session {
+    fn player_id(&self) -> Option<PlayerId> {
+        self.player.as_ref().map(|(player_id, _)| *player_id)
+    }
+
+    fn is_spectating(&self, room_id: RoomId) -> bool {
+        self.room_id == Some(room_id) && self.spectating
+    }
+}
+
+impl ServerState {
+    pub fn restore(saved: SavedState) -> Self {
+        let SavedState {
+            next_player_id,
+            players,
+            mut rooms,
+        } = saved;
+        rooms
+            .entry(DEFAULT_ROOM)
+            .or_insert_with(|| Room::new("Main room".to_owned(), RulesPreset::default()));
+        let next_room_id = rooms
+            .keys()
+            .max()
+            .map_or(DEFAULT_ROOM, |room_id| room_id + 1);
+
+        // Countdowns do not survive restarts, so the restored games would wait forever.
+        let mut next_countdown_id = CountdownId::default();
+        let countdowns = rooms
+            .iter_mut()
+            .map(|(room_id, room)| {
+                let changes = room.update_turn_countdown(&mut next_countdown_id, true);
+                (*room_id, changes)
+            })
+            .collect::<Vec<_>>();
+
+        let state = Self(Arc::new(Mutex::new(ServerStateInner {
+            next_player_id,
+            next_room_id,
+            next_countdown_id,
+            players,
+            rooms,
+            ..Default::default()
+        })));
+        for (room_id, changes) in countdowns {
+            state.announce_countdowns(room_id, changes);
+        }
+        state
+    }
+
+    pub fn save(&self) -> SavedState {
+        let inner = self.lock();
+        SavedState {
+            next_player_id: inner.next_player_id,
+            players: inner.players.clone(),
+            rooms: inner.rooms.clone(),
+        }
+    }
+
+    pub fn connect(&self, client: FrontendClient) -> SessionId {
+        let mut inner = self.lock();
+        let session_id = inner.next_session_id;
+        inner.next_session_id += 1;
+        inner.sessions.insert(
+            session_id,
+            Session {
+                client,
+                player: None,
+                room_id: None,
+                spectating: false,
+                in_lobby: false,
+                chat_bucket: TokenBucket::new(CHAT_BURST, CHAT_RATE),
+            },
+        );
+        session_id
+    }
+
+    pub fn disconnect(&self, session_id: SessionId) {
+        let session = self.lock().sessions.remove(&session_id);
+        if let Some(player_id) = session.and_then(|session| session.player_id()) {
+            self.broadcast_latency(player_id, None);
+        }
+    }
+
+    pub fn authenticate(&self, session_id: SessionId, name: String) -> PlayerId {
+        let mut inner = self.lock();
+        let ServerStateInner {
+            next_player_id,
+            players,
+            sessions,
+            ..
+        } = &mut *inner;
+
+        let player_id = *players.entry(name.clone()).or_insert_with(|| {
+            let player_id = *next_player_id;
+            *next_player_id += 1;
+            player_id
+        });
+
+        if let Some(session) = sessions.get_mut(&session_id) {
+            session.player = Some((player_id, name));
+        }
+
+        player_id
+    }
+
+    pub fn update_latency(&self, session_id: SessionId, latency: Duration) {
+        let player_id = self
+            .lock()
+            .sessions
+            .get(&session_id)
+            .and_then(Session::player_id);
+
+        // Latency of anonymous sessions is of no interest to anyone.
+        if let Some(player_id) = player_id {
+            self.broadcast_latency(player_id, Some(latency));
+        }
+    }
+
+    /// Moves the session into the lobby, returning all rooms.
+    pub fn list_rooms(&self, session_id: SessionId) -> Vec<RoomInfo> {
+        let mut inner = self.lock();
+        if let Some(session) = inner.sessions.get_mut(&session_id) {
+            session.room_id = None;
+            session.spectating = false;
+            session.in_lobby = true;
+        }
+
+        let mut rooms = inner
+            .rooms
+            .iter()
+            .map(|(room_id, room)| room.info(*room_id))
+            .collect::<Vec<_>>();
+        rooms.sort_by_key(|room| room.id);
+        rooms
+    }
+
+    pub fn create_room(
+        &self,
+        session_id: SessionId,
+        name: String,
+        rules: RulesPreset,
+    ) -> Result<RoomId, CreateRoomError> {
+        let name = name.trim();
+        ensure!(!name.is_empty(), room::create_room_error::EmptyNameSnafu);
+        ensure!(
+            name.chars().count() <= MAX_ROOM_NAME_LENGTH,
+            room::create_room_error::NameTooLongSnafu
+        );
+
+        let mut inner = self.lock();
+        ensure!(
+            inner
+                .sessions
+                .get(&session_id)
+                .is_some_and(|session| session.player.is_some()),
+            room::create_room_error::NotAuthenticatedSnafu
+        );
+        ensure!(
+            inner.rooms.len() < MAX_ROOMS,
+            room::create_room_error::TooManyRoomsSnafu
+        );
+
+        let room_id = inner.next_room_id;
+        inner.next_room_id += 1;
+        let room = Room::new(name.to_owned(), rules);
+        let info = room.info(room_id);
+        inner.rooms.insert(room_id, room);
+        drop(inner);
+
+        self.broadcast_room(info);
+        Ok(room_id)
+    }
+
+    /// Moves the session into the room, taking a seat unless it joins as a spectator.
+    /// Returns everything the session needs to catch up with the room.
+    pub fn join_room(
+        &self,
+        session_id: SessionId,
+        room_id: RoomId,
+        role: RoomRole,
+    ) -> Result<JoinedRoom, JoinRoomError> {
+        let mut inner = self.lock();
+        let ServerStateInner {
+            players,
+            sessions,
+            rooms,
+            ..
+        } = &mut *inner;
+
+        let session = sessions
+            .get_mut(&session_id)
+            .context(room::NotAuthenticatedSnafu)?;
+        let player_id = session.player_id().context(room::NotAuthenticatedSnafu)?;
+        let room = rooms
+            .get_mut(&room_id)
+            .context(room::RoomNotFoundSnafu { room_id })?;
+
+        let spectating = role == RoomRole::Spectator;
+        let seats = room.seats.len();
+        if spectating {
+            // Their seat is what lets them take their turns.
+            let is_playing = room.game.as_ref().is_some_and(|game| {
+                !game.is_finished() && game.players_left().any(|id| id == player_id)
+            });
+            ensure!(!is_playing, room::StillPlayingSnafu);
+            room.seats.retain(|seated| *seated != player_id);
+        } else if !room.seats.contains(&player_id) {
+            ensure!(room.seats.len() < MAX_PLAYERS, room::RoomFullSnafu);
+            room.seats.push(player_id);
+        }
+        session.room_id = Some(room_id);
+        session.spectating = spectating;
+        session.in_lobby = false;
+
+        let info = (room.seats.len() != seats).then(|| room.info(room_id));
+        let joined = JoinedRoom {
+            rules: room.rules,
+            chat: room
+                .chat
+                .iter()
+                .filter(|message| spectating || message.channel == ChatChannel::Players)
+                .cloned()
+                .collect(),
+            seats: room
+                .seats
+                .iter()
+                .filter_map(|seated| {
+                    players
+                        .iter()
+                        .find(|(_, player_id)| *player_id == seated)
+                        .map(|(name, player_id)| (*player_id, name.clone()))
+                })
+                .collect(),
+            game: room.game.clone(),
+            game_log: room.game_log.iter().cloned().collect(),
+            rematch: room.rematch,
+            countdowns: room.countdowns(),
+        };
+        drop(inner);
+
+        if let Some(info) = info {
+            self.broadcast_room(info);
+        }
+        Ok(joined)
+    }
+
+    /// Saves the message into the chat history of the session's room and sends it to everyone
+    /// in the room.
+    pub fn send_chat_message(&self, session_id: SessionId, text: String) -> Result<(), ChatError> {
+        let text = text.trim();
+        ensure!(!text.is_empty(), chat::EmptyMessageSnafu);
+        ensure!(
+            text.chars().count() <= MAX_CHAT_MESSAGE_LENGTH,
+            chat::MessageTooLongSnafu
+        );
+
+        let mut inner = self.lock();
+        let ServerStateInner {
+            sessions, rooms, ..
+        } = &mut *inner;
+
+        let session = sessions
+            .get_mut(&session_id)
+            .context(chat::NotAuthenticatedSnafu)?;
+        let (sender_id, sender_name) = session
+            .player
+            .clone()
+            .context(chat::NotAuthenticatedSnafu)?;
+        let room_id = session.room_id.context(chat::NotInRoomSnafu)?;
+        let room = rooms.get_mut(&room_id).context(chat::NotInRoomSnafu)?;
+        ensure!(session.chat_bucket.try_take(), chat::RateLimitedSnafu);
+
+        let channel = if session.spectating {
+            ChatChannel::Spectators
+        } else {
+            ChatChannel::Players
+        };
+        let message = ChatMessage {
+            sender_id,
+            sender_name,
+            channel,
+            text: text.to_owned(),
+            sent_at: unix_millis(),
+        };
+        room.chat.push_back(message.clone());
+        if room.chat.len() > MAX_CHAT_HISTORY {
+            room.chat.pop_front();
+        }
+        drop(inner);
+
+        self.broadcast(
+            |session| match channel {
+                ChatChannel::Players => session.room_id == Some(room_id),
+                ChatChannel::Spectators => session.is_spectating(room_id),
+            },
+            move |client| {
+                let message = message.clone();
+                async move {
+                    client
+                        .chat_message(context::current(), room_id, message)
+                        .await
+                }
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Saves the state of the game in the session's room and sends it to everyone else in the
+    /// room. Only the player whose turn it is runs the game, so only they can change it.
+    pub fn update_game(
+        &self,
+        session_id: SessionId,
+        mut snapshot: GameSnapshot,
+        events: Vec<LogEntry>,
+    ) -> Result<(), GameError> {
+        let mut inner = self.lock();
+        let ServerStateInner {
+            next_countdown_id,
+            sessions,
+            rooms,
+            ..
+        } = &mut *inner;
+
+        let session = sessions
+            .get(&session_id)
+            .context(game::NotAuthenticatedSnafu)?;
+        let player_id = session.player_id().context(game::NotAuthenticatedSnafu)?;
+        let room_id = session.room_id.context(game::NotInRoomSnafu)?;
+        let room = rooms.get_mut(&room_id).context(game::NotInRoomSnafu)?;
+        ensure!(
+            !session.spectating && room.seats.contains(&player_id),
+            game::NotSeatedSnafu
+        );
+        match &room.game {
+            Some(game) => {
+                ensure!(
+                    game.players_left().any(|id| id == player_id),
+                    game::NotPlayingSnafu
+                );
+                ensure!(game.current_player == player_id, game::NotYourTurnSnafu);
+                ensure!(
+                    snapshot.revision > game.revision && snapshot.current_turn >= game.current_turn,
+                    game::StaleGameSnafu
+                );
+                ensure!(snapshot.follows(game), game::IllegalGameSnafu);
+            }
+            // The game is started by the player who took the first seat.
+            None => ensure!(
+                room.seats.first() == Some(&player_id),
+                game::NotYourTurnSnafu
+            ),
+        }
+
+        // Surrenders, forfeits and draws are decided here, and may have happened after the
+        // publisher's client made the snapshot.
+        if let Some(game) = &room.game {
+            snapshot.is_drawn |= game.is_drawn;
+            for player in game.players.iter().filter(|player| player.has_lost) {
+                snapshot.eliminate(player.id);
+            }
+        }
+
+        let status = room.status();
+        let is_new_turn = room.game.as_ref().is_none_or(|game| {
+            (game.current_turn, game.current_player)
+                != (snapshot.current_turn, snapshot.current_player)
+        });
+        room.game = Some(snapshot.clone());
+        room.game_log.extend(events.iter().cloned());
+        let overflow = room.game_log.len().saturating_sub(MAX_GAME_LOG_LENGTH);
+        room.game_log.drain(..overflow);
+        let countdowns = room.update_turn_countdown(next_countdown_id, is_new_turn);
+        let info = (room.status() != status).then(|| room.info(room_id));
+        drop(inner);
+
+        if let Some(info) = info {
+            self.broadcast_room(info);
+        }
+        self.announce_countdowns(room_id, countdowns);
+
+        self.broadcast(
+            |session| {
+                session.room_id == Some(room_id)
+                    && (session.spectating || session.player_id() != Some(player_id))
+            },
+            move |client| {
+                let snapshot = snapshot.clone();
+                let events = events.clone();
+                async move {
+                    client
+                        .game_updated(context::current(), room_id, snapshot, events)
+                        .await
+                }
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Takes the player out of the latest state of the game, so that they cannot surrender twice
+    /// and their turn passes on even if they left, and tells everyone in the room about it.
+    pub fn surrender(&self, session_id: SessionId) -> Result<(), GameError> {
+        let mut inner = self.lock();
+        let ServerStateInner {
+            sessions, rooms, ..
+        } = &mut *inner;
+
+        let (player_id, room_id, _) = playing(sessions, rooms, session_id)?;
+        self.eliminate(inner, room_id, player_id);
+
+        Ok(())
+    }
+
+    /// Counts the player's vote on a draw, ending the game once everyone left in it agrees.
+    pub fn vote_draw(&self, session_id: SessionId, agree: bool) -> Result<(), GameError> {
+        let mut inner = self.lock();
+        let ServerStateInner {
+            next_countdown_id,
+            sessions,
+            rooms,
+            ..
+        } = &mut *inner;
+
+        let (player_id, room_id, room) = playing(sessions, rooms, session_id)?;
+        if !agree {
+            room.draw_votes.clear();
+        } else if !room.draw_votes.contains(&player_id) {
+            room.draw_votes.push(player_id);
+        }
+
+        let status = room.status();
+        let is_agreed = room.game.as_mut().is_some_and(|game| {
+            let is_agreed = game
+                .players_left()
+                .all(|player_id| room.draw_votes.contains(&player_id));
+            game.is_drawn |= is_agreed;
+            is_agreed
+        });
+        let vote = DrawVote {
+            player_id,
+            agree,
+            votes: room.draw_votes.clone(),
+            is_agreed,
+        };
+        if is_agreed {
+            room.draw_votes.clear();
+        }
+        let countdowns = room.update_turn_countdown(next_countdown_id, false);
+        let info = (room.status() != status).then(|| room.info(room_id));
+        drop(inner);
+
+        if let Some(info) = info {
+            self.broadcast_room(info);
+        }
+        self.announce_countdowns(room_id, countdowns);
+        self.broadcast(
+            |session| session.room_id == Some(room_id),
+            move |client| {
+                let vote = vote.clone();
+                async move {
+                    client
+                        .draw_vote_updated(context::current(), room_id, vote)
+                        .await
+                }
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Asks the bidder to bid in the auction the current player runs, replacing the request that
+    /// is pending already. The bidder gets their own countdown, and the current player's turn is
+    /// extended by as much, so that waiting for the bidder does not use up the turn.
+    pub fn request_bid(&self, session_id: SessionId, request: BidRequest) -> Result<(), GameError> {
+        let mut inner = self.lock();
+        let ServerStateInner {
+            next_countdown_id,
+            sessions,
+            rooms,
+            ..
+        } = &mut *inner;
+
+        let (player_id, room_id, room) = playing(sessions, rooms, session_id)?;
+        let game = room.game.as_ref().context(game::NoGameSnafu)?;
+        ensure!(game.current_player == player_id, game::NotYourTurnSnafu);
+        ensure!(
+            game.players_left().any(|left| left == request.bidder),
+            game::UnknownBidderSnafu
+        );
+        ensure!(request.min_bid <= request.max_bid, game::InvalidBidSnafu);
+
+        let mut changes = Vec::new();
+        if let Some(replaced) = room.bid_countdown.take() {
+            changes.push(CountdownChange::Stopped(replaced.countdown.id));
+        }
+        let duration = room.timers.duration(TimerKind::Bid);
+        if let Some(turn) = &mut room.turn_countdown {
+            turn.deadline += duration;
+            changes.push(CountdownChange::Started(Countdown {
+                duration: turn.deadline.saturating_duration_since(Instant::now()),
+                ..turn.countdown
+            }));
+        }
+        let countdown = room.start_countdown(
+            next_countdown_id,
+            request.bidder,
+            TimerKind::Bid,
+            duration,
+            false,
+        );
+        changes.push(CountdownChange::Started(countdown));
+        let bidder = request.bidder;
+        room.bid_request = Some(request.clone());
+        drop(inner);
+
+        self.broadcast(
+            |session| {
+                session.room_id == Some(room_id)
+                    && !session.spectating
+                    && session.player_id() == Some(bidder)
+            },
+            move |client| {
+                let request = request.clone();
+                async move {
+                    client
+                        .bid_requested(context::current(), room_id, request)
+                        .await
+                }
+            },
+        );
+        self.announce_countdowns(room_id, changes);
+
+        Ok(())
+    }
+
+    /// Passes the bidder's answer on to the player running the auction. An answer to a request
+    /// that was replaced, or whose countdown expired, is ignored, as the bidder passed already.
+    pub fn place_bid(&self, session_id: SessionId, bid: Option<Money>) -> Result<(), GameError> {
+        let mut inner = self.lock();
+        let ServerStateInner {
+            sessions, rooms, ..
+        } = &mut *inner;
+
+        let (player_id, room_id, room) = playing(sessions, rooms, session_id)?;
+        let Some(request) = room
+            .bid_request
+            .as_ref()
+            .filter(|request| request.bidder == player_id)
+        else {
+            return Ok(());
+        };
+        ensure!(
+            bid.is_none_or(|bid| (request.min_bid..=request.max_bid).contains(&bid)),
+            game::InvalidBidSnafu
+        );
+        let auctioneer = room
+            .game
+            .as_ref()
+            .context(game::NoGameSnafu)?
+            .current_player;
+        room.bid_request = None;
+        let countdown = room.bid_countdown.take();
+        drop(inner);
+
+        if let Some(countdown) = countdown {
+            self.announce_countdown(room_id, CountdownChange::Stopped(countdown.countdown.id));
+        }
+        self.send_bid(room_id, auctioneer, player_id, bid);
+
+        Ok(())
+    }
+
+    pub fn rematch(&self, session_id: SessionId) -> Result<RoomId, RematchError> {
+        let mut inner = self.lock();
+        let ServerStateInner {
+            next_room_id,
+            sessions,
+            rooms,
+            ..
+        } = &mut *inner;
+
+        let session = sessions
+            .get(&session_id)
+            .context(room::rematch_error::NotAuthenticatedSnafu)?;
+        let player_id = session
+            .player_id()
+            .context(room::rematch_error::NotAuthenticatedSnafu)?;
+        let room_id = session
+            .room_id
+            .context(room::rematch_error::NotInRoomSnafu)?;
+        let room = rooms
+            .get(&room_id)
+            .context(room::rematch_error::NotInRoomSnafu)?;
+        ensure!(
+            !session.spectating && room.seats.contains(&player_id),
+            room::rematch_error::NotSeatedSnafu
+        );
+        if let Some(rematch_id) = room.rematch {
+            return Ok(rematch_id);
+        }
+        ensure!(
+            rooms.len() < MAX_ROOMS,
+            room::rematch_error::TooManyRoomsSnafu
+        );
+
+        let rematch_id = *next_room_id;
+        *next_room_id += 1;
+        let rematch = Room {
+            seats: room.seats.clone(),
+            // Keeps the original name so that repeated rematches do not outgrow
+            // `MAX_ROOM_NAME_LENGTH`.
+            ..Room::new(room.info(room_id).name, room.rules)
+        };
+        let info = rematch.info(rematch_id);
+        rooms.insert(rematch_id, rematch);
+        if let Some(room) = rooms.get_mut(&room_id) {
+            room.rematch = Some(rematch_id);
+        }
+        drop(inner);
+
+        self.broadcast_room(info);
+        self.broadcast(
+            |session| session.room_id == Some(room_id),
+            move |client| async move {
+                client
+                    .rematch_created(context::current(), room_id, rematch_id)
+                    .await
+            },
+        );
+
+        Ok(rematch_id)
+    }
+
+    /// Starts the countdown of a decision of the player whose turn it is. A decision countdown that
+    /// is running already is replaced, but the player never gets more time than it had left, nor
+    /// more than is left of their turn.
+    pub fn start_countdown(&self, session_id: SessionId) -> Result<Countdown, CountdownError> {
+        let mut inner = self.lock();
+        let ServerStateInner {
+            next_countdown_id,
+            sessions,
+            rooms,
+            ..
+        } = &mut *inner;
+
+        let session = sessions
+            .get(&session_id)
+            .context(timer::NotAuthenticatedSnafu)?;
+        let player_id = session.player_id().context(timer::NotAuthenticatedSnafu)?;
+        let room_id = session.room_id.context(timer::NotInRoomSnafu)?;
+        ensure!(!session.spectating, timer::SpectatingSnafu);
+        let room = rooms.get_mut(&room_id).context(timer::NotInRoomSnafu)?;
+        let game = room.game.as_ref().context(timer::NoGameSnafu)?;
+        ensure!(
+            !game.is_finished() && game.current_player == player_id,
+            timer::NotYourTurnSnafu
+        );
+
+        let mut duration = room.timers.decision;
+        let mut is_overtime = false;
+        let now = Instant::now();
+        for running in [room.turn_countdown, room.decision_countdown]
+            .into_iter()
+            .flatten()
+        {
+            ensure!(
+                running.countdown.player_id == player_id,
+                timer::NotYourCountdownSnafu
+            );
+            duration = duration.min(running.deadline.saturating_duration_since(now));
+            is_overtime |= running.is_overtime;
+        }
+        let countdown = room.start_countdown(
+            next_countdown_id,
+            player_id,
+            TimerKind::Decision,
+            duration,
+            is_overtime,
+        );
+        drop(inner);
+
+        self.announce_countdown(room_id, CountdownChange::Started(countdown));
+
+        Ok(countdown)
+    }
+
+    /// Stops the countdown of the player's decision. The turn countdown keeps running until the
+    /// turn changes.
+    pub fn stop_countdown(&self, session_id: SessionId) -> Result<(), CountdownError> {
+        let mut inner = self.lock();
+        let ServerStateInner {
+            sessions, rooms, ..
+        } = &mut *inner;
+
+        let session = sessions
+            .get(&session_id)
+            .context(timer::NotAuthenticatedSnafu)?;
+        let player_id = session.player_id().context(timer::NotAuthenticatedSnafu)?;
+        let room_id = session.room_id.context(timer::NotInRoomSnafu)?;
+        ensure!(!session.spectating, timer::SpectatingSnafu);
+        let room = rooms.get_mut(&room_id).context(timer::NotInRoomSnafu)?;
+
+        // Countdown that already expired does not count as acting in time.
+        let Some(running) = room.decision_countdown else {
+            return Ok(());
+        };
+        ensure!(
+            running.countdown.player_id == player_id,
+            timer::NotYourCountdownSnafu
+        );
+        room.decision_countdown = None;
+        if !running.is_overtime {
+            room.timeouts.remove(&player_id);
+        }
+        drop(inner);
+
+        self.announce_countdown(room_id, CountdownChange::Stopped(running.countdown.id));
+
+        Ok(())
+    }
+
+    pub fn start_request(&self) -> RequestGuard {
+        self.lock().in_flight_requests += 1;
+        RequestGuard {
+            state: self.clone(),
+        }
+    }
+
+    pub fn in_flight_requests(&self) -> usize {
+        self.lock().in_flight_requests
+    }
+
+    /// Tells every client that the server is going down, waiting until they receive it.
+    pub async fn notify_restarting(&self) {
+        let requests = self.clients(|_| true).into_iter().map(|client| {
+            async move { client.server_restarting(context::current()).await }
+                .unwrap_or_else(|error| tracing::warn!("Failed to notify client: {}", error))
+        });
+        future::join_all(requests).await;
+    }
+
+    /// Restarts the expired countdown, so that the player is timed out again if their client does
+    /// not take the default action, until they run out of timeouts and are taken out of the game.
+    fn expire_countdown(&self, room_id: RoomId, countdown_id: CountdownId) {
+        let mut inner = self.lock();
+        let ServerStateInner {
+            next_countdown_id,
+            rooms,
+            ..
+        } = &mut *inner;
+        let Some(room) = rooms.get_mut(&room_id) else {
+            return;
+        };
+
+        let is_expired = |running: &RunningCountdown| running.countdown.id == countdown_id;
+        let now = Instant::now();
+        let is_extended = [
+            room.turn_countdown,
+            room.decision_countdown,
+            room.bid_countdown,
+        ]
+        .into_iter()
+        .flatten()
+        .any(|running| is_expired(&running) && running.deadline > now);
+        if is_extended {
+            // The countdown expires once its new deadline comes.
+            return;
+        }
+
+        // Letting the bid countdown expire passes, without counting as a timeout.
+        if let Some(bid) = room.bid_countdown.take_if(|running| is_expired(running)) {
+            let request = room.bid_request.take();
+            let auctioneer = room.game.as_ref().map(|game| game.current_player);
+            drop(inner);
+
+            let countdown = bid.countdown;
+            self.broadcast(
+                |session| session.room_id == Some(room_id),
+                move |client| async move {
+                    client
+                        .countdown_expired(context::current(), room_id, countdown, false)
+                        .await
+                },
+            );
+            if let (Some(request), Some(auctioneer)) = (request, auctioneer) {
+                self.send_bid(room_id, auctioneer, request.bidder, None);
+            }
+            return;
+        }
+
+        let mut changes = Vec::new();
+        let expired = if let Some(turn) = room.turn_countdown.take_if(|running| is_expired(running))
+        {
+            // The decision ends with the turn, and its default action is taken below.
+            if let Some(decision) = room.decision_countdown.take() {
+                changes.push(CountdownChange::Stopped(decision.countdown.id));
+            }
+            turn
+        } else {
+            // A decision cut short by the end of the turn expires together with the turn.
+            let turn_deadline = room.turn_countdown.map(|turn| turn.deadline);
+            let Some(decision) = room.decision_countdown.take_if(|running| {
+                is_expired(running)
+                    && turn_deadline.is_none_or(|deadline| running.deadline < deadline)
+            }) else {
+                // The countdown was stopped or replaced in time.
+                return;
+            };
+            decision
+        };
+        let countdown = expired.countdown;
+
+        let timeouts = room.timeouts.entry(countdown.player_id).or_default();
+        *timeouts += 1;
+        let forfeit = *timeouts >= room.timers.max_timeouts;
+        if forfeit {
+            room.timeouts.remove(&countdown.player_id);
+        } else {
+            let turn_left = room
+                .turn_countdown
+                .map(|turn| turn.deadline.saturating_duration_since(now));
+            let duration = match countdown.kind {
+                TimerKind::Turn | TimerKind::Bid => room.timers.duration(countdown.kind),
+                TimerKind::Decision => turn_left.map_or(room.timers.decision, |turn_left| {
+                    room.timers.decision.min(turn_left)
+                }),
+            };
+            let restarted = room.start_countdown(
+                next_countdown_id,
+                countdown.player_id,
+                countdown.kind,
+                duration,
+                true,
+            );
+            changes.push(CountdownChange::Started(restarted));
+        }
+
+        if forfeit {
+            self.eliminate(inner, room_id, countdown.player_id);
+        } else {
+            drop(inner);
+        }
+
+        self.broadcast(
+            |session| session.room_id == Some(room_id),
+            move |client| async move {
+                client
+                    .countdown_expired(context::current(), room_id, countdown, forfeit)
+                    .await
+            },
+        );
+        self.announce_countdowns(room_id, changes);
+    }
+
+    /// Takes the player out of the room's game, passing their turn on, and tells everyone in the
+    /// room about it.
+    fn eliminate(
+        &self,
+        mut inner: MutexGuard<'_, ServerStateInner>,
+        room_id: RoomId,
+        player_id: PlayerId,
+    ) {
+        let ServerStateInner {
+            next_countdown_id,
+            rooms,
+            ..
+        } = &mut *inner;
+        let Some(room) = rooms.get_mut(&room_id) else {
+            return;
+        };
+        let Some(game) = &mut room.game else {
+            return;
+        };
+
+        let current_player = game.current_player;
+        game.eliminate(player_id);
+        let is_new_turn = game.current_player != current_player;
+        // The offer was made to a different set of players.
+        room.draw_votes.clear();
+        let countdowns = room.update_turn_countdown(next_countdown_id, is_new_turn);
+        let info = room.info(room_id);
+        drop(inner);
+
+        self.broadcast_room(info);
+        self.broadcast(
+            |session| session.room_id == Some(room_id),
+            move |client| async move {
+                client
+                    .player_surrendered(context::current(), room_id, player_id)
+                    .await
+            },
+        );
+        self.announce_countdowns(room_id, countdowns);
+    }
+
+    /// Tells the player running the auction about the bidder's answer.
+    fn send_bid(
+        &self,
+        room_id: RoomId,
+        auctioneer: PlayerId,
+        bidder: PlayerId,
+        bid: Option<Money>,
+    ) {
+        self.broadcast(
+            |session| {
+                session.room_id == Some(room_id)
+                    && !session.spectating
+                    && session.player_id() == Some(auctioneer)
+            },
+            move |client| async move {
+                client
+                    .bid_placed(context::current(), room_id, bidder, bid)
+                    .await
+            },
+        );
+    }
+
+    fn announce_countdowns(&self, room_id: RoomId, changes: Vec<CountdownChange>) {
+        for change in changes {
+            self.announce_countdown(room_id, change);
+        }
+    }
+
+    /// Tells everyone in the room about the change of the countdown, and expires a started
+    /// countdown once its time is up.
+    fn announce_countdown(&self, room_id: RoomId, change: CountdownChange) {
+        match change {
+            CountdownChange::Started(countdown) => {
+                self.broadcast(
+                    |session| session.room_id == Some(room_id),
+                    move |client| async move {
+                        client
+                            .countdown_started(context::current(), room_id, countdown)
+                            .await
+                    },
+                );
+
+                let state = self.clone();
+                Executor::spawn(async move {
+                    tokio::time::sleep(countdown.duration).await;
+                    state.expire_countdown(room_id, countdown.id);
+                });
+            }
+            CountdownChange::Stopped(countdown_id) => self.broadcast(
+                |session| session.room_id == Some(room_id),
+                move |client| async move {
+                    client
+                        .countdown_stopped(context::current(), room_id, countdown_id)
+                        .await
+                },
+            ),
+        }
+    }
+
+    fn broadcast_room(&self, room: RoomInfo) {
+        self.broadcast(
+            |session| session.in_lobby,
+            move |client| {
+                let room = room.clone();
+                async move { client.room_updated(context::current(), room).await }
+            },
+        );
+    }
+
+    fn broadcast_latency(&self, player_id: PlayerId, latency: Option<Duration>) {
+        self.broadcast(
+            |_| true,
+            move |client| async move {
+                client
+                    .latency_updated(context::current(), player_id, latency)
+                    .await
+            },
+        );
+    }
+
+    /// Sends a request to every connected client of matching sessions, without waiting for
+    /// responses.
+    fn broadcast<F, Fut>(&self, filter: impl Fn(&Session) -> bool, request: F)
+    where
+        F: Fn(FrontendClient) -> Fut,
+        Fut: Future<Output = Result<(), RpcError>> + Send + 'static,
+    {
+        for client in self.clients(filter) {
+            Executor::spawn(
+                request(client)
+                    .unwrap_or_else(|error| tracing::warn!("Failed to notify client: {}", error)),
+            );
+        }
+    }
+
+    fn clients(&self, filter: impl Fn(&Session) -> bool) -> Vec<FrontendClient> {
+        self.lock()
+            .sessions
+            .values()
+            .filter(|session| filter(session))
+            .map(|session| session.client.clone())
+            .collect()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ServerStateInner> {
+        self.0
+            .lock()
+            .expect("ServerState mutex should not be poisoned")
+    }
+}
+
+/// Room of the session, if the session's player is still in the room's game.
+fn playing<'a>(
+    sessions: &HashMap<SessionId, Session>,
+    rooms: &'a mut HashMap<RoomId, Room>,
+    session_id: SessionId,
+) -> Result<(PlayerId, RoomId, &'a mut Room), GameError> {
+    let session = sessions
+        .get(&session_id)
+        .context(game::NotAuthenticatedSnafu)?;
+    let player_id = session.player_id().context(game::NotAuthenticatedSnafu)?;
+    let room_id = session.room_id.context(game::NotInRoomSnafu)?;
+    let room = rooms.get_mut(&room_id).context(game::NotInRoomSnafu)?;
+    ensure!(
+        !session.spectating && room.seats.contains(&player_id),
+        game::NotSeatedSnafu
+    );
+
+    let game = room.game.as_ref().context(game::NoGameSnafu)?;
+    ensure!(!game.is_finished(), game::GameOverSnafu);
+    ensure!(
+        game.players_left().any(|left| left == player_id),
+        game::NotPlayingSnafu
+    );
+
+    Ok((player_id, room_id, room))
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+impl Drop for RequestGuard {
+    fn drop(&mut self) {
+        self.state.lock().in_flight_requests -= 1;
+    }
+}
@@ -0,0 +1,72 @@
+use std::{io, path::PathBuf};
+
+use serde::{de::DeserializeOwned, Serialize};
+use snafu::prelude::*;
+use tokio::fs;
+
+/// JSON documents saved in the data directory.
+#[derive(Debug, Clone)]
+pub struct Storage {
+    dir: PathBuf,
+}
+
+#[derive(Debug, Snafu)]
+pub enum StorageError {
+    #[snafu(display("Unable to read \"{}\": {source}", path.display()))]
+    Read { path: PathBuf, source: io::Error },
+    #[snafu(display("Unable to write \"{}\": {source}", path.display()))]
+    Write { path: PathBuf, source: io::Error },
+    #[snafu(display("Unable to parse \"{}\": {source}", path.display()))]
+    Parse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[snafu(display("Unable to serialize \"{name}\": {source}"))]
+    Serialize {
+        name: String,
+        source: serde_json::Error,
+    },
+}
+
+impl Storage {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Loads the document, or returns `None` if it was never saved.
+    pub async fn load<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>, StorageError> {
+        let path = self.path(name);
+        let content = match fs::read(&path).await {
+            Ok(content) => content,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error).context(ReadSnafu { path }),
+        };
+
+        serde_json::from_slice(&content)
+            .map(Some)
+            .context(ParseSnafu { path })
+    }
+
+    /// Saves the document, replacing the previous version atomically.
+    pub async fn save<T: Serialize>(&self, name: &str, value: &T) -> Result<(), StorageError> {
+        let content = serde_json::to_vec_pretty(value).context(SerializeSnafu { name })?;
+
+        fs::create_dir_all(&self.dir)
+            .await
+            .context(WriteSnafu { path: &self.dir })?;
+
+        // Write to a temporary file first, so that a crash cannot leave a half-written document.
+        let path = self.path(name);
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, content)
+            .await
+            .context(WriteSnafu { path: &temp_path })?;
+        fs::rename(&temp_path, &path)
+            .await
+            .context(WriteSnafu { path })
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}.json"))
+    }
+}
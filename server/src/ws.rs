@@ -1,39 +1,265 @@
-use async_stream::try_stream;
-use futures::Stream;
-use serde::{de::DeserializeOwned, Serialize};
-use tokio::net::ToSocketAddrs;
-use tokio_util::codec::{Framed, LengthDelimitedCodec};
+use std::{future::Future, io, mem, net::SocketAddr, sync::Arc};
 
-use async_tungstenite::tokio::accept_async;
+use any_spawner::Executor;
+use async_tungstenite::{
+    tokio::TokioAdapter,
+    tungstenite::{
+        handshake::derive_accept_key,
+        protocol::{Role, WebSocketConfig},
+    },
+    WebSocketStream,
+};
+use futures::{channel::mpsc, Stream, StreamExt};
+use serde::{de::DeserializeOwned, Serialize};
+use snafu::prelude::*;
+use tokio::{
+    io::AsyncWriteExt,
+    net::{TcpListener, TcpStream},
+};
+use tokio_rustls::{server::TlsStream, TlsAcceptor};
+use tokio_util::{
+    codec::{Framed, LengthDelimitedCodec},
+    either::Either,
+};
 use ws_stream_tungstenite::*;
 
+use crate::{
+    config::{Config, TlsError},
+    http::{self, RequestError},
+    limits::{ConnectionGuard, ConnectionLimiter},
+};
+
+/// Path of the WebSocket endpoint. Everything else is served from the static directory.
+const WS_PATH: &str = "/ws";
+/// Largest RPC message a client can send.
+const MAX_FRAME_LENGTH: usize = 256 * 1024;
+
+type Connection = Either<TcpStream, TlsStream<TcpStream>>;
+
+#[derive(Debug, Snafu)]
+pub enum BindError {
+    #[snafu(display("Unable to listen on {address}: {source}"))]
+    Listen {
+        address: SocketAddr,
+        source: io::Error,
+    },
+    #[snafu(transparent)]
+    Tls { source: TlsError },
+}
+
+#[derive(Debug, Snafu)]
+enum ConnectionError {
+    #[snafu(display("TLS handshake failed: {source}"))]
+    TlsHandshake { source: io::Error },
+    #[snafu(transparent)]
+    Request { source: RequestError },
+    #[snafu(display("Unable to send response: {source}"))]
+    Respond { source: io::Error },
+    #[snafu(display("Origin {origin:?} is not allowed"))]
+    OriginNotAllowed { origin: Option<String> },
+    #[snafu(display("Request to {WS_PATH} is not a WebSocket upgrade"))]
+    NotWebSocket,
+    #[snafu(display("WebSocket version {version:?} is not supported"))]
+    UnsupportedWebSocketVersion { version: Option<String> },
+}
+
+/// Accepts connections until `shutdown` resolves.
 pub async fn bind<Item, SinkItem>(
-    address: impl ToSocketAddrs,
-) -> impl Stream<
-    Item = Result<
-        impl tarpc::Transport<SinkItem, Item, TransportError = std::io::Error>,
-        std::io::Error,
+    config: &Config,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> Result<
+    impl Stream<
+        Item = (
+            impl tarpc::Transport<SinkItem, Item, TransportError = io::Error>,
+            ConnectionGuard,
+        ),
     >,
+    BindError,
 >
 where
     Item: DeserializeOwned,
     SinkItem: Serialize,
 {
+    let tls_acceptor = config.tls.as_ref().map(|tls| tls.acceptor()).transpose()?;
+    let address = config.socket_address();
+
     tracing::info!("Binding RPC TCP Session");
-    let listener = tokio::net::TcpListener::bind(address).await.unwrap();
+    let listener = TcpListener::bind(address)
+        .await
+        .context(ListenSnafu { address })?;
     tracing::info!("Bound, waiting on clients");
 
-    try_stream! {
-        loop {
-            let (stream, addr) = listener.accept().await?;
-            let ws = accept_async(stream).await.unwrap();
-            let ws_stream = WsStream::new(ws);
-            tracing::info!("New WebSocket connection: {}", addr);
-            let frame = Framed::new(ws_stream, LengthDelimitedCodec::new());
-            yield tarpc::serde_transport::new(
-                frame,
-                tokio_serde::formats::Json::<Item, SinkItem>::default(),
-            );
+    let config = Arc::new(config.clone());
+    let limiter = ConnectionLimiter::default();
+    let (sender, receiver) = mpsc::unbounded();
+
+    Executor::spawn({
+        let limiter = limiter.clone();
+        async move {
+            tokio::pin!(shutdown);
+            loop {
+                // The listener is dropped right away on shutdown, so that no more connections
+                // are accepted while the sessions wind down.
+                let accepted = tokio::select! {
+                    () = &mut shutdown => break,
+                    accepted = listener.accept() => accepted,
+                };
+                let (stream, addr) = match accepted {
+                    Ok(connection) => connection,
+                    Err(error) => {
+                        tracing::warn!("Failed to accept connection: {}", error);
+                        continue;
+                    }
+                };
+
+                let guard = match limiter.try_connect(addr.ip()) {
+                    Ok(guard) => guard,
+                    Err(error) => {
+                        tracing::warn!("Rejecting connection from {}: {}", addr, error);
+                        continue;
+                    }
+                };
+                let config = config.clone();
+                let tls_acceptor = tls_acceptor.clone();
+                let sender = sender.clone();
+
+                // Handshakes are done in separate tasks, so that a slow client cannot stall the
+                // accept loop.
+                Executor::spawn(async move {
+                    match accept_connection(stream, tls_acceptor, &config).await {
+                        Ok(Some(ws_stream)) => {
+                            tracing::info!("New WebSocket connection: {}", addr);
+                            let _ = sender.unbounded_send((ws_stream, addr, guard));
+                        }
+                        Ok(None) => {}
+                        Err(error) => {
+                            tracing::warn!("Dropping connection from {}: {}", addr, error)
+                        }
+                    }
+                });
+            }
         }
+    });
+
+    Ok(receiver.map(move |(ws_stream, addr, guard)| {
+        let codec = LengthDelimitedCodec::builder()
+            .max_frame_length(MAX_FRAME_LENGTH)
+            .new_codec();
+        let frame = Framed::new(ws_stream, codec);
+        let limiter = limiter.clone();
+        let transport = tarpc::serde_transport::new(
+            frame,
+            tokio_serde::formats::Json::<Item, SinkItem>::default(),
+        )
+        // Both oversized frames and undecodable messages are reported as `InvalidData`.
+        .inspect(move |message| {
+            if let Err(error) = message {
+                if error.kind() == io::ErrorKind::InvalidData {
+                    tracing::warn!("Malformed frame from {}: {}", addr, error);
+                    limiter.strike(addr.ip());
+                }
+            }
+        });
+        (transport, guard)
+    }))
+}
+
+/// Serves a static file, or upgrades the connection to a WebSocket if it was requested.
+async fn accept_connection(
+    stream: TcpStream,
+    tls_acceptor: Option<TlsAcceptor>,
+    config: &Config,
+) -> Result<Option<WsStream<TokioAdapter<Connection>>>, ConnectionError> {
+    let mut stream = match tls_acceptor {
+        Some(tls_acceptor) => Either::Right(
+            tls_acceptor
+                .accept(stream)
+                .await
+                .context(TlsHandshakeSnafu)?,
+        ),
+        None => Either::Left(stream),
+    };
+
+    let request = http::read_request_head(&mut stream).await?;
+
+    if request.route() != WS_PATH {
+        http::serve_static(&mut stream, &config.static_dir, &request)
+            .await
+            .context(RespondSnafu)?;
+        return Ok(None);
+    }
+
+    let origin = request.header("origin");
+    if !config.is_origin_allowed(origin) {
+        http::write_response(
+            &mut stream,
+            "403 Forbidden",
+            &[],
+            b"Origin is not allowed",
+            true,
+        )
+        .await
+        .context(RespondSnafu)?;
+        return OriginNotAllowedSnafu {
+            origin: origin.map(ToOwned::to_owned),
+        }
+        .fail();
     }
+
+    let Some(key) = request
+        .header("sec-websocket-key")
+        .filter(|_| request.is_websocket_upgrade())
+    else {
+        http::write_response(
+            &mut stream,
+            "400 Bad Request",
+            &[],
+            b"Expected WebSocket upgrade",
+            true,
+        )
+        .await
+        .context(RespondSnafu)?;
+        return NotWebSocketSnafu.fail();
+    };
+
+    let version = request.header("sec-websocket-version");
+    if version != Some(http::WEBSOCKET_VERSION) {
+        http::write_response(
+            &mut stream,
+            "426 Upgrade Required",
+            &[("Sec-WebSocket-Version", http::WEBSOCKET_VERSION)],
+            b"Unsupported WebSocket version",
+            true,
+        )
+        .await
+        .context(RespondSnafu)?;
+        return UnsupportedWebSocketVersionSnafu {
+            version: version.map(ToOwned::to_owned),
+        }
+        .fail();
+    }
+
+    let response = format!(
+        "HTTP/1.1 101 Switching Protocols\r\n\
+         Upgrade: websocket\r\n\
+         Connection: Upgrade\r\n\
+         Sec-WebSocket-Accept: {}\r\n\r\n",
+        derive_accept_key(key.as_bytes())
+    );
+    stream
+        .write_all(response.as_bytes())
+        .await
+        .context(RespondSnafu)?;
+
+    // Length prefix of the codec may end up in the same WebSocket message as the frame.
+    let max_message_size = MAX_FRAME_LENGTH + mem::size_of::<u32>();
+    let ws_config = WebSocketConfig {
+        max_message_size: Some(max_message_size),
+        max_frame_size: Some(max_message_size),
+        ..Default::default()
+    };
+    let ws =
+        WebSocketStream::from_raw_socket(TokioAdapter::new(stream), Role::Server, Some(ws_config))
+            .await;
+    Ok(Some(WsStream::new(ws)))
 }
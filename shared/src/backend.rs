@@ -5,9 +5,76 @@ use futures::{stream::AbortHandle, TryFutureExt};
 use tarpc::{client, ClientMessage, Response, Transport};
 
 mod private {
+    use crate::{
+        chat::ChatError,
+        game::{BidRequest, GameError, GameSnapshot, LogEntry},
+        money::Money,
+        player::PlayerId,
+        room::{
+            CreateRoomError, JoinRoomError, JoinedRoom, RematchError, RoomId, RoomInfo, RoomRole,
+            RulesPreset,
+        },
+        timer::{Countdown, CountdownError},
+    };
+
     #[tarpc::service]
     pub trait Backend {
-        async fn authenticate(name: String);
+        /// Returns the id of the player with the given name, registering a new one if needed.
+        async fn authenticate(name: String) -> PlayerId;
+
+        /// Returns all rooms, and starts receiving their updates until the player joins one.
+        /// Leaves the room the player has joined, if any.
+        async fn list_rooms() -> Vec<RoomInfo>;
+
+        /// Creates an empty room. The player still needs to join it.
+        async fn create_room(name: String, rules: RulesPreset) -> Result<RoomId, CreateRoomError>;
+
+        /// Starts receiving events of the room, either as a seated player or as a spectator.
+        async fn join_room(room_id: RoomId, role: RoomRole) -> Result<JoinedRoom, JoinRoomError>;
+
+        /// Sends the message to everyone in the room the player has joined. Messages of
+        /// spectators go to the spectator channel.
+        async fn send_chat_message(text: String) -> Result<(), ChatError>;
+
+        /// Publishes the state of the game after it changed, along with the events that changed
+        /// it, to everyone else in the room. Only the player whose turn it is can do that, and
+        /// the turns only go forward. The first player in the room starts the game. Players who
+        /// lost, and a draw, stay as the server decided them.
+        async fn update_game(
+            snapshot: GameSnapshot,
+            events: Vec<LogEntry>,
+        ) -> Result<(), GameError>;
+
+        /// Gives up the game for the player. Everyone in the room is told about it, so that the
+        /// player's properties return to the bank. If it was the player's turn, it passes to the
+        /// next player.
+        async fn surrender() -> Result<(), GameError>;
+
+        /// Offers the other players to end the game in a draw, or agrees to the offer. Declining
+        /// cancels the offer. The game ends once everyone left in it agrees.
+        async fn vote_draw(agree: bool) -> Result<(), GameError>;
+
+        /// Asks a player to bid in the auction that the player whose turn it is runs, limited by
+        /// the bidder's own countdown. Their answer comes back with `bid_placed`, and so does a
+        /// pass, once the countdown expires.
+        async fn request_bid(request: BidRequest) -> Result<(), GameError>;
+
+        /// Answers the bid request sent to the player. `None` passes.
+        async fn place_bid(bid: Option<Money>) -> Result<(), GameError>;
+
+        /// Creates a room with the same rules and players as the joined one, or returns the one
+        /// created already. Everyone in the room is told about it.
+        async fn rematch() -> Result<RoomId, RematchError>;
+
+        /// Starts the countdown of a decision of the player whose turn it is. The turn countdowns
+        /// are started by the server, and run until the turn changes. The decision countdown that
+        /// is running is replaced, but never extended, and no decision outlasts the turn. When a
+        /// countdown expires, the server tells the player to take the default action, or takes
+        /// them out of the game once they ran out of timeouts.
+        async fn start_countdown() -> Result<Countdown, CountdownError>;
+
+        /// Stops the countdown of the player's decision, because they acted in time.
+        async fn stop_countdown() -> Result<(), CountdownError>;
     }
 }
 
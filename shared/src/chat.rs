@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+use snafu::prelude::*;
+
+use crate::player::PlayerId;
+
+pub const MAX_CHAT_MESSAGE_LENGTH: usize = 500;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChatChannel {
+    /// Read by everyone in the room.
+    #[default]
+    Players,
+    /// Read by spectators only, so that they cannot give hints to the players.
+    Spectators,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub sender_id: PlayerId,
+    pub sender_name: String,
+    #[serde(default)]
+    pub channel: ChatChannel,
+    pub text: String,
+    /// Milliseconds since the Unix epoch, according to the server.
+    pub sent_at: u64,
+}
+
+#[derive(Debug, Clone, Snafu, Serialize, Deserialize)]
+#[snafu(visibility(pub))]
+pub enum ChatError {
+    #[snafu(display("Log in to use the chat"))]
+    NotAuthenticated,
+    #[snafu(display("Join a room to use the chat"))]
+    NotInRoom,
+    #[snafu(display("Message is empty"))]
+    EmptyMessage,
+    #[snafu(display("Message is longer than {MAX_CHAT_MESSAGE_LENGTH} characters"))]
+    MessageTooLong,
+    #[snafu(display("You are sending messages too fast"))]
+    RateLimited,
+}
@@ -1,4 +1,55 @@
+use std::time::Duration;
+
+use crate::{
+    chat::ChatMessage,
+    game::{BidRequest, DrawVote, GameSnapshot, LogEntry},
+    money::Money,
+    player::PlayerId,
+    room::{RoomId, RoomInfo},
+    timer::{Countdown, CountdownId},
+};
+
 #[tarpc::service]
 pub trait Frontend {
     async fn event();
+
+    /// Round-trip time of the player's connection. `None` means the player has disconnected.
+    async fn latency_updated(player_id: PlayerId, latency: Option<Duration>);
+
+    /// Room was created or changed, while the player is in the lobby.
+    async fn room_updated(room: RoomInfo);
+
+    /// New message in the chat of the room the player has joined.
+    async fn chat_message(room_id: RoomId, message: ChatMessage);
+
+    /// New state of the game in the room, published by the player whose turn it is, and the
+    /// events since the previous one. The publisher is not told about their own changes.
+    async fn game_updated(room_id: RoomId, snapshot: GameSnapshot, events: Vec<LogEntry>);
+
+    /// The player gave up the game in the room.
+    async fn player_surrendered(room_id: RoomId, player_id: PlayerId);
+
+    /// Someone in the room voted on ending the game in a draw.
+    async fn draw_vote_updated(room_id: RoomId, vote: DrawVote);
+
+    /// The player whose turn it is asks the player to bid in an auction.
+    async fn bid_requested(room_id: RoomId, request: BidRequest);
+
+    /// Answer of the bidder to the auction's request, for the player running the auction. `None`
+    /// is a pass, also when the bidder's countdown expired.
+    async fn bid_placed(room_id: RoomId, bidder: PlayerId, bid: Option<Money>);
+
+    /// Someone in the room started a rematch in another room.
+    async fn rematch_created(room_id: RoomId, rematch_id: RoomId);
+
+    async fn countdown_started(room_id: RoomId, countdown: Countdown);
+
+    async fn countdown_stopped(room_id: RoomId, countdown_id: CountdownId);
+
+    /// The player did not act in time, and should take the default action. `forfeit` means they
+    /// ran out of timeouts instead, so the server took them out of the game.
+    async fn countdown_expired(room_id: RoomId, countdown: Countdown, forfeit: bool);
+
+    /// The server is shutting down and will close the connection soon.
+    async fn server_restarting();
 }
@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use snafu::prelude::*;
+
+use crate::{
+    ledger::Transfer,
+    money::Money,
+    player::{PlayerColor, PlayerId},
+};
+
+/// Amount of game log entries kept per room.
+pub const MAX_GAME_LOG_LENGTH: usize = 1000;
+
+/// Something that happened in the game, worth telling the players about.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameEvent {
+    RolledDice {
+        player: String,
+        dice: (usize, usize),
+    },
+    PassedStart {
+        player: String,
+        reward: Money,
+    },
+    SteppedOnStart {
+        player: String,
+        reward: Money,
+    },
+    SteppedOnOwnProperty {
+        player: String,
+        property: String,
+    },
+    PaidRent {
+        player: String,
+        owner: String,
+        property: String,
+        rent: Money,
+    },
+    BoughtProperty {
+        player: String,
+        property: String,
+        price: Money,
+    },
+    DeclinedProperty {
+        player: String,
+        property: String,
+    },
+    BuiltAgency {
+        player: String,
+        property: String,
+    },
+    SoldAgency {
+        player: String,
+        property: String,
+    },
+    WonAgencyAuction {
+        player: String,
+        property: String,
+        price: Money,
+    },
+    MortgagedProperty {
+        player: String,
+        property: String,
+        reward: Money,
+    },
+    RecoveredProperty {
+        player: String,
+        property: String,
+        price: Money,
+    },
+    MortgageExpiring {
+        player: String,
+        property: String,
+        rounds_left: usize,
+    },
+    LostMortgagedProperty {
+        player: String,
+        property: String,
+    },
+    WonPropertyAuction {
+        player: String,
+        property: String,
+        price: Money,
+    },
+    Chance {
+        player: String,
+        amount: Money,
+    },
+    PaidTax {
+        player: String,
+        amount: Money,
+    },
+    WentToJail {
+        player: String,
+    },
+    Surrendered {
+        player: String,
+    },
+    OfferedDraw {
+        player: String,
+    },
+    AgreedToDraw {
+        player: String,
+    },
+    DeclinedDraw {
+        player: String,
+    },
+    /// Everyone left in the game agreed to end it in a draw.
+    Drawn,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// Milliseconds since the Unix epoch.
+    pub at: u64,
+    pub event: GameEvent,
+}
+
+/// State of the game, as seen by everyone in the room. The player whose turn it is publishes it
+/// after every change, so that everyone else can follow the game.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameSnapshot {
+    /// Number of the snapshot, which grows with every one published, so that one that arrives
+    /// late does not overwrite a newer one.
+    #[serde(default)]
+    pub revision: u64,
+    pub players: Vec<PlayerSnapshot>,
+    pub properties: Vec<PropertySnapshot>,
+    pub current_player: PlayerId,
+    pub current_turn: usize,
+    pub current_round: usize,
+    pub rolled_dice: Option<(usize, usize)>,
+    #[serde(default)]
+    pub stats: GameStats,
+    #[serde(default)]
+    pub ledger: Vec<Transfer>,
+    /// The players agreed to end the game in a draw.
+    #[serde(default)]
+    pub is_drawn: bool,
+}
+
+impl GameSnapshot {
+    /// Whether the game ended in a draw, or at most one player is left in it.
+    pub fn is_finished(&self) -> bool {
+        self.is_drawn || self.players_left().count() <= 1
+    }
+
+    /// Players that have not lost yet.
+    pub fn players_left(&self) -> impl Iterator<Item = PlayerId> + '_ {
+        self.players
+            .iter()
+            .filter(|player| !player.has_lost)
+            .map(|player| player.id)
+    }
+
+    /// Whether the snapshot can follow the previous one: the same players are in the game, the
+    /// ledger only grew, and every balance changed exactly by the transfers added to it.
+    pub fn follows(&self, previous: &GameSnapshot) -> bool {
+        if self.players.len() != previous.players.len() {
+            return false;
+        }
+        let Some(transfers) = self.ledger.strip_prefix(previous.ledger.as_slice()) else {
+            return false;
+        };
+
+        self.players
+            .iter()
+            .zip(&previous.players)
+            .all(|(player, before)| {
+                let balance = transfers
+                    .iter()
+                    .try_fold(before.balance, |balance, transfer| {
+                        balance.checked_add(transfer.change_for(player.id)?)
+                    });
+                player.id == before.id && balance == Some(player.balance)
+            })
+    }
+
+    /// Player whose turn comes after the current one, and whether the round ends with the current
+    /// turn. Players take turns in the order of their ids.
+    pub fn next_player(&self) -> Option<(PlayerId, bool)> {
+        let mut players_left = self.players_left().collect::<Vec<_>>();
+        players_left.sort();
+
+        match players_left
+            .iter()
+            .find(|player_id| **player_id > self.current_player)
+        {
+            Some(next_player) => Some((*next_player, false)),
+            None => players_left.first().map(|next_player| (*next_player, true)),
+        }
+    }
+
+    /// Takes the player out of the game: their properties return to the bank without mortgages or
+    /// agencies, and their turn passes to the next player.
+    pub fn eliminate(&mut self, player_id: PlayerId) {
+        let Some(player) = self
+            .players
+            .iter_mut()
+            .find(|player| player.id == player_id && !player.has_lost)
+        else {
+            return;
+        };
+        player.has_lost = true;
+        self.stats.eliminated.push(player_id);
+        self.properties
+            .iter_mut()
+            .filter(|property| property.owner == Some(player_id))
+            .for_each(PropertySnapshot::return_to_bank);
+
+        if self.current_player != player_id || self.is_finished() {
+            return;
+        }
+        if let Some((next_player, is_round_ended)) = self.next_player() {
+            self.current_player = next_player;
+            self.current_turn += 1;
+            if is_round_ended {
+                self.current_round += 1;
+            }
+            self.rolled_dice = None;
+        }
+    }
+}
+
+/// What is auctioned.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuctionLot {
+    /// Agency of a kind the bank has fewer of than there are players who want to build one.
+    ScarceAgency,
+    /// Property its owner forfeited.
+    Property { property: String },
+}
+
+/// Request to a player to bid in an auction, which the player whose turn it is runs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BidRequest {
+    pub bidder: PlayerId,
+    pub lot: AuctionLot,
+    /// Lowest bid that beats the leading one.
+    pub min_bid: Money,
+    /// Most the bidder can afford.
+    pub max_bid: Money,
+}
+
+/// Vote of a player on ending the game in a draw.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DrawVote {
+    pub player_id: PlayerId,
+    pub agree: bool,
+    /// Players that agree to the draw. Empty, once someone declined it.
+    pub votes: Vec<PlayerId>,
+    /// Everyone left in the game agreed, so it is over.
+    pub is_agreed: bool,
+}
+
+/// Statistics collected while the game is played, for the summary at its end.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameStats {
+    /// Net worth of every player at the start of the game and at the end of every round.
+    pub net_worth_history: Vec<Vec<(PlayerId, Money)>>,
+    pub rent_paid: HashMap<PlayerId, Money>,
+    pub rent_received: HashMap<PlayerId, Money>,
+    /// Rent collected on every property, by its title.
+    pub property_income: HashMap<String, Money>,
+    /// Players in the order they lost.
+    pub eliminated: Vec<PlayerId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerSnapshot {
+    pub id: PlayerId,
+    pub name: String,
+    pub color: PlayerColor,
+    pub balance: Money,
+    pub position: usize,
+    pub is_in_jail: bool,
+    pub has_lost: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PropertySnapshot {
+    /// Index of the property's cell on the board.
+    pub cell: usize,
+    pub owner: Option<PlayerId>,
+    /// Amount of agencies, for properties that can have them.
+    pub level: usize,
+    pub mortgaged_for: Option<usize>,
+    pub is_agency_built: bool,
+}
+
+impl PropertySnapshot {
+    /// Leaves the property without an owner, mortgage or agencies. The agencies return to the
+    /// bank's supply.
+    pub fn return_to_bank(&mut self) {
+        *self = Self {
+            cell: self.cell,
+            owner: None,
+            level: 0,
+            mortgaged_for: None,
+            is_agency_built: false,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Snafu, Serialize, Deserialize)]
+#[snafu(visibility(pub))]
+pub enum GameError {
+    #[snafu(display("Log in to play"))]
+    NotAuthenticated,
+    #[snafu(display("Join a room to play"))]
+    NotInRoom,
+    #[snafu(display("Only seated players can change the game"))]
+    NotSeated,
+    #[snafu(display("The game has not started yet"))]
+    NoGame,
+    #[snafu(display("The game is over already"))]
+    GameOver,
+    #[snafu(display("You are not in the game anymore"))]
+    NotPlaying,
+    #[snafu(display("Only the player whose turn it is can change the game"))]
+    NotYourTurn,
+    #[snafu(display("The game has moved on since"))]
+    StaleGame,
+    #[snafu(display("The game does not follow from its previous state"))]
+    IllegalGame,
+    #[snafu(display("The bidder is not in the game"))]
+    UnknownBidder,
+    #[snafu(display("The bid is out of range"))]
+    InvalidBid,
+}
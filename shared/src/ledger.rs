@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{money::Money, player::PlayerId};
+
+/// Side of a money transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Party {
+    Bank,
+    Player(PlayerId),
+}
+
+/// What the money was transferred for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransferReason {
+    PassedStart,
+    SteppedOnStart,
+    Rent {
+        property: String,
+    },
+    /// Whatever the player had left, when they could not pay their debt.
+    Bankruptcy,
+    Purchase {
+        property: String,
+    },
+    Agency {
+        property: String,
+    },
+    AgencySale {
+        property: String,
+    },
+    Mortgage {
+        property: String,
+    },
+    Recovery {
+        property: String,
+    },
+    Chance,
+    Tax,
+}
+
+/// Entry of the game's ledger. Every change of a player's balance is one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transfer {
+    pub turn: usize,
+    pub from: Party,
+    pub to: Party,
+    pub amount: Money,
+    pub reason: TransferReason,
+}
+
+impl Transfer {
+    /// Change of the player's balance caused by the transfer. `None` if it overflows, which only
+    /// a forged transfer can.
+    pub fn change_for(&self, player_id: PlayerId) -> Option<Money> {
+        let player = Party::Player(player_id);
+        match (self.from == player, self.to == player) {
+            (true, false) => self.amount.checked_neg(),
+            (false, true) => Some(self.amount),
+            _ => Some(Money::default()),
+        }
+    }
+}
@@ -1,4 +1,12 @@
 pub mod backend;
+pub mod chat;
 pub mod frontend;
+pub mod game;
+pub mod ledger;
+pub mod money;
+pub mod player;
+pub mod room;
+pub mod rules;
+pub mod timer;
 
 pub mod spawn_two_way;
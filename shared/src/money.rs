@@ -0,0 +1,285 @@
+use std::fmt::Display;
+
+use derive_more::derive::{Constructor, From, Into, Neg};
+use serde::{Deserialize, Serialize};
+
+/// Amount of money, in thousands of dollars, which the "k" next to it shows.
+///
+/// All arithmetic goes through the checked helpers, so that an overflow is never silently wrapped
+/// around in a release build.
+#[derive(
+    Debug,
+    Default,
+    Constructor,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    From,
+    Into,
+    Neg,
+    Serialize,
+    Deserialize,
+)]
+#[serde(transparent)]
+pub struct Money(i64);
+
+impl Money {
+    pub fn is_positive(self) -> bool {
+        self.0 > 0
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    pub fn checked_neg(self) -> Option<Money> {
+        self.0.checked_neg().map(Money)
+    }
+
+    pub fn checked_add(self, other: Money) -> Option<Money> {
+        self.0.checked_add(other.0).map(Money)
+    }
+
+    pub fn checked_sub(self, other: Money) -> Option<Money> {
+        self.0.checked_sub(other.0).map(Money)
+    }
+
+    pub fn checked_mul(self, factor: i64) -> Option<Money> {
+        self.0.checked_mul(factor).map(Money)
+    }
+
+    /// Quotient rounded toward zero. `None` when dividing by zero.
+    pub fn checked_div(self, divisor: i64) -> Option<Money> {
+        self.0.checked_div(divisor).map(Money)
+    }
+
+    /// `percent` percents of the amount, rounded toward zero.
+    pub fn checked_percent(self, percent: i64) -> Option<Money> {
+        self.checked_mul(percent)?.checked_div(100)
+    }
+
+    pub fn format(&self, format: &MoneyFormat) -> String {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let symbol = format.symbol;
+        let amount = self.0.unsigned_abs();
+
+        if !format.is_compact {
+            return format!("{sign}{symbol}{}", group_digits(amount, format.separator));
+        }
+        if amount < 1000 {
+            return format!("{sign}{symbol}{amount}k");
+        }
+
+        // Truncated rather than rounded, so that the short form never overstates the amount.
+        let tenths = amount / 100;
+        let millions = group_digits(tenths / 10, format.separator);
+        match tenths % 10 {
+            0 => format!("{sign}{symbol}{millions}M"),
+            tenth => format!("{sign}{symbol}{millions}{}{tenth}M", format.decimal_point),
+        }
+    }
+}
+
+impl Display for Money {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.format(&MoneyFormat::default()))
+    }
+}
+
+/// How [`Money`] is written: "-$1,500" in full, or "-$1.5M" in short.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoneyFormat {
+    /// Written between the sign and the digits.
+    symbol: &'static str,
+    /// Separator between groups of three digits, if they are grouped at all.
+    separator: Option<char>,
+    decimal_point: char,
+    /// Whether the amount is shortened to thousands ("950k") or millions ("1.5M"). The full form
+    /// has no suffix, since the "k" is usually styled apart from the digits.
+    is_compact: bool,
+}
+
+impl Default for MoneyFormat {
+    fn default() -> Self {
+        Self {
+            symbol: "",
+            separator: Some(','),
+            decimal_point: '.',
+            is_compact: false,
+        }
+    }
+}
+
+impl MoneyFormat {
+    pub fn with_symbol(self, symbol: &'static str) -> Self {
+        Self { symbol, ..self }
+    }
+
+    /// Digits are grouped with `separator`, or not at all for `None`.
+    pub fn with_separator(self, separator: Option<char>) -> Self {
+        Self { separator, ..self }
+    }
+
+    pub fn with_decimal_point(self, decimal_point: char) -> Self {
+        Self {
+            decimal_point,
+            ..self
+        }
+    }
+
+    pub fn compact(self) -> Self {
+        Self {
+            is_compact: true,
+            ..self
+        }
+    }
+}
+
+fn group_digits(amount: u64, separator: Option<char>) -> String {
+    let digits = amount.to_string();
+    let Some(separator) = separator else {
+        return digits;
+    };
+
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index) % 3 == 0 {
+            grouped.push(separator);
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn full(amount: i64) -> String {
+        Money::new(amount).format(&MoneyFormat::default())
+    }
+
+    fn compact(amount: i64) -> String {
+        Money::new(amount).format(&MoneyFormat::default().compact())
+    }
+
+    #[test]
+    fn formats_zero() {
+        assert_eq!(full(0), "0");
+        assert_eq!(compact(0), "0k");
+    }
+
+    #[test]
+    fn groups_digits_by_three() {
+        assert_eq!(full(999), "999");
+        assert_eq!(full(1_000), "1,000");
+        assert_eq!(full(15_000), "15,000");
+        assert_eq!(full(1_234_567), "1,234,567");
+    }
+
+    #[test]
+    fn puts_sign_before_symbol() {
+        assert_eq!(full(-1_500), "-1,500");
+        let format = MoneyFormat::default().with_symbol("$");
+        assert_eq!(Money::new(-1_500).format(&format), "-$1,500");
+        assert_eq!(Money::new(1_500).format(&format), "$1,500");
+    }
+
+    #[test]
+    fn formats_extreme_amounts() {
+        assert_eq!(full(i64::MAX), "9,223,372,036,854,775,807");
+        assert_eq!(full(i64::MIN), "-9,223,372,036,854,775,808");
+    }
+
+    #[test]
+    fn uses_locale_separators() {
+        let format = MoneyFormat::default()
+            .with_separator(Some('.'))
+            .with_decimal_point(',');
+        assert_eq!(Money::new(1_234_567).format(&format), "1.234.567");
+        assert_eq!(Money::new(1_500).format(&format.compact()), "1,5M");
+        assert_eq!(Money::new(1_234_500).format(&format.compact()), "1.234,5M");
+
+        let ungrouped = MoneyFormat::default().with_separator(None);
+        assert_eq!(Money::new(1_234_567).format(&ungrouped), "1234567");
+    }
+
+    #[test]
+    fn shortens_compact_amounts() {
+        assert_eq!(compact(950), "950k");
+        assert_eq!(compact(-950), "-950k");
+        assert_eq!(compact(1_000), "1M");
+        assert_eq!(compact(1_500), "1.5M");
+        assert_eq!(compact(-2_500), "-2.5M");
+        assert_eq!(compact(1_234_500), "1,234.5M");
+    }
+
+    #[test]
+    fn truncates_compact_amounts() {
+        assert_eq!(compact(1_599), "1.5M");
+        assert_eq!(compact(1_099), "1M");
+        assert_eq!(compact(-1_599), "-1.5M");
+    }
+
+    #[test]
+    fn checked_arithmetic_detects_overflow() {
+        assert_eq!(Money::new(i64::MAX).checked_add(Money::new(1)), None);
+        assert_eq!(Money::new(i64::MIN).checked_sub(Money::new(1)), None);
+        assert_eq!(Money::new(i64::MAX).checked_mul(2), None);
+        assert_eq!(Money::new(1).checked_div(0), None);
+        assert_eq!(
+            Money::new(1_000).checked_add(Money::new(-1_500)),
+            Some(Money::new(-500))
+        );
+    }
+
+    #[test]
+    fn percent_rounds_toward_zero() {
+        assert_eq!(Money::new(1_001).checked_percent(50), Some(Money::new(500)));
+        assert_eq!(
+            Money::new(-1_001).checked_percent(50),
+            Some(Money::new(-500))
+        );
+        assert_eq!(Money::new(600).checked_percent(110), Some(Money::new(660)));
+    }
+
+    proptest! {
+        #[test]
+        fn ungrouped_amounts_parse_back(amount in any::<i64>()) {
+            let format = MoneyFormat::default().with_separator(None);
+            prop_assert_eq!(Money::new(amount).format(&format).parse::<i64>(), Ok(amount));
+        }
+
+        #[test]
+        fn digits_are_grouped_by_three(amount in any::<i64>()) {
+            let formatted = full(amount);
+            let groups = formatted.trim_start_matches('-').split(',').collect::<Vec<_>>();
+            prop_assert!((1..=3).contains(&groups[0].len()));
+            prop_assert!(groups[1..].iter().all(|group| group.len() == 3));
+            prop_assert_eq!(formatted.replace(',', "").parse::<i64>(), Ok(amount));
+        }
+
+        #[test]
+        fn compact_amounts_are_truncated(amount in any::<i64>()) {
+            let formatted = compact(amount);
+            prop_assert_eq!(formatted.starts_with('-'), amount < 0);
+
+            let unsigned = formatted.trim_start_matches('-').replace(',', "");
+            let shown = match unsigned.strip_suffix('k') {
+                Some(thousands) => thousands.parse::<u64>().unwrap(),
+                None => {
+                    let millions = unsigned.strip_suffix('M').unwrap();
+                    let (whole, tenth) = millions.split_once('.').unwrap_or((millions, "0"));
+                    whole.parse::<u64>().unwrap() * 1000 + tenth.parse::<u64>().unwrap() * 100
+                }
+            };
+            let amount = amount.unsigned_abs();
+            prop_assert!(shown <= amount && amount - shown < 100, "{formatted} for {amount}");
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+pub type PlayerId = u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlayerColor {
+    Red,
+    Blue,
+    Green,
+    Purple,
+    Yellow,
+}
+
+impl PlayerColor {
+    /// Colors of the seats in a room, in order.
+    pub const VARIANTS: [PlayerColor; 5] = [
+        PlayerColor::Blue,
+        PlayerColor::Green,
+        PlayerColor::Red,
+        PlayerColor::Purple,
+        PlayerColor::Yellow,
+    ];
+
+    pub fn get_player_gradient(&self) -> &'static str {
+        match self {
+            PlayerColor::Red => "linear-gradient(45deg,#cd3747,#f26b61)",
+            PlayerColor::Blue => "linear-gradient(45deg,#54c9f0,#2191e1)",
+            PlayerColor::Green => "linear-gradient(45deg,#66b343,#b0e372)",
+            PlayerColor::Purple => "linear-gradient(45deg,#a17fef,#d188e3)",
+            PlayerColor::Yellow => "linear-gradient(45deg,#e8b30e,#f7d35c)",
+        }
+    }
+
+    /// Solid color, readable on both light and dark backgrounds.
+    pub fn get_text_color(&self) -> &'static str {
+        match self {
+            PlayerColor::Red => "#f26b61",
+            PlayerColor::Blue => "#2191e1",
+            PlayerColor::Green => "#66b343",
+            PlayerColor::Purple => "#a17fef",
+            PlayerColor::Yellow => "#e8b30e",
+        }
+    }
+
+    pub fn get_cell_gradient(&self) -> &'static str {
+        match self {
+            PlayerColor::Red => "linear-gradient(45deg,#d96975,#f59088)",
+            PlayerColor::Blue => "linear-gradient(45deg,#7ed6f3,#58ace8)",
+            PlayerColor::Green => "linear-gradient(45deg,#8cc672,#c3ea95)",
+            PlayerColor::Purple => "linear-gradient(45deg,#b89ff3,#dca5ea)",
+            PlayerColor::Yellow => "linear-gradient(45deg,#edc447,#f9de85)",
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use std::{fmt, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use snafu::prelude::*;
+
+use crate::{
+    chat::ChatMessage,
+    game::{GameSnapshot, LogEntry},
+    player::{PlayerColor, PlayerId},
+    rules::{AgencyTerms, MortgageTerms},
+    timer::{Countdown, TurnTimers},
+};
+
+pub type RoomId = u64;
+
+/// Room that always exists, so that the lobby is never empty.
+pub const DEFAULT_ROOM: RoomId = 0;
+/// Amount of seats in every room, one per player color.
+pub const MAX_PLAYERS: usize = PlayerColor::VARIANTS.len();
+pub const MAX_ROOM_NAME_LENGTH: usize = 40;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RulesPreset {
+    #[default]
+    Classic,
+    /// Short timers and fewer timeouts, for quick games.
+    Blitz,
+}
+
+impl RulesPreset {
+    pub const VARIANTS: [RulesPreset; 2] = [RulesPreset::Classic, RulesPreset::Blitz];
+
+    pub fn timers(&self) -> TurnTimers {
+        match self {
+            RulesPreset::Classic => TurnTimers::default(),
+            RulesPreset::Blitz => TurnTimers {
+                turn: Duration::from_secs(20),
+                decision: Duration::from_secs(10),
+                max_timeouts: 2,
+            },
+        }
+    }
+
+    pub fn mortgage_terms(&self) -> MortgageTerms {
+        match self {
+            RulesPreset::Classic => MortgageTerms::default(),
+            RulesPreset::Blitz => MortgageTerms {
+                duration: 8,
+                warning_rounds: 2,
+                ..MortgageTerms::default()
+            },
+        }
+    }
+
+    pub fn agency_terms(&self) -> AgencyTerms {
+        AgencyTerms::default()
+    }
+}
+
+impl fmt::Display for RulesPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RulesPreset::Classic => write!(f, "Classic"),
+            RulesPreset::Blitz => write!(f, "Blitz"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RoomStatus {
+    /// Waiting for players, the game has not started yet.
+    Open,
+    Running,
+    Finished,
+}
+
+impl fmt::Display for RoomStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RoomStatus::Open => write!(f, "Open"),
+            RoomStatus::Running => write!(f, "Running"),
+            RoomStatus::Finished => write!(f, "Finished"),
+        }
+    }
+}
+
+/// Summary of a room, as shown in the lobby.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoomInfo {
+    pub id: RoomId,
+    pub name: String,
+    pub players: usize,
+    pub rules: RulesPreset,
+    pub status: RoomStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RoomRole {
+    /// Takes a seat at the table and plays the game.
+    Player,
+    /// Follows the game read-only and chats with other spectators.
+    Spectator,
+}
+
+/// Everything a client needs to catch up with the room it joined.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JoinedRoom {
+    #[serde(default)]
+    pub rules: RulesPreset,
+    /// Chat history of the room, limited to the channels the client can read.
+    pub chat: Vec<ChatMessage>,
+    /// Ids and names of the seated players, in the order they took their seats.
+    pub seats: Vec<(PlayerId, String)>,
+    /// Latest state of the game, if a seated player has published one.
+    pub game: Option<GameSnapshot>,
+    pub game_log: Vec<LogEntry>,
+    /// Room created for a rematch of this room's game.
+    pub rematch: Option<RoomId>,
+    /// Running countdowns, with the time they have left.
+    #[serde(default)]
+    pub countdowns: Vec<Countdown>,
+}
+
+#[derive(Debug, Clone, Snafu, Serialize, Deserialize)]
+#[snafu(visibility(pub))]
+pub enum JoinRoomError {
+    #[snafu(display("Log in to join a room"))]
+    NotAuthenticated,
+    #[snafu(display("Room {room_id} does not exist"))]
+    RoomNotFound { room_id: RoomId },
+    #[snafu(display("All {MAX_PLAYERS} seats in the room are taken"))]
+    RoomFull,
+    #[snafu(display("Players cannot watch a game they are still playing"))]
+    StillPlaying,
+}
+
+#[derive(Debug, Clone, Snafu, Serialize, Deserialize)]
+#[snafu(module, visibility(pub))]
+pub enum RematchError {
+    #[snafu(display("Log in to play"))]
+    NotAuthenticated,
+    #[snafu(display("Join a room to play"))]
+    NotInRoom,
+    #[snafu(display("Only seated players can start a rematch"))]
+    NotSeated,
+    #[snafu(display("There are too many rooms already"))]
+    TooManyRooms,
+}
+
+#[derive(Debug, Clone, Snafu, Serialize, Deserialize)]
+#[snafu(module, visibility(pub))]
+pub enum CreateRoomError {
+    #[snafu(display("Log in to create a room"))]
+    NotAuthenticated,
+    #[snafu(display("Room name is empty"))]
+    EmptyName,
+    #[snafu(display("Room name is longer than {MAX_ROOM_NAME_LENGTH} characters"))]
+    NameTooLong,
+    #[snafu(display("There are too many rooms already"))]
+    TooManyRooms,
+}
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+use crate::money::Money;
+
+/// Terms the bank mortgages properties on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MortgageTerms {
+    /// Rounds the owner has to recover the property in, before forfeiting it.
+    pub duration: usize,
+    /// Interest on top of the mortgage, which the owner pays to recover the property, in percents.
+    pub interest_percent: i64,
+    /// Rounds before the forfeiture, in which the owner is warned about it.
+    pub warning_rounds: usize,
+    /// Whether forfeited properties are auctioned among the other players, instead of returning
+    /// to the bank.
+    pub auction_forfeited: bool,
+}
+
+impl Default for MortgageTerms {
+    fn default() -> Self {
+        Self {
+            duration: 15,
+            interest_percent: 20,
+            warning_rounds: 3,
+            auction_forfeited: true,
+        }
+    }
+}
+
+/// Terms the bank trades agencies on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgencyTerms {
+    /// Part of the agency price the bank pays back, when the agency is sold, in percents.
+    pub sell_back_percent: i64,
+}
+
+impl Default for AgencyTerms {
+    fn default() -> Self {
+        Self {
+            sell_back_percent: 50,
+        }
+    }
+}
+
+impl MortgageTerms {
+    /// Amount to pay back for the mortgage of the given amount.
+    pub fn with_interest(&self, mortgage: Money) -> Money {
+        mortgage
+            .checked_percent(100 + self.interest_percent)
+            .expect("Mortgage with the interest should not overflow")
+    }
+}
@@ -1,31 +1,65 @@
 use any_spawner::Executor;
-use futures::{io, Sink, SinkExt, Stream, StreamExt, TryFutureExt, TryStreamExt};
+use futures::{
+    channel::mpsc,
+    future, io,
+    stream::{self, AbortHandle, Abortable},
+    FutureExt, Sink, SinkExt, Stream, StreamExt, TryFutureExt, TryStreamExt,
+};
 use tarpc::transport::channel::UnboundedChannel;
 
-use std::error::Error;
+use std::{cell::RefCell, collections::VecDeque, error::Error, rc::Rc, time::Duration};
 
 /// A tarpc message that can be either a request or a response.
+/// `Ping` and `Pong` are heartbeat messages - they are handled by the transport itself and never
+/// reach tarpc.
 #[derive(serde::Serialize, serde::Deserialize)]
 pub enum TwoWayMessage<Req, Resp> {
     ClientMessage(tarpc::ClientMessage<Req>),
     Response(tarpc::Response<Resp>),
+    Ping(u64),
+    Pong(u64),
 }
 
 pub type TwoWayTransports<Req1, Resp1, Req2, Resp2> = (
     UnboundedChannel<tarpc::ClientMessage<Req1>, tarpc::Response<Resp1>>,
     UnboundedChannel<tarpc::Response<Resp2>, tarpc::ClientMessage<Req2>>,
+    mpsc::UnboundedReceiver<Duration>,
 );
 
+/// Keepalive settings of the connection.
+pub struct Heartbeat<I, C> {
+    /// Yields every time a ping should be sent.
+    pub interval: I,
+    /// Monotonic clock, used to measure round-trip time.
+    pub clock: C,
+    /// Amount of unanswered pings, after which the peer is considered dead.
+    pub max_missed_pongs: usize,
+}
+
+#[derive(Default)]
+struct HeartbeatState {
+    next_nonce: u64,
+    /// Pings that were not answered yet, along with the time they were sent at.
+    in_flight: VecDeque<(u64, Duration)>,
+}
+
 /// Returns two transports that multiplex over the given transport.
 /// The first transport can be used by a server: it receives requests and sends back responses.
 /// The second transport can be used by a client: it sends requests and receives back responses.
-pub fn spawn_two_way<Req1, Resp1, Req2, Resp2, T>(
+/// The third element yields round-trip time of every answered heartbeat ping.
+///
+/// When the peer does not answer `heartbeat.max_missed_pongs` pings in a row, the connection is
+/// closed.
+pub fn spawn_two_way<Req1, Resp1, Req2, Resp2, T, I, C>(
     transport: T,
+    heartbeat: Heartbeat<I, C>,
 ) -> TwoWayTransports<Req1, Resp1, Req2, Resp2>
 where
     T: Stream<Item = io::Result<TwoWayMessage<Req1, Resp2>>>,
     T: Sink<TwoWayMessage<Req2, Resp1>, Error = io::Error>,
     T: 'static,
+    I: Stream<Item = ()> + 'static,
+    C: Fn() -> Duration + 'static,
     Req1: 'static,
     Resp1: 'static,
     Req2: 'static,
@@ -36,34 +70,94 @@ where
     let (mut server_sink, server_stream) = server.split();
     let (mut client_sink, client_stream) = client.split();
     let (transport_sink, mut transport_stream) = transport.split();
+    let (pong_sender, pong_receiver) = mpsc::unbounded();
+    let (latency_sender, latency_receiver) = mpsc::unbounded();
+    let (inbound_handle, inbound_registration) = AbortHandle::new_pair();
+    let (outbound_handle, outbound_registration) = AbortHandle::new_pair();
+    let Heartbeat {
+        interval,
+        clock,
+        max_missed_pongs,
+    } = heartbeat;
+    let clock = Rc::new(clock);
+    let heartbeat_state = Rc::new(RefCell::new(HeartbeatState::default()));
 
     // Task for inbound message handling.
-    Executor::spawn_local(async move {
-        let e: Result<(), Box<dyn Error>> = async move {
-            while let Some(msg) = transport_stream.next().await {
-                match msg? {
-                    TwoWayMessage::ClientMessage(req) => server_sink.send(req).await?,
-                    TwoWayMessage::Response(resp) => client_sink.send(resp).await?,
+    let inbound = {
+        let clock = clock.clone();
+        let heartbeat_state = heartbeat_state.clone();
+        let outbound_handle = outbound_handle.clone();
+        async move {
+            let e: Result<(), Box<dyn Error>> = async move {
+                while let Some(msg) = transport_stream.next().await {
+                    match msg? {
+                        TwoWayMessage::ClientMessage(req) => server_sink.send(req).await?,
+                        TwoWayMessage::Response(resp) => client_sink.send(resp).await?,
+                        TwoWayMessage::Ping(nonce) => {
+                            pong_sender.unbounded_send(TwoWayMessage::Pong(nonce))?
+                        }
+                        TwoWayMessage::Pong(nonce) => {
+                            let mut state = heartbeat_state.borrow_mut();
+                            let Some(index) =
+                                state.in_flight.iter().position(|(sent, _)| *sent == nonce)
+                            else {
+                                continue;
+                            };
+
+                            // Pings sent before the answered one are not interesting anymore.
+                            if let Some((_, sent_at)) = state.in_flight.drain(..=index).last() {
+                                let _ =
+                                    latency_sender.unbounded_send(clock().saturating_sub(sent_at));
+                            }
+                        }
+                    }
                 }
+                Ok(())
             }
-            Ok(())
-        }
-        .await;
-        match e {
-            Ok(()) => eprintln!("Transport closed."),
-            Err(e) => eprintln!("Failed to forward messages to server: {:?}", e),
+            .await;
+            match e {
+                Ok(()) => eprintln!("Transport closed."),
+                Err(e) => eprintln!("Failed to forward messages to server: {:?}", e),
+            }
+
+            // Nobody is listening on the other side anymore.
+            outbound_handle.abort();
         }
-    });
+    };
+
+    Executor::spawn_local(Abortable::new(inbound, inbound_registration).map(|_| ()));
+
+    let pings = interval
+        .map(move |()| {
+            let mut state = heartbeat_state.borrow_mut();
+            if state.in_flight.len() >= max_missed_pongs {
+                eprintln!("Peer did not answer {max_missed_pongs} pings. Closing connection.");
+                inbound_handle.abort();
+                outbound_handle.abort();
+                return None;
+            }
+
+            let nonce = state.next_nonce;
+            state.next_nonce += 1;
+            state.in_flight.push_back((nonce, clock()));
+            Some(TwoWayMessage::Ping(nonce))
+        })
+        .take_while(|ping| future::ready(ping.is_some()))
+        .filter_map(future::ready);
 
     // Task for outbound message handling.
-    Executor::spawn_local(
-        futures::stream::select(
+    let outbound = stream::select(
+        stream::select(
             server_stream.map_ok(|resp| TwoWayMessage::Response(resp)),
             client_stream.map_ok(|req| TwoWayMessage::ClientMessage(req)),
         )
-        .map_err(|error| io::Error::new(io::ErrorKind::Other, error))
-        .forward(transport_sink)
-        .unwrap_or_else(|e| eprintln!("Failed to forward messages to transport: {:?}", e)),
-    );
-    (server_, client_)
+        .map_err(|error| io::Error::new(io::ErrorKind::Other, error)),
+        stream::select(pong_receiver, pings).map(Ok),
+    )
+    .forward(transport_sink)
+    .unwrap_or_else(|e| eprintln!("Failed to forward messages to transport: {:?}", e));
+
+    Executor::spawn_local(Abortable::new(outbound, outbound_registration).map(|_| ()));
+
+    (server_, client_, latency_receiver)
 }
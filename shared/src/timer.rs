@@ -0,0 +1,74 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use snafu::prelude::*;
+
+use crate::player::PlayerId;
+
+pub type CountdownId = u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimerKind {
+    /// Waiting for the player to finish their turn.
+    Turn,
+    /// Waiting for the player to answer a prompt, e.g. to buy a property.
+    Decision,
+    /// Waiting for a player to bid in an auction, which may be run in someone else's turn.
+    Bid,
+}
+
+/// Time limits of a room.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TurnTimers {
+    pub turn: Duration,
+    pub decision: Duration,
+    /// Amount of timeouts in a row, after which the player surrenders.
+    pub max_timeouts: u32,
+}
+
+impl Default for TurnTimers {
+    fn default() -> Self {
+        Self {
+            turn: Duration::from_secs(60),
+            decision: Duration::from_secs(30),
+            max_timeouts: 3,
+        }
+    }
+}
+
+impl TurnTimers {
+    pub fn duration(&self, kind: TimerKind) -> Duration {
+        match kind {
+            TimerKind::Turn => self.turn,
+            TimerKind::Decision | TimerKind::Bid => self.decision,
+        }
+    }
+}
+
+/// Server-side countdown of a player's action. A room runs at most one countdown of each kind at
+/// once. The turn and decision countdowns are the current player's, the bid countdown is the
+/// bidder's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Countdown {
+    pub id: CountdownId,
+    pub player_id: PlayerId,
+    pub kind: TimerKind,
+    pub duration: Duration,
+}
+
+#[derive(Debug, Clone, Snafu, Serialize, Deserialize)]
+#[snafu(visibility(pub))]
+pub enum CountdownError {
+    #[snafu(display("Log in to take turns"))]
+    NotAuthenticated,
+    #[snafu(display("Join a room to take turns"))]
+    NotInRoom,
+    #[snafu(display("Spectators cannot take turns"))]
+    Spectating,
+    #[snafu(display("The game has not started yet"))]
+    NoGame,
+    #[snafu(display("Wait for your turn"))]
+    NotYourTurn,
+    #[snafu(display("The countdown belongs to another player"))]
+    NotYourCountdown,
+}
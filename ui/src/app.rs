@@ -1,20 +1,43 @@
-use leptos::prelude::*;
+use leptos::{prelude::*, task::spawn_local};
 use leptos_router::{
     components::{Route, Routes},
-    StaticSegment,
+    ParamSegment, StaticSegment,
 };
 
 use crate::{
+    components::locale_switcher::LocaleSwitcher,
     hooks::redirect::use_redirect,
-    pages::{game::GamePage, login::LoginPage},
+    i18n::I18n,
+    pages::{game::GamePage, lobby::LobbyPage, login::LoginPage},
+    server::ServerConnection,
 };
 
 #[component]
 pub fn App() -> impl IntoView {
+    let connection = ServerConnection::new();
+    connection.provide_context();
+    let i18n = I18n::new();
+    i18n.provide_context();
+    spawn_local(async move { connection.connect().await });
+
     view! {
+        <Show when=move || connection.is_restarting()>
+            <div class="fixed top-0 inset-x-0 z-50 p-2 text-center bg-yellow-600">
+                {move || i18n.t("app.restarting", &[])}
+            </div>
+        </Show>
+        <LocaleSwitcher class="fixed right-2 bottom-2 z-40" />
         <Routes fallback=|| use_redirect("/", Default::default())>
             <Route path=StaticSegment("") view=LoginPage/>
-            <Route path=StaticSegment("game") view=GamePage/>
+            <Route path=StaticSegment("lobby") view=LobbyPage/>
+            <Route
+                path=(StaticSegment("game"), ParamSegment("room_id"))
+                view=|| view! { <GamePage/> }
+            />
+            <Route
+                path=(StaticSegment("watch"), ParamSegment("room_id"))
+                view=|| view! { <GamePage spectating=true/> }
+            />
         </Routes>
     }
 }
@@ -1,20 +1,32 @@
-use std::fmt::Display;
+use std::collections::HashMap;
 
-use derive_more::derive::{
-    Add, AddAssign, Constructor, Deref, Div, DivAssign, From, Mul, MulAssign, Neg, Not, Rem,
-    RemAssign, Sub, SubAssign, TryUnwrap,
-};
+use derive_more::derive::TryUnwrap;
 use leptos::prelude::*;
+use shared::game::PropertySnapshot;
+pub use shared::money::Money;
 use snafu::prelude::*;
 
 use crate::{
-    components::in_game_modal::ModalResponse,
+    components::in_game_modal::{ModalResponse, PromptAction},
+    game_log::GameEvent,
     game_state::GameState,
+    ledger::{Party, TransferReason},
     player::{NotEnoughMoneyError, Player, PlayerId},
     utils::rand,
 };
 
 pub const CELLS_COUNT: usize = 40;
+/// Reward for passing Start, including stepping on it.
+pub const PASSED_START_REWARD: i64 = 2000;
+/// Additional reward for stepping on Start.
+pub const START_REWARD: i64 = 1000;
+/// Amounts Chance gives (or takes), equally likely.
+pub const CHANCE_OUTCOMES: [i64; 6] = [500, 1000, 2000, -2000, -1000, -500];
+/// Amount of agencies the bank has for the whole board.
+pub const AGENCY_SUPPLY: usize = 32;
+/// Amount of top-level agencies the bank has for the whole board. Building one returns the
+/// agencies of the property to the bank.
+pub const TOP_LEVEL_SUPPLY: usize = 12;
 
 #[derive(Debug, Clone, Copy, TryUnwrap)]
 #[allow(clippy::large_enum_variant)]
@@ -33,23 +45,41 @@ impl Cell {
         let current_player = untrack(|| game_state.current_player());
         match self {
             Cell::Jail | Cell::FreeParking => {}
-            Cell::Start => current_player.deposit(1000.into()),
-            Cell::GoToJail => current_player.set_is_in_jail(true),
+            Cell::Start => {
+                let reward = START_REWARD.into();
+                game_state.pay_from_bank(&current_player, reward, TransferReason::SteppedOnStart);
+                game_state.log.record(GameEvent::SteppedOnStart {
+                    player: current_player.name.get_value(),
+                    reward,
+                });
+            }
+            Cell::GoToJail => {
+                current_player.set_is_in_jail(true);
+                game_state.log.record(GameEvent::WentToJail {
+                    player: current_player.name.get_value(),
+                });
+            }
             Cell::Property(prop) => {
                 if prop
                     .owner
                     .get_untracked()
                     .is_some_and(|owner| owner == current_player)
                 {
-                    // TODO: Log into chat: "Stepped on his own property"
+                    game_state.log.record(GameEvent::SteppedOnOwnProperty {
+                        player: current_player.name.get_value(),
+                        property: prop.data.title.to_owned(),
+                    });
                 } else if let Some(rent) = untrack(|| prop.rent(game_state)) {
                     game_state
-                        .in_game_modal_state
-                        .one_button_async(
-                            &format!("Oi! You owe this fine lad some moneh: {}$", rent),
-                            "Pay moneh",
+                        .prompt(
+                            &game_state
+                                .i18n
+                                .t("prompt.rent", &[("rent", &game_state.i18n.money(rent))]),
+                            &game_state.i18n.t("button.pay", &[]),
+                            PromptAction::EndTurn,
                         )
                         .await;
+                    game_state.raise_money(&current_player, rent).await;
 
                     if let Err(error) = prop.pay_rent(&current_player, game_state) {
                         match error {
@@ -61,36 +91,33 @@ impl Cell {
                                 // TODO: Add modal for user
                             }
                             OwnedPropertyError::NotEnoughMoney { source } => {
-                                // Ideally, we should never reach here, because UI should force player to surrender,
-                                // if they don't have enough money.
-                                tracing::error!(
+                                // The player could not raise the money, or chose to go bankrupt.
+                                tracing::info!(
                                     "Player \"{}\" (id: {}) does not have enough money ({}$) to pay rent for property. Surrendering...",
                                     source.player_name,
                                     source.player_id,
                                     source.amount,
                                 );
-                                game_state.surrender_player(&current_player);
-                                if let Some(owner) = prop.owner.get_untracked() {
-                                    owner.deposit(rent);
-                                }
+                                // The owner gets whatever the player has left.
+                                game_state.go_bankrupt(&current_player, prop.owner.get_untracked());
                             }
                         }
                     };
                 } else {
                     let response = game_state
-                        .in_game_modal_state
-                        .two_buttons_async(
-                            &format!(
-                                "Oi! You wanna buy this fine land? It's gonna cost ya {}$",
-                                prop.data.price
+                        .prompt_choice(
+                            &game_state.i18n.t(
+                                "prompt.buy",
+                                &[("price", &game_state.i18n.money(prop.data.price))],
                             ),
-                            "Buy",
-                            "Decline",
+                            &game_state.i18n.t("button.buy", &[]),
+                            &game_state.i18n.t("button.decline", &[]),
+                            PromptAction::Buy,
                         )
                         .await;
 
                     if let ModalResponse::Ok = response {
-                        if let Err(error) = prop.buy(&current_player) {
+                        if let Err(error) = prop.buy(&current_player, game_state) {
                             match error {
                                 FreePropertyError::AlreadyOwned { source } => {
                                     tracing::warn!(
@@ -114,56 +141,96 @@ impl Cell {
                                 }
                             }
                         }
+                    } else {
+                        game_state.log.record(GameEvent::DeclinedProperty {
+                            player: current_player.name.get_value(),
+                            property: prop.data.title.to_owned(),
+                        });
                     }
                 }
             }
 
             Cell::Chance => {
-                let random_chance = rand::get_usize(0..=5);
-                let you_get = [500, 1000, 2000, -2000, -1000, -500].map(Money::new)[random_chance];
+                let random_chance = rand::get_usize(0..CHANCE_OUTCOMES.len());
+                let you_get = Money::new(CHANCE_OUTCOMES[random_chance]);
                 game_state
-                    .in_game_modal_state
-                    .one_button_async(
-                        &format!("Your chance is: {you_get}$"),
-                        if you_get.is_negative() {
-                            "Pay moneh"
-                        } else {
-                            "Get moneh"
-                        },
+                    .prompt(
+                        &game_state.i18n.t(
+                            "prompt.chance",
+                            &[("amount", &game_state.i18n.money(you_get))],
+                        ),
+                        &game_state.i18n.t(
+                            if you_get.is_negative() {
+                                "button.pay"
+                            } else {
+                                "button.get"
+                            },
+                            &[],
+                        ),
+                        PromptAction::EndTurn,
                     )
                     .await;
+                if you_get.is_negative() {
+                    game_state.raise_money(&current_player, -you_get).await;
+                }
 
+                let chance = GameEvent::Chance {
+                    player: current_player.name.get_value(),
+                    amount: you_get,
+                };
                 if you_get.is_positive() {
-                    current_player.deposit(you_get);
-                } else if let Err(error) = current_player.withdraw(-you_get) {
-                    // Ideally, we should never reach here, because UI should force player to surrender,
-                    // if they don't have enough money.
-                    tracing::error!(
+                    game_state.pay_from_bank(&current_player, you_get, TransferReason::Chance);
+                    game_state.log.record(chance);
+                } else if let Err(error) = game_state.transfer(
+                    Party::Player(current_player.id),
+                    Party::Bank,
+                    -you_get,
+                    TransferReason::Chance,
+                ) {
+                    // The player could not raise the money, or chose to go bankrupt.
+                    tracing::info!(
                         "Player \"{}\" (id: {}) does not have enough money ({}$) to pay for chance. Surrendering...",
                         error.player_name,
                         error.player_id,
                         error.amount,
                     );
-                    game_state.surrender_player(&current_player);
+                    game_state.go_bankrupt(&current_player, None);
+                } else {
+                    game_state.log.record(chance);
                 }
             }
 
             Cell::Tax(amount) => {
                 game_state
-                    .in_game_modal_state
-                    .one_button_async(&format!("You owe me: {amount}$"), "Pay moneh")
+                    .prompt(
+                        &game_state
+                            .i18n
+                            .t("prompt.tax", &[("amount", &game_state.i18n.money(*amount))]),
+                        &game_state.i18n.t("button.pay", &[]),
+                        PromptAction::EndTurn,
+                    )
                     .await;
-
-                if let Err(error) = current_player.withdraw(*amount) {
-                    // Ideally, we should never reach here, because UI should force player to surrender,
-                    // if they don't have enough money.
-                    tracing::error!(
+                game_state.raise_money(&current_player, *amount).await;
+
+                if let Err(error) = game_state.transfer(
+                    Party::Player(current_player.id),
+                    Party::Bank,
+                    *amount,
+                    TransferReason::Tax,
+                ) {
+                    // The player could not raise the money, or chose to go bankrupt.
+                    tracing::info!(
                         "Player \"{}\" (id: {}) does not have enough money ({}$) to pay for chance. Surrendering...",
                         error.player_name,
                         error.player_id,
                         error.amount,
                     );
-                    game_state.surrender_player(&current_player);
+                    game_state.go_bankrupt(&current_player, None);
+                } else {
+                    game_state.log.record(GameEvent::PaidTax {
+                        player: current_player.name.get_value(),
+                        amount: *amount,
+                    });
                 }
             }
         }
@@ -209,6 +276,24 @@ pub struct PropertyGroup {
     pub color: &'static str,
 }
 
+/// Agencies of the bank, or taken from it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AgencySupply {
+    pub agencies: usize,
+    pub top_levels: usize,
+}
+
+impl AgencySupply {
+    /// Whether the bank has what the next level of the property takes from it.
+    pub fn has_next_level(&self, property: &Property) -> bool {
+        if property.level() + 1 == property.max_level() {
+            self.top_levels > 0
+        } else {
+            self.agencies > 0
+        }
+    }
+}
+
 #[derive(Debug, Snafu)]
 pub enum OwnedPropertyError {
     #[snafu(transparent)]
@@ -217,6 +302,28 @@ pub enum OwnedPropertyError {
     NotEnoughMoney { source: NotEnoughMoneyError },
 }
 
+#[derive(Debug, Snafu)]
+pub enum MortgagePropertyError {
+    #[snafu(transparent)]
+    NoOwner { source: NoOwnerError },
+    #[snafu(display("Property \"{property_title}\" is mortgaged already"))]
+    AlreadyMortgaged { property_title: &'static str },
+    #[snafu(display(
+        "Cannot mortgage property: sell the agencies of property \"{property_title}\" from the same group first"
+    ))]
+    GroupHasAgencies { property_title: &'static str },
+}
+
+#[derive(Debug, Snafu)]
+pub enum RecoverPropertyError {
+    #[snafu(transparent)]
+    NoOwner { source: NoOwnerError },
+    #[snafu(transparent)]
+    NotEnoughMoney { source: NotEnoughMoneyError },
+    #[snafu(display("Property \"{property_title}\" is not mortgaged"))]
+    NotMortgaged { property_title: &'static str },
+}
+
 #[derive(Debug, Snafu)]
 pub enum FreePropertyError {
     #[snafu(transparent)]
@@ -231,17 +338,51 @@ pub enum BuildAgencyError {
     NoOwner { source: NoOwnerError },
     #[snafu(transparent)]
     NotEnoughMoney { source: NotEnoughMoneyError },
+    #[snafu(transparent)]
+    NotBuildable { source: NotBuildableError },
     #[snafu(display("Cannot build agency: you built one this turn for property \"{property_title}\" from the same group \"{group_title}\""))]
     AlreadyBuilt {
         property_title: &'static str,
         group_title: &'static str,
     },
+    #[snafu(display("Cannot build agency: you should own the whole group \"{group_title}\""))]
+    NoMonopoly { group_title: &'static str },
+    #[snafu(display(
+        "Cannot build agency: property \"{property_title}\" from the same group is mortgaged"
+    ))]
+    GroupMortgaged { property_title: &'static str },
+    #[snafu(display(
+        "Cannot build agency: build one on property \"{property_title}\" from the same group first"
+    ))]
+    UnevenBuilding { property_title: &'static str },
+    #[snafu(display(
+        "Cannot build agency: property \"{property_title}\" has the most agencies already"
+    ))]
+    MaxLevel { property_title: &'static str },
+    #[snafu(display("Cannot build agency: the bank has run out of them"))]
+    BuildShortage,
 }
 
 #[derive(Debug, Snafu)]
 pub enum SellAgencyError {
     #[snafu(transparent)]
     NoOwner { source: NoOwnerError },
+    #[snafu(transparent)]
+    NotBuildable { source: NotBuildableError },
+    #[snafu(display("Cannot sell agency: property \"{property_title}\" has none"))]
+    NoAgencies { property_title: &'static str },
+    #[snafu(display(
+        "Cannot sell agency: sell one from property \"{property_title}\" from the same group first"
+    ))]
+    UnevenSelling { property_title: &'static str },
+    #[snafu(display("Cannot sell top-level agency: the bank has only {agencies} agencies to give back instead of {needed}"))]
+    SellShortage { agencies: usize, needed: usize },
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(display("Property \"{property_title}\" is not a street, agencies cannot be built on it"))]
+pub struct NotBuildableError {
+    pub property_title: &'static str,
 }
 
 #[derive(Debug, Snafu)]
@@ -272,19 +413,52 @@ impl Property {
     }
 
     pub fn reward_for_mortgaging(&self) -> Money {
-        self.data.price / 2
+        self.data
+            .price
+            .checked_div(2)
+            .expect("Halving the price should not overflow")
+    }
+
+    pub fn recovery_price(&self, game_state: &GameState) -> Money {
+        game_state
+            .mortgage_terms()
+            .with_interest(self.reward_for_mortgaging())
     }
 
-    pub fn recovery_price(&self) -> Money {
-        self.data.price * 6 / 10
+    /// Price of the agencies built on the property.
+    pub fn agencies_value(&self) -> Money {
+        match self.ty {
+            PropertyType::Simple {
+                level_price, level, ..
+            } => level_price
+                .checked_mul(level.get() as i64)
+                .expect("Price of the agencies should not overflow"),
+            PropertyType::Transport { .. } | PropertyType::Utility { .. } => 0.into(),
+        }
+    }
+
+    /// Amount the owner has to pay to recover the property, if it is mortgaged.
+    pub fn mortgage_debt(&self, game_state: &GameState) -> Money {
+        if self.mortgaged_for().is_some() {
+            self.recovery_price(game_state)
+        } else {
+            0.into()
+        }
     }
 
     pub fn owner(&self) -> Option<Player> {
         self.owner.get()
     }
 
-    pub fn remove_owner(&self) {
+    /// Gives the property back to the bank without an owner, mortgage or agencies. The agencies
+    /// return to the bank's supply.
+    pub fn return_to_bank(&self) {
         self.owner.set(None);
+        self.mortgaged_for.set(None);
+        self.is_agency_built.set(false);
+        if let PropertyType::Simple { level, .. } = self.ty {
+            level.set(0);
+        }
     }
 
     pub fn mortgaged_for(&self) -> Option<usize> {
@@ -295,7 +469,91 @@ impl Property {
         self.is_agency_built.get()
     }
 
+    /// Amount of agencies built on the property.
+    pub fn level(&self) -> usize {
+        match self.ty {
+            PropertyType::Simple { level, .. } => level.get(),
+            PropertyType::Transport { .. } | PropertyType::Utility { .. } => 0,
+        }
+    }
+
+    /// Level of the top-level agency, or 0 if agencies cannot be built on the property.
+    pub fn max_level(&self) -> usize {
+        match self.ty {
+            PropertyType::Simple { levels, .. } => levels.len() - 1,
+            PropertyType::Transport { .. } | PropertyType::Utility { .. } => 0,
+        }
+    }
+
+    /// Agencies and top-level agencies the property takes from the bank's supply.
+    pub fn supply_used(&self) -> AgencySupply {
+        match self.level() {
+            0 => AgencySupply::default(),
+            level if level == self.max_level() => AgencySupply {
+                agencies: 0,
+                top_levels: 1,
+            },
+            level => AgencySupply {
+                agencies: level,
+                top_levels: 0,
+            },
+        }
+    }
+
+    /// Price of one agency of the property, or 0 if agencies cannot be built on it.
+    pub fn agency_price(&self) -> Money {
+        match self.ty {
+            PropertyType::Simple { level_price, .. } => level_price,
+            PropertyType::Transport { .. } | PropertyType::Utility { .. } => 0.into(),
+        }
+    }
+
+    /// Amount the bank pays back for one agency of the property.
+    pub fn agency_sell_price(&self, game_state: &GameState) -> Money {
+        self.agency_price()
+            .checked_percent(game_state.agency_terms().sell_back_percent)
+            .expect("Sell price of the agency should not overflow")
+    }
+
+    pub fn snapshot(&self, cell: usize) -> PropertySnapshot {
+        PropertySnapshot {
+            cell,
+            owner: self.owner.get().map(|owner| owner.id),
+            level: self.level(),
+            mortgaged_for: self.mortgaged_for.get(),
+            is_agency_built: self.is_agency_built.get(),
+        }
+    }
+
+    pub fn apply_snapshot(&self, snapshot: &PropertySnapshot, players: &HashMap<PlayerId, Player>) {
+        self.owner.set(
+            snapshot
+                .owner
+                .and_then(|owner| players.get(&owner).copied()),
+        );
+        if let PropertyType::Simple { levels, level, .. } = self.ty {
+            level.set(snapshot.level.min(levels.len() - 1));
+        }
+        self.mortgaged_for.set(snapshot.mortgaged_for);
+        self.is_agency_built.set(snapshot.is_agency_built);
+    }
+
     pub fn rent(&self, game_state: &GameState) -> Option<Money> {
+        let dice_total = match self.ty {
+            PropertyType::Utility { .. } => {
+                let (dice1, dice2) = game_state
+                    .rolled_dice()
+                    .expect("Rolled dice should be present when calculating rent");
+                dice1 + dice2
+            }
+            PropertyType::Simple { .. } | PropertyType::Transport { .. } => 0,
+        };
+        self.rent_for_roll(game_state, dice_total)
+    }
+
+    /// Rent for landing on the property with the given sum of the dice, which only utilities
+    /// depend on.
+    pub fn rent_for_roll(&self, game_state: &GameState, dice_total: usize) -> Option<Money> {
         let owner = self.owner.get()?;
         if self.mortgaged_for().is_some() {
             return Some(0.into());
@@ -307,7 +565,8 @@ impl Property {
                 let has_monopoly_on = game_state.has_monopoly_on(&owner, &self.data.group);
 
                 if has_monopoly_on && level.get() == 0 {
-                    rent * 2
+                    rent.checked_mul(2)
+                        .expect("Rent for the monopoly should not overflow")
                 } else {
                     rent
                 }
@@ -318,13 +577,11 @@ impl Property {
             }
 
             PropertyType::Utility { levels } => {
-                let (dice1, dice2) = game_state
-                    .rolled_dice()
-                    .expect("Rolled dice should be present when calculating rent");
                 let (owns, _) = game_state.has_from_group(&owner, &self.data.group);
                 let rent = levels[owns - 1];
 
-                rent * (dice1 + dice2) as i64
+                rent.checked_mul(dice_total as i64)
+                    .expect("Rent for the utility should not overflow")
             }
         })
     }
@@ -346,12 +603,35 @@ impl Property {
             .map_err(Into::into);
         };
 
-        player.withdraw(rent)?;
-        owner.deposit(rent);
+        game_state.transfer(
+            Party::Player(player.id),
+            Party::Player(owner.id),
+            rent,
+            TransferReason::Rent {
+                property: self.data.title.to_owned(),
+            },
+        )?;
+        game_state.record_rent(player, &owner, self.data.title, rent);
+        game_state.log.record(GameEvent::PaidRent {
+            player: player.name.get_value(),
+            owner: owner.name.get_value(),
+            property: self.data.title.to_owned(),
+            rent,
+        });
         Ok(())
     }
 
-    pub fn buy(&self, player: &Player) -> Result<(), FreePropertyError> {
+    pub fn buy(&self, player: &Player, game_state: &GameState) -> Result<(), FreePropertyError> {
+        self.buy_for(player, self.data.price, game_state)
+    }
+
+    /// Buys the property for the given price, instead of the usual one. Used by auctions.
+    pub fn buy_for(
+        &self,
+        player: &Player,
+        price: Money,
+        game_state: &GameState,
+    ) -> Result<(), FreePropertyError> {
         if let Some(owner) = self.owner.get_untracked() {
             return HasOwnerSnafu {
                 property_title: self.data.title,
@@ -362,25 +642,75 @@ impl Property {
             .map_err(Into::into);
         };
 
-        player.withdraw(self.data.price)?;
+        game_state.transfer(
+            Party::Player(player.id),
+            Party::Bank,
+            price,
+            TransferReason::Purchase {
+                property: self.data.title.to_owned(),
+            },
+        )?;
         self.owner.set(Some(*player));
+        game_state.log.record(GameEvent::BoughtProperty {
+            player: player.name.get_value(),
+            property: self.data.title.to_owned(),
+            price,
+        });
         Ok(())
     }
 
-    pub fn mortgage(&self) -> Result<(), NoOwnerError> {
-        let Some(owner) = self.owner.get_untracked() else {
+    /// Checks that the property is not mortgaged yet, and no property of its group has agencies.
+    /// Returns the owner, who would get the mortgage.
+    pub fn check_mortgage(&self, game_state: &GameState) -> Result<Player, MortgagePropertyError> {
+        let Some(owner) = self.owner() else {
             return NoOwnerSnafu {
                 property_title: self.data.title,
             }
-            .fail();
+            .fail()
+            .map_err(Into::into);
         };
+        ensure!(
+            self.mortgaged_for().is_none(),
+            AlreadyMortgagedSnafu {
+                property_title: self.data.title
+            }
+        );
+        if let Some(prop) = game_state
+            .get_properties_by_group(&self.data.group)
+            .into_iter()
+            .find(|prop| prop.level() > 0)
+        {
+            return GroupHasAgenciesSnafu {
+                property_title: prop.data.title,
+            }
+            .fail();
+        }
 
-        self.mortgaged_for.set(Some(15));
-        owner.deposit(self.reward_for_mortgaging());
+        Ok(owner)
+    }
+
+    pub fn mortgage(&self, game_state: &GameState) -> Result<(), MortgagePropertyError> {
+        let owner = untrack(|| self.check_mortgage(game_state))?;
+
+        self.mortgaged_for
+            .set(Some(game_state.mortgage_terms().duration));
+        game_state.pay_from_bank(
+            &owner,
+            self.reward_for_mortgaging(),
+            TransferReason::Mortgage {
+                property: self.data.title.to_owned(),
+            },
+        );
+        game_state.log.record(GameEvent::MortgagedProperty {
+            player: owner.name.get_value(),
+            property: self.data.title.to_owned(),
+            reward: self.reward_for_mortgaging(),
+        });
         Ok(())
     }
 
-    pub fn recover(&self) -> Result<(), OwnedPropertyError> {
+    /// Pays the mortgage back with the interest. Nothing changes, if the owner cannot afford it.
+    pub fn recover(&self, game_state: &GameState) -> Result<(), RecoverPropertyError> {
         let Some(owner) = self.owner.get_untracked() else {
             return NoOwnerSnafu {
                 property_title: self.data.title,
@@ -388,125 +718,223 @@ impl Property {
             .fail()
             .map_err(Into::into);
         };
-
+        ensure!(
+            self.mortgaged_for.get_untracked().is_some(),
+            NotMortgagedSnafu {
+                property_title: self.data.title
+            }
+        );
+
+        let price = untrack(|| self.recovery_price(game_state));
+        game_state.transfer(
+            Party::Player(owner.id),
+            Party::Bank,
+            price,
+            TransferReason::Recovery {
+                property: self.data.title.to_owned(),
+            },
+        )?;
         self.mortgaged_for.set(None);
-        owner.withdraw(self.recovery_price())?;
+        game_state.log.record(GameEvent::RecoveredProperty {
+            player: owner.name.get_value(),
+            property: self.data.title.to_owned(),
+            price,
+        });
         Ok(())
     }
 
-    pub fn tick(&self) {
+    /// Ends the round for the property. Returns the owner, if they forfeited the mortgaged
+    /// property, which then goes back to the bank.
+    pub fn tick(&self, game_state: &GameState) -> Option<Player> {
         self.is_agency_built.set(false);
 
-        let Some(mut mortgaged_for) = self.mortgaged_for.get_untracked() else {
-            return;
-        };
-
-        mortgaged_for = mortgaged_for.saturating_sub(1);
+        let mortgaged_for = self.mortgaged_for.get_untracked()?.saturating_sub(1);
+        let owner = self.owner.get_untracked();
 
         if mortgaged_for == 0 {
-            self.owner.set(None);
-            self.mortgaged_for.set(None)
-        } else {
-            self.mortgaged_for.set(Some(mortgaged_for))
+            if let Some(owner) = owner {
+                game_state.log.record(GameEvent::LostMortgagedProperty {
+                    player: owner.name.get_value(),
+                    property: self.data.title.to_owned(),
+                });
+            }
+            self.return_to_bank();
+            return owner;
         }
+
+        if let Some(owner) =
+            owner.filter(|_| mortgaged_for <= game_state.mortgage_terms().warning_rounds)
+        {
+            game_state.log.record(GameEvent::MortgageExpiring {
+                player: owner.name.get_value(),
+                property: self.data.title.to_owned(),
+                rounds_left: mortgaged_for,
+            });
+        }
+        self.mortgaged_for.set(Some(mortgaged_for));
+        None
     }
 
-    pub fn build_agency(&self, game_state: &GameState) -> Result<(), BuildAgencyError> {
-        let Some(owner) = self.owner.get_untracked() else {
+    /// Checks the building rules: the whole group is owned and not mortgaged, agencies are built
+    /// evenly and at most one per group a round. Returns the owner, who would pay for the agency.
+    pub fn check_build_agency(&self, game_state: &GameState) -> Result<Player, BuildAgencyError> {
+        let Some(owner) = self.owner() else {
             return NoOwnerSnafu {
                 property_title: self.data.title,
             }
             .fail()
             .map_err(Into::into);
         };
+        let PropertyType::Simple { levels, level, .. } = self.ty else {
+            return NotBuildableSnafu {
+                property_title: self.data.title,
+            }
+            .fail()
+            .map_err(Into::into);
+        };
 
-        if let Some(prop) = game_state
-            .get_properties_by_group(&self.data.group)
-            .into_iter()
-            .find(|prop| prop.is_agency_built.get_untracked())
-        {
+        let group = game_state.get_properties_by_group(&self.data.group);
+        ensure!(
+            group.iter().all(|prop| prop.owner() == Some(owner)),
+            NoMonopolySnafu {
+                group_title: self.data.group.title
+            }
+        );
+        if let Some(prop) = group.iter().find(|prop| prop.mortgaged_for().is_some()) {
+            return GroupMortgagedSnafu {
+                property_title: prop.data.title,
+            }
+            .fail();
+        }
+        ensure!(
+            level.get() < levels.len() - 1,
+            MaxLevelSnafu {
+                property_title: self.data.title
+            }
+        );
+        if let Some(prop) = group.iter().find(|prop| prop.level() < level.get()) {
+            return UnevenBuildingSnafu {
+                property_title: prop.data.title,
+            }
+            .fail();
+        }
+        if let Some(prop) = group.iter().find(|prop| prop.is_agency_built()) {
             return AlreadyBuiltSnafu {
                 property_title: prop.data.title,
                 group_title: prop.data.group.title,
             }
             .fail();
         }
+        ensure!(
+            game_state.agency_supply().has_next_level(self),
+            BuildShortageSnafu
+        );
 
-        let PropertyType::Simple {
-            level_price, level, ..
-        } = self.ty
-        else {
-            panic!(
-                "Unable to build agency: property \"{}\" is not a PropertyType::Simple",
-                self.data.title
-            );
+        Ok(owner)
+    }
+
+    pub fn build_agency(&self, game_state: &GameState) -> Result<(), BuildAgencyError> {
+        let PropertyType::Simple { level_price, .. } = self.ty else {
+            return NotBuildableSnafu {
+                property_title: self.data.title,
+            }
+            .fail()
+            .map_err(Into::into);
+        };
+        self.build_agency_for(level_price, game_state)
+    }
+
+    /// Builds an agency for the given price, instead of the usual one. Used by auctions.
+    pub fn build_agency_for(
+        &self,
+        price: Money,
+        game_state: &GameState,
+    ) -> Result<(), BuildAgencyError> {
+        let owner = untrack(|| self.check_build_agency(game_state))?;
+        let PropertyType::Simple { level, .. } = self.ty else {
+            unreachable!("Property type is checked by check_build_agency");
         };
 
-        owner.withdraw(level_price)?;
+        game_state.transfer(
+            Party::Player(owner.id),
+            Party::Bank,
+            price,
+            TransferReason::Agency {
+                property: self.data.title.to_owned(),
+            },
+        )?;
         level.update(|x| *x += 1);
         self.is_agency_built.set(true);
+        game_state.log.record(GameEvent::BuiltAgency {
+            player: owner.name.get_value(),
+            property: self.data.title.to_owned(),
+        });
         Ok(())
     }
 
-    pub fn sell_agency(&self) -> Result<(), SellAgencyError> {
-        let Some(owner) = self.owner.get_untracked() else {
+    /// Checks that the property has an agency, and no other property of the group has more.
+    /// Returns the owner, who would be paid for the agency.
+    pub fn check_sell_agency(&self, game_state: &GameState) -> Result<Player, SellAgencyError> {
+        let Some(owner) = self.owner() else {
             return NoOwnerSnafu {
                 property_title: self.data.title,
             }
             .fail()
             .map_err(Into::into);
         };
+        let PropertyType::Simple { level, .. } = self.ty else {
+            return NotBuildableSnafu {
+                property_title: self.data.title,
+            }
+            .fail()
+            .map_err(Into::into);
+        };
 
-        let PropertyType::Simple {
-            level_price, level, ..
-        } = self.ty
-        else {
-            panic!(
-                "Unable to build agency: property \"{}\" is not a PropertyType::Simple",
-                self.data.title
-            );
+        ensure!(
+            level.get() > 0,
+            NoAgenciesSnafu {
+                property_title: self.data.title
+            }
+        );
+        if let Some(prop) = game_state
+            .get_properties_by_group(&self.data.group)
+            .into_iter()
+            .find(|prop| prop.level() > level.get())
+        {
+            return UnevenSellingSnafu {
+                property_title: prop.data.title,
+            }
+            .fail();
+        }
+        // Top-level agency is replaced with the agencies of the level below it.
+        if level.get() == self.max_level() {
+            let agencies = game_state.agency_supply().agencies;
+            let needed = level.get() - 1;
+            ensure!(agencies >= needed, SellShortageSnafu { agencies, needed });
+        }
+
+        Ok(owner)
+    }
+
+    pub fn sell_agency(&self, game_state: &GameState) -> Result<(), SellAgencyError> {
+        let owner = untrack(|| self.check_sell_agency(game_state))?;
+        let PropertyType::Simple { level, .. } = self.ty else {
+            unreachable!("Property type is checked by check_sell_agency");
         };
 
-        owner.deposit(level_price);
+        game_state.pay_from_bank(
+            &owner,
+            self.agency_sell_price(game_state),
+            TransferReason::AgencySale {
+                property: self.data.title.to_owned(),
+            },
+        );
         level.update(|x| *x -= 1);
         self.is_agency_built.set(false);
+        game_state.log.record(GameEvent::SoldAgency {
+            player: owner.name.get_value(),
+            property: self.data.title.to_owned(),
+        });
         Ok(())
     }
 }
-
-#[derive(
-    Debug,
-    Default,
-    Constructor,
-    Clone,
-    Copy,
-    PartialEq,
-    Eq,
-    PartialOrd,
-    Ord,
-    From,
-    Deref,
-    Not,
-    Neg,
-    Add,
-    Sub,
-    Mul,
-    Div,
-    Rem,
-    AddAssign,
-    SubAssign,
-    MulAssign,
-    DivAssign,
-    RemAssign,
-)]
-pub struct Money(i64);
-
-impl Display for Money {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.0 >= 1000 {
-            write!(f, "{},{:03}", self.0 / 1000, (self.0 % 1000).abs())
-        } else {
-            write!(f, "{}", self.0)
-        }
-    }
-}
@@ -0,0 +1,427 @@
+use std::collections::HashSet;
+
+use leptos::{html::Div, prelude::*, task::spawn_local};
+use shared::{
+    chat::{ChatChannel, ChatMessage, MAX_CHAT_MESSAGE_LENGTH},
+    player::PlayerId,
+};
+use tailwind_merge::tw;
+use wasm_bindgen::JsValue;
+
+use crate::{game_log::LogEntry, game_state::GameState, i18n::I18n, server::ServerConnection};
+
+/// Distance from the bottom (in pixels), within which the chat is considered scrolled down.
+const STICK_TO_BOTTOM_THRESHOLD: i32 = 24;
+const FALLBACK_SENDER_COLOR: &str = "#d1d5db";
+const EXPORT_FILE_NAME: &str = "rustopoly-log.txt";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FeedFilter {
+    All,
+    Chat,
+    /// Chat of the spectators, which the players do not see.
+    Spectators,
+    Log,
+}
+
+impl FeedFilter {
+    const VARIANTS: [FeedFilter; 4] = [
+        FeedFilter::All,
+        FeedFilter::Chat,
+        FeedFilter::Spectators,
+        FeedFilter::Log,
+    ];
+
+    fn label(&self, i18n: I18n) -> String {
+        let key = match self {
+            FeedFilter::All => "chat.all",
+            FeedFilter::Chat => "chat.chat",
+            FeedFilter::Spectators => "chat.spectators",
+            FeedFilter::Log => "chat.log",
+        };
+        i18n.t(key, &[])
+    }
+
+    fn shows_chat(&self, channel: ChatChannel) -> bool {
+        match self {
+            FeedFilter::All => true,
+            FeedFilter::Chat => channel == ChatChannel::Players,
+            FeedFilter::Spectators => channel == ChatChannel::Spectators,
+            FeedFilter::Log => false,
+        }
+    }
+
+    fn shows_log(&self) -> bool {
+        matches!(self, FeedFilter::All | FeedFilter::Log)
+    }
+}
+
+/// Chat message or game event, as shown in the chat panel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FeedItem {
+    Chat(ChatMessage),
+    Log(LogEntry),
+}
+
+impl FeedItem {
+    fn at(&self) -> u64 {
+        match self {
+            FeedItem::Chat(message) => message.sent_at,
+            FeedItem::Log(entry) => entry.at,
+        }
+    }
+
+    fn to_text(&self, i18n: I18n) -> String {
+        match self {
+            FeedItem::Chat(message) => format!(
+                "[{}] {}: {}",
+                format_time(message.sent_at),
+                message.sender_name,
+                message.text
+            ),
+            FeedItem::Log(entry) => {
+                format!("[{}] * {}", format_time(entry.at), i18n.event(&entry.event))
+            }
+        }
+    }
+}
+
+#[component]
+pub fn ChatPanel() -> impl IntoView {
+    let connection = ServerConnection::use_context();
+    let game_state = GameState::use_context();
+    let i18n = game_state.i18n;
+    let messages_ref = NodeRef::<Div>::new();
+    let is_at_bottom = RwSignal::new(true);
+    let unread = RwSignal::new(0usize);
+    let seen = StoredValue::new((0usize, 0usize));
+    let filter = RwSignal::new(FeedFilter::All);
+    let muted = RwSignal::new(HashSet::<PlayerId>::new());
+    let draft = RwSignal::new(String::new());
+    let error = RwSignal::new(None::<String>);
+
+    let self_player_id = move || connection.self_player().map(|(player_id, _)| player_id);
+    let scroll_to_bottom = move || {
+        if let Some(messages) = messages_ref.get_untracked() {
+            messages.set_scroll_top(messages.scroll_height());
+        }
+    };
+
+    // Both chat and log are sorted by time already, so they only need to be merged.
+    let feed = Memo::new(move |_| {
+        let filter = filter.get();
+        let chat = connection.chat().get();
+        let log = game_state.log.entries().get();
+        let mut chat = chat
+            .into_iter()
+            .filter(|message| filter.shows_chat(message.channel))
+            .map(FeedItem::Chat)
+            .enumerate()
+            .peekable();
+        let mut log = log
+            .into_iter()
+            .filter(|_| filter.shows_log())
+            .map(FeedItem::Log)
+            .enumerate()
+            .peekable();
+
+        let mut feed = Vec::new();
+        loop {
+            let next = match (chat.peek(), log.peek()) {
+                (Some((_, message)), Some((_, entry))) if message.at() <= entry.at() => chat.next(),
+                (Some(_), None) => chat.next(),
+                (_, Some(_)) => log.next(),
+                (None, None) => break,
+            };
+            feed.extend(next);
+        }
+        feed
+    });
+
+    let export_href = move || {
+        let text = feed.with(|feed| {
+            feed.iter()
+                .map(|(_, item)| item.to_text(i18n))
+                .collect::<Vec<_>>()
+                .join("\n")
+        });
+        format!(
+            "data:text/plain;charset=utf-8,{}",
+            js_sys::encode_uri_component(&text)
+        )
+    };
+
+    // New messages either scroll the chat down, or are counted as unread while the player is
+    // reading the history.
+    Effect::new(move |_| {
+        let filter = filter.get_untracked();
+        let (chat_seen, log_seen) = seen.get_value();
+        let (chat_total, new_chat) = connection.chat().with(|chat| {
+            let new = chat.get(chat_seen..).unwrap_or_default();
+            let shown = new
+                .iter()
+                .filter(|message| filter.shows_chat(message.channel))
+                .count();
+            (chat.len(), shown)
+        });
+        let log_total = game_state.log.entries().with(Vec::len);
+        seen.set_value((chat_total, log_total));
+
+        let new = new_chat + log_total.saturating_sub(log_seen) * usize::from(filter.shows_log());
+
+        if is_at_bottom.get_untracked() {
+            request_animation_frame(scroll_to_bottom);
+        } else {
+            unread.update(|unread| *unread += new);
+        }
+    });
+
+    let on_scroll = move |_| {
+        let Some(messages) = messages_ref.get_untracked() else {
+            return;
+        };
+
+        let distance = messages.scroll_height() - messages.scroll_top() - messages.client_height();
+        let at_bottom = distance <= STICK_TO_BOTTOM_THRESHOLD;
+        is_at_bottom.set(at_bottom);
+        if at_bottom {
+            unread.set(0);
+        }
+    };
+
+    let send = move || {
+        let text = draft.get_untracked().trim().to_owned();
+        if text.is_empty() {
+            return;
+        }
+
+        spawn_local(async move {
+            match connection.send_chat_message(text).await {
+                Ok(()) => {
+                    draft.set(String::new());
+                    error.set(None);
+                }
+                Err(err) => error.set(Some(err.to_string())),
+            }
+        });
+    };
+
+    let sender_color = move |sender_id: PlayerId| {
+        game_state
+            .get_players()
+            .get(&sender_id)
+            .map(|player| player.color.get_text_color())
+            .unwrap_or(FALLBACK_SENDER_COLOR)
+    };
+
+    let toggle_mute = move |player_id: PlayerId| {
+        muted.update(|muted| {
+            if !muted.remove(&player_id) {
+                muted.insert(player_id);
+            }
+        })
+    };
+
+    let muted_names = move || {
+        let muted = muted.get();
+        let mut names = connection.chat().with(|chat| {
+            chat.iter()
+                .filter(|message| muted.contains(&message.sender_id))
+                .map(|message| (message.sender_id, message.sender_name.clone()))
+                .collect::<Vec<_>>()
+        });
+        names.sort();
+        names.dedup();
+        names
+    };
+
+    view! {
+        <div class="flex absolute inset-0 flex-col p-2 text-sm text-white">
+            <div class="flex gap-1 pb-1 text-xs">
+                {FeedFilter::VARIANTS
+                    .into_iter()
+                    .filter(|variant| {
+                        *variant != FeedFilter::Spectators || game_state.is_spectating()
+                    })
+                    .map(|variant| {
+                        view! {
+                            <button
+                                class=move || {
+                                    tw!(
+                                        "px-2 rounded", filter.get() == variant => "bg-black/30"
+                                    )
+                                }
+                                on:click=move |_| {
+                                    filter.set(variant);
+                                    unread.set(0);
+                                    request_animation_frame(scroll_to_bottom);
+                                }
+                            >
+                                {move || variant.label(i18n)}
+                            </button>
+                        }
+                    })
+                    .collect_view()}
+                <a
+                    class="px-2 ml-auto rounded hover:bg-black/30"
+                    download=EXPORT_FILE_NAME
+                    href=export_href
+                >
+                    {move || i18n.t("chat.export", &[])}
+                </a>
+            </div>
+            <Show when=move || !muted.with(HashSet::is_empty)>
+                <div class="flex flex-wrap gap-1 pb-1 text-xs opacity-75">
+                    {move || i18n.t("chat.muted", &[])}
+                    <For
+                        each=muted_names
+                        key=|(player_id, _)| *player_id
+                        children=move |(player_id, name)| {
+                            view! {
+                                <button
+                                    class="px-1 rounded bg-black/20"
+                                    title=move || i18n.t("chat.unmute", &[])
+                                    on:click=move |_| toggle_mute(player_id)
+                                >
+                                    {name}
+                                    " ×"
+                                </button>
+                            }
+                        }
+                    />
+                </div>
+            </Show>
+            <div
+                node_ref=messages_ref
+                class="overflow-y-auto flex-1 min-h-0"
+                on:scroll=on_scroll
+            >
+                <For
+                    each=move || feed.get()
+                    key=|(index, item)| {
+                        (*index, matches!(item, FeedItem::Log(_)), item.at())
+                    }
+                    children=move |(_, item)| match item {
+                        FeedItem::Chat(message) => {
+                            let sender_id = message.sender_id;
+                            let is_own = self_player_id() == Some(sender_id);
+                            view! {
+                                <ChatMessageView
+                                    message
+                                    color=sender_color(sender_id)
+                                    is_own
+                                    is_muted=Signal::derive(move || {
+                                        muted.with(|muted| muted.contains(&sender_id))
+                                    })
+                                    on_toggle_mute=move || toggle_mute(sender_id)
+                                />
+                            }
+                                .into_any()
+                        }
+                        FeedItem::Log(entry) => view! { <LogEntryView entry /> }.into_any(),
+                    }
+                />
+            </div>
+            <Show when=move || { unread.get() > 0 }>
+                <button
+                    class="self-center px-2 -mt-7 mb-1 rounded-full bg-cyan-900"
+                    on:click=move |_| {
+                        scroll_to_bottom();
+                        unread.set(0);
+                    }
+                >
+                    {move || i18n.t("chat.new_messages", &[("count", &unread.get())])}
+                </button>
+            </Show>
+            {move || error.get().map(|error| view! { <div class="text-red-300">{error}</div> })}
+            <form
+                class="flex gap-2 pt-2"
+                on:submit=move |event| {
+                    event.prevent_default();
+                    send();
+                }
+            >
+                <input
+                    class="flex-1 p-1 min-w-0 text-black rounded"
+                    placeholder=move || {
+                        if game_state.is_spectating() {
+                            i18n.t("chat.message_spectators", &[])
+                        } else {
+                            i18n.t("chat.message", &[])
+                        }
+                    }
+                    maxlength=MAX_CHAT_MESSAGE_LENGTH.to_string()
+                    prop:value=move || draft.get()
+                    on:input=move |event| draft.set(event_target_value(&event))
+                />
+                <button class="px-2 rounded border-2" type="submit">
+                    {move || i18n.t("chat.send", &[])}
+                </button>
+            </form>
+        </div>
+    }
+}
+
+#[component]
+fn ChatMessageView(
+    message: ChatMessage,
+    color: &'static str,
+    is_own: bool,
+    #[prop(into)] is_muted: Signal<bool>,
+    #[prop(into)] on_toggle_mute: Callback<()>,
+) -> impl IntoView {
+    let ChatMessage {
+        sender_name,
+        channel,
+        text,
+        sent_at,
+        ..
+    } = message;
+    let i18n = I18n::use_context();
+
+    view! {
+        <Show when=move || !is_muted.get()>
+            <div class="group flex gap-1 items-baseline py-0.5">
+                <span class="text-xs opacity-50">{format_time(sent_at)}</span>
+                <span class="font-bold" style:color=color>
+                    {sender_name.clone()}
+                </span>
+                {(channel == ChatChannel::Spectators)
+                    .then(|| {
+                        view! {
+                            <span class="text-xs opacity-50">
+                                {move || i18n.t("chat.spectator", &[])}
+                            </span>
+                        }
+                    })}
+                <span class="break-words min-w-0 grow">{text.clone()}</span>
+                <button
+                    class=tw!("hidden text-xs opacity-50 hover:opacity-100", !is_own => "group-hover:inline")
+                    title=move || i18n.t("chat.mute_title", &[])
+                    on:click=move |_| on_toggle_mute.run(())
+                >
+                    {move || i18n.t("chat.mute", &[])}
+                </button>
+            </div>
+        </Show>
+    }
+}
+
+#[component]
+fn LogEntryView(entry: LogEntry) -> impl IntoView {
+    let i18n = I18n::use_context();
+    let at = format_time(entry.at);
+
+    view! {
+        <div class="flex gap-1 items-baseline py-0.5 italic text-cyan-100">
+            <span class="text-xs not-italic opacity-50">{at}</span>
+            <span class="break-words min-w-0">{move || i18n.event(&entry.event)}</span>
+        </div>
+    }
+}
+
+/// Formats milliseconds since the Unix epoch as local "HH:MM".
+fn format_time(millis: u64) -> String {
+    let date = js_sys::Date::new(&JsValue::from_f64(millis as f64));
+    format!("{:02}:{:02}", date.get_hours(), date.get_minutes())
+}
@@ -0,0 +1,33 @@
+use std::time::Duration;
+
+use leptos::prelude::*;
+use tailwind_merge::tw;
+
+use crate::utils::clock;
+
+const TICK_INTERVAL: Duration = Duration::from_millis(250);
+/// Amount of seconds left, after which the countdown turns red.
+const URGENT_SECONDS: u64 = 5;
+
+/// Seconds left until `deadline` (value of [`clock::now_ms`]).
+#[component]
+pub fn CountdownTimer(
+    deadline: f64,
+    #[prop(into, optional)] class: Signal<String>,
+) -> impl IntoView {
+    let now = RwSignal::new(clock::now_ms());
+    let handle = set_interval_with_handle(move || now.set(clock::now_ms()), TICK_INTERVAL).ok();
+    on_cleanup(move || {
+        if let Some(handle) = handle {
+            handle.clear();
+        }
+    });
+
+    let seconds_left = move || ((deadline - now.get()) / 1000.).ceil().max(0.) as u64;
+
+    view! {
+        <span class=move || {
+            tw!("tabular-nums", seconds_left() <= URGENT_SECONDS => "text-red-500", class())
+        }>{move || format!("{}s", seconds_left())}</span>
+    }
+}
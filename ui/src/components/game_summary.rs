@@ -0,0 +1,417 @@
+use std::{collections::HashMap, time::Duration};
+
+use leptos::{prelude::*, task::spawn_local};
+use leptos_router::hooks::use_navigate;
+use shared::game::GameStats;
+use tailwind_merge::tw;
+
+use crate::{
+    cell::Money,
+    game_state::GameState,
+    player::{Player, PlayerId},
+    server::ServerConnection,
+};
+
+const CHART_WIDTH: f64 = 400.;
+const CHART_HEIGHT: f64 = 160.;
+const REPLAY_STEP_INTERVAL: Duration = Duration::from_millis(700);
+/// Amount of events before the current one, shown by the replay.
+const REPLAY_CONTEXT: usize = 6;
+
+/// Statistics of the finished game, with the buttons to play it again or replay its log.
+#[component]
+pub fn GameSummary() -> impl IntoView {
+    let game_state = GameState::use_context();
+    let i18n = game_state.i18n;
+    let is_replaying = RwSignal::new(false);
+
+    view! {
+        <div class="flex flex-col gap-4 p-6 w-full max-w-2xl bg-gray-900 rounded-lg max-h-[90vh] overflow-y-auto">
+            <h2 class="text-2xl font-bold text-center">
+                {move || {
+                    if game_state.is_drawn() {
+                        i18n.t("summary.draw", &[])
+                    } else {
+                        i18n.t("summary.finished", &[])
+                    }
+                }}
+            </h2>
+            <Show
+                when=move || is_replaying.get()
+                fallback=move || {
+                    view! {
+                        <Ranking />
+                        <NetWorthChart />
+                        <RentTable />
+                        <div class="flex gap-6 justify-center text-sm opacity-80">
+                            <span>
+                                {move || {
+                                    i18n.t(
+                                        "summary.rounds_played",
+                                        &[("count", &game_state.current_round())],
+                                    )
+                                }}
+                            </span>
+                            {move || {
+                                most_profitable_property(&game_state.stats())
+                                    .map(|(title, income)| {
+                                        let income = i18n.money(income);
+                                        let text = i18n
+                                            .t(
+                                                "summary.most_profitable",
+                                                &[("property", &title), ("income", &income)],
+                                            );
+                                        view! { <span>{text}</span> }
+                                    })
+                            }}
+                        </div>
+                    }
+                }
+            >
+                <Replay />
+            </Show>
+            <div class="flex gap-3 justify-center">
+                <RematchButton />
+                <button
+                    class="p-2 rounded border-2"
+                    on:click=move |_| is_replaying.update(|is_replaying| *is_replaying = !*is_replaying)
+                >
+                    {move || {
+                        if is_replaying.get() {
+                            i18n.t("summary.statistics", &[])
+                        } else {
+                            i18n.t("summary.replay", &[])
+                        }
+                    }}
+                </button>
+                <a class="p-2 rounded border-2" href="/lobby">
+                    {move || i18n.t("summary.back_to_lobby", &[])}
+                </a>
+            </div>
+        </div>
+    }
+}
+
+/// Players still in the game by their net worth, then the eliminated ones, last to lose first.
+#[component]
+fn Ranking() -> impl IntoView {
+    let game_state = GameState::use_context();
+    let i18n = game_state.i18n;
+
+    let ranking = move || {
+        let players = game_state.get_players();
+        let left = game_state
+            .standings()
+            .into_iter()
+            .filter(|(player, _)| !player.has_lost());
+        let eliminated = game_state
+            .stats()
+            .eliminated
+            .into_iter()
+            .rev()
+            .filter_map(|player_id| players.get(&player_id).copied())
+            .map(|player| (player, game_state.net_worth(&player)));
+
+        left.chain(eliminated).collect::<Vec<_>>()
+    };
+
+    view! {
+        <ol class="flex flex-col gap-1">
+            {move || {
+                ranking()
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, (player, net_worth))| {
+                        view! {
+                            <li class=tw!(
+                                "flex gap-3 items-center p-2 rounded bg-black/30",
+                                index == 0 => "font-bold"
+                            )>
+                                <span class="w-6 text-right">{format!("{}.", index + 1)}</span>
+                                <span class="grow" style:color=player.color.get_text_color()>
+                                    {player.name.get_value()}
+                                </span>
+                                <span class="tabular-nums">
+                                    {format!("{}$", i18n.money(net_worth))}
+                                </span>
+                            </li>
+                        }
+                    })
+                    .collect_view()
+            }}
+        </ol>
+    }
+}
+
+/// Net worth of every player at the start of the game and at the end of every round.
+#[component]
+fn NetWorthChart() -> impl IntoView {
+    let game_state = GameState::use_context();
+    let i18n = game_state.i18n;
+
+    let lines = move || {
+        let history = game_state.stats().net_worth_history;
+        let players = game_state.get_players();
+        let max = history
+            .iter()
+            .flatten()
+            .map(|(_, net_worth)| i64::from(*net_worth))
+            .max()
+            .unwrap_or_default()
+            .max(1) as f64;
+        let step = CHART_WIDTH / history.len().saturating_sub(1).max(1) as f64;
+
+        let mut points = HashMap::<PlayerId, Vec<String>>::new();
+        for (round, net_worths) in history.iter().enumerate() {
+            for (player_id, net_worth) in net_worths {
+                let x = round as f64 * step;
+                let y = CHART_HEIGHT - i64::from(*net_worth).max(0) as f64 / max * CHART_HEIGHT;
+                points
+                    .entry(*player_id)
+                    .or_default()
+                    .push(format!("{x:.1},{y:.1}"));
+            }
+        }
+
+        points
+            .into_iter()
+            .filter_map(|(player_id, points)| {
+                let player = players.get(&player_id)?;
+                Some((*player, points.join(" ")))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    view! {
+        <div class="flex flex-col gap-1">
+            <div class="text-sm opacity-80">{move || i18n.t("summary.net_worth", &[])}</div>
+            <svg
+                class="w-full h-40 rounded bg-black/30"
+                viewBox=format!("0 0 {CHART_WIDTH} {CHART_HEIGHT}")
+                preserveAspectRatio="none"
+            >
+                {move || {
+                    lines()
+                        .into_iter()
+                        .map(|(player, points): (Player, String)| {
+                            view! {
+                                <polyline
+                                    points=points
+                                    fill="none"
+                                    stroke=player.color.get_text_color()
+                                    stroke-width="2"
+                                    vector-effect="non-scaling-stroke"
+                                />
+                            }
+                        })
+                        .collect_view()
+                }}
+            </svg>
+        </div>
+    }
+}
+
+#[component]
+fn RentTable() -> impl IntoView {
+    let game_state = GameState::use_context();
+    let i18n = game_state.i18n;
+
+    let rows = move || {
+        let stats = game_state.stats();
+        let mut players = game_state.get_players().into_values().collect::<Vec<_>>();
+        players.sort_by_key(|player| player.id);
+        players
+            .into_iter()
+            .map(|player| {
+                let paid = stats.rent_paid.get(&player.id).copied().unwrap_or_default();
+                let received = stats
+                    .rent_received
+                    .get(&player.id)
+                    .copied()
+                    .unwrap_or_default();
+                (player, paid, received)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    view! {
+        <table class="text-sm">
+            <thead>
+                <tr class="opacity-80">
+                    <th class="text-left">{move || i18n.t("summary.player", &[])}</th>
+                    <th class="text-right">{move || i18n.t("summary.rent_paid", &[])}</th>
+                    <th class="text-right">{move || i18n.t("summary.rent_received", &[])}</th>
+                </tr>
+            </thead>
+            <tbody>
+                {move || {
+                    rows()
+                        .into_iter()
+                        .map(|(player, paid, received)| {
+                            view! {
+                                <tr>
+                                    <td style:color=player.color.get_text_color()>
+                                        {player.name.get_value()}
+                                    </td>
+                                    <td class="tabular-nums text-right">
+                                        {format!("{}$", i18n.money(paid))}
+                                    </td>
+                                    <td class="tabular-nums text-right">
+                                        {format!("{}$", i18n.money(received))}
+                                    </td>
+                                </tr>
+                            }
+                        })
+                        .collect_view()
+                }}
+            </tbody>
+        </table>
+    }
+}
+
+/// Starts the rematch, or follows the one someone else started.
+#[component]
+fn RematchButton() -> impl IntoView {
+    let connection = ServerConnection::use_context();
+    let game_state = GameState::use_context();
+    let i18n = game_state.i18n;
+    let navigate = use_navigate();
+    let error = RwSignal::new(None::<String>);
+
+    let rematch = move || {
+        let navigate = navigate.clone();
+        spawn_local(async move {
+            match connection.rematch().await {
+                Ok(room_id) => navigate(&format!("/game/{room_id}"), Default::default()),
+                Err(err) => error.set(Some(err.to_string())),
+            }
+        });
+    };
+
+    move || match connection.rematch_room() {
+        Some(room_id) if game_state.is_spectating() => view! {
+            <a class="p-2 rounded border-2" href=format!("/watch/{room_id}")>
+                {move || i18n.t("summary.watch_rematch", &[])}
+            </a>
+        }
+        .into_any(),
+        Some(room_id) => view! {
+            <a class="p-2 rounded border-2" href=format!("/game/{room_id}")>
+                {move || i18n.t("summary.join_rematch", &[])}
+            </a>
+        }
+        .into_any(),
+        None if game_state.is_spectating() => ().into_any(),
+        None => {
+            let rematch = rematch.clone();
+            view! {
+                <button
+                    class="p-2 rounded border-2"
+                    title=move || error.get().unwrap_or_default()
+                    on:click=move |_| rematch()
+                >
+                    {move || i18n.t("summary.rematch", &[])}
+                </button>
+            }
+            .into_any()
+        }
+    }
+}
+
+/// Steps through the game log, event by event.
+#[component]
+fn Replay() -> impl IntoView {
+    let game_state = GameState::use_context();
+    let i18n = game_state.i18n;
+    let step = RwSignal::new(0usize);
+    let is_playing = RwSignal::new(false);
+
+    let total = move || game_state.log.entries().with(Vec::len);
+    let next = move || {
+        if step.get_untracked() + 1 < total() {
+            step.update(|step| *step += 1);
+        } else {
+            is_playing.set(false);
+        }
+    };
+
+    let handle = set_interval_with_handle(
+        move || {
+            if is_playing.get_untracked() {
+                next();
+            }
+        },
+        REPLAY_STEP_INTERVAL,
+    )
+    .ok();
+    on_cleanup(move || {
+        if let Some(handle) = handle {
+            handle.clear();
+        }
+    });
+
+    let shown = move || {
+        let step = step.get();
+        game_state.log.entries().with(|entries| {
+            entries
+                .iter()
+                .enumerate()
+                .take(step + 1)
+                .skip((step + 1).saturating_sub(REPLAY_CONTEXT + 1))
+                .map(|(index, entry)| (index == step, i18n.event(&entry.event)))
+                .collect::<Vec<_>>()
+        })
+    };
+
+    view! {
+        <div class="flex flex-col gap-1 p-2 text-sm rounded min-h-48 bg-black/30">
+            {move || {
+                shown()
+                    .into_iter()
+                    .map(|(is_current, text)| {
+                        view! {
+                            <div class=tw!("italic text-cyan-100", !is_current => "opacity-50")>
+                                {text}
+                            </div>
+                        }
+                    })
+                    .collect_view()
+            }}
+        </div>
+        <div class="flex gap-3 justify-center items-center">
+            <button
+                class="px-2 rounded border-2"
+                on:click=move |_| step.update(|step| *step = step.saturating_sub(1))
+            >
+                "◀"
+            </button>
+            <button
+                class="px-2 rounded border-2"
+                on:click=move |_| is_playing.update(|is_playing| *is_playing = !*is_playing)
+            >
+                {move || {
+                    if is_playing.get() {
+                        i18n.t("summary.pause", &[])
+                    } else {
+                        i18n.t("summary.play", &[])
+                    }
+                }}
+            </button>
+            <button class="px-2 rounded border-2" on:click=move |_| next()>
+                "▶"
+            </button>
+            <span class="text-sm tabular-nums opacity-80">
+                {move || format!("{}/{}", (step.get() + 1).min(total()), total())}
+            </span>
+        </div>
+    }
+}
+
+/// Title of the property that collected the most rent, and the rent it collected.
+fn most_profitable_property(stats: &GameStats) -> Option<(String, Money)> {
+    stats
+        .property_income
+        .iter()
+        .max_by_key(|(_, income)| **income)
+        .map(|(title, income)| (title.clone(), *income))
+}
@@ -1,125 +1,326 @@
-use std::sync::Arc;
+use std::{collections::VecDeque, sync::Arc};
 
 use derive_more::derive::Debug;
-use futures::{channel::mpsc, SinkExt, StreamExt};
-use leptos::{either::EitherOf3, prelude::*, task::spawn_local};
+use futures::{channel::mpsc, StreamExt};
+use leptos::{either::EitherOf3, prelude::*};
 use tailwind_merge::tw;
 
-use crate::game_state::GameState;
+use crate::{
+    cell::Cell, components::countdown::CountdownTimer, game_state::GameState,
+    server::ServerConnection,
+};
+
+/// Keys that decline the move the prompt offers, e.g. buying.
+pub const CANCEL_SHORTCUTS: &str = "D Escape";
 
 #[component]
 pub fn InGameModal() -> impl IntoView {
     let game_state = GameState::use_context();
-    let is_hidden = move || game_state.in_game_modal_state.ty.get().is_none();
+    let connection = ServerConnection::use_context();
+    let modal_state = game_state.in_game_modal_state;
+    let i18n = game_state.i18n;
+    let is_hidden = move || modal_state.queue.with(VecDeque::is_empty);
+    let self_countdown = move || {
+        let (self_player_id, _) = connection.self_player()?;
+        connection.countdown(self_player_id)
+    };
 
     Effect::new(move |_| {
-        if game_state.self_player.has_lost() {
-            game_state.in_game_modal_state.ty.set(None);
+        if game_state
+            .self_player
+            .is_some_and(|self_player| self_player.has_lost())
+        {
+            modal_state.close();
         }
     });
 
     view! {
-        <div class=move || {
-            tw!(
-                "absolute top-2 right-2 left-2 p-3 text-black bg-white rounded-md",
-                is_hidden() => "hidden"
-            )
-        }>
-            {move || match game_state.in_game_modal_state.ty.get() {
-                None => EitherOf3::A(()),
-                Some(InGameModalStateType::OneButton { text, button_text, on_click }) => {
-                    EitherOf3::B(
+        <div
+            class=move || {
+                tw!(
+                    "absolute top-2 right-2 left-2 p-3 text-black bg-white rounded-md",
+                    is_hidden() => "hidden"
+                )
+            }
+            role="alertdialog"
+            aria-label=move || i18n.t("modal.your_move", &[])
+            aria-hidden=move || is_hidden().to_string()
+        >
+            {move || {
+                self_countdown()
+                    .map(|active| {
                         view! {
-                            <InGameModalView
-                                text
-                                left_button_text=button_text
-                                on_left_button_click=move || on_click()
-                            />
-                        },
-                    )
-                }
-                Some(
-                    InGameModalStateType::TwoButtons {
-                        text,
-                        ok_button_text,
-                        cancel_button_text,
-                        on_ok_click,
-                        on_cancel_click,
-                    },
-                ) => {
-                    EitherOf3::C(
+                            <CountdownTimer deadline=active.deadline class="float-right font-bold" />
+                        }
+                    })
+            }}
+            {move || {
+                modal_state
+                    .queue
+                    .with(|queue| queue.front().cloned())
+                    .map(|queued| view! { <PromptView queued /> })
+            }}
+            {move || {
+                let waiting = modal_state.queue.with(VecDeque::len).saturating_sub(1);
+                (waiting > 0)
+                    .then(|| {
                         view! {
-                            <InGameModalView
-                                text
-                                left_button_text=ok_button_text
-                                on_left_button_click=move || on_ok_click()
-                                right_button_text=cancel_button_text
-                                on_right_button_click=move || on_cancel_click()
-                            />
-                        },
-                    )
-                }
+                            <p class="mt-2 text-xs opacity-60">
+                                {i18n.t("modal.waiting", &[("count", &waiting)])}
+                            </p>
+                        }
+                    })
             }}
         </div>
     }
 }
 
 #[component]
-pub fn InGameModalView(
-    text: String,
-    left_button_text: String,
-    #[prop(into)] on_left_button_click: Callback<()>,
-    #[prop(optional)] right_button_text: String,
-    #[prop(optional, into)] on_right_button_click: Option<Callback<()>>,
-) -> impl IntoView {
-    view! {
-        <>
-            <p>{text}</p>
-            <button
-                class="p-2 mt-3 rounded border-2"
-                on:click=move |_| on_left_button_click.run(())
-            >
-                {left_button_text}
-            </button>
-            {move || {
-                on_right_button_click
-                    .map(|on_right_button_click| {
-                        view! {
-                            <button
-                                class="p-2 ml-3 rounded border-2"
-                                on:click=move |_| on_right_button_click.run(())
-                            >
-                                {right_button_text.clone()}
-                            </button>
+fn PromptView(queued: QueuedPrompt) -> impl IntoView {
+    let game_state = GameState::use_context();
+    let i18n = game_state.i18n;
+    let QueuedPrompt {
+        prompt, respond, ..
+    } = queued;
+    let shortcut = prompt.action.map(PromptAction::shortcut);
+    let respond = StoredValue::new(respond);
+    let answer = move |response: PromptResponse| respond.with_value(|respond| respond(response));
+
+    let body = match prompt.kind {
+        PromptKind::Choices(choices) => EitherOf3::A(
+            choices
+                .into_iter()
+                .enumerate()
+                .map(|(index, choice)| {
+                    view! {
+                        <button
+                            class="p-2 mt-3 mr-3 rounded border-2"
+                            aria-keyshortcuts=shortcut.filter(|_| index == 0)
+                            title=shortcut.filter(|_| index == 0)
+                            on:click=move |_| answer(PromptResponse::Choice(index))
+                        >
+                            {choice}
+                        </button>
+                    }
+                })
+                .collect_view(),
+        ),
+        PromptKind::Number {
+            min,
+            max,
+            submit_text,
+        } => {
+            let draft = RwSignal::new(min.to_string());
+            let value = move || {
+                draft
+                    .get()
+                    .trim()
+                    .parse::<i64>()
+                    .ok()
+                    .filter(|value| (min..=max).contains(value))
+            };
+            EitherOf3::B(view! {
+                <form
+                    class="inline-flex gap-3 items-center mt-3 mr-3"
+                    on:submit=move |event| {
+                        event.prevent_default();
+                        if let Some(value) = value() {
+                            answer(PromptResponse::Number(value));
                         }
-                    })
-            }}
-        </>
+                    }
+                >
+                    <input
+                        class="p-2 w-28 rounded border-2"
+                        type="number"
+                        min=min.to_string()
+                        max=max.to_string()
+                        aria-label=move || i18n.t("modal.number_range", &[("min", &min), ("max", &max)])
+                        prop:value=draft
+                        on:input=move |event| draft.set(event_target_value(&event))
+                    />
+                    <button class="p-2 rounded border-2" type="submit" disabled=move || value().is_none()>
+                        {submit_text}
+                    </button>
+                </form>
+            })
+        }
+        PromptKind::Property { cells } => EitherOf3::C(
+            cells
+                .into_iter()
+                .filter_map(|cell| match game_state.get_cell(cell) {
+                    Cell::Property(property) => Some((cell, property)),
+                    _ => None,
+                })
+                .map(|(cell, property)| {
+                    view! {
+                        <button
+                            class="p-2 mt-3 mr-3 rounded border-2"
+                            style:border-color=property.data.group.color
+                            on:click=move |_| answer(PromptResponse::Property(cell))
+                        >
+                            {property.data.title}
+                        </button>
+                    }
+                })
+                .collect_view(),
+        ),
+    };
+
+    view! {
+        <p>{prompt.text}</p>
+        {body}
+        {prompt
+            .cancel_text
+            .map(|cancel_text| {
+                view! {
+                    <button
+                        class="p-2 mt-3 rounded border-2"
+                        aria-keyshortcuts=shortcut.map(|_| CANCEL_SHORTCUTS)
+                        title=move || shortcut.map(|_| i18n.t("modal.decline_shortcut", &[]))
+                        on:click=move |_| answer(PromptResponse::Cancel)
+                    >
+                        {cancel_text}
+                    </button>
+                }
+            })}
     }
 }
 
-#[derive(Debug, Default, Clone, Copy)]
-pub struct InGameModalState {
-    ty: RwSignal<Option<InGameModalStateType>>,
+/// What the player is asked to answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PromptKind {
+    /// A button for every choice.
+    Choices(Vec<String>),
+    /// Number within the bounds, including both.
+    Number {
+        min: i64,
+        max: i64,
+        submit_text: String,
+    },
+    /// One of the properties on the given cells.
+    Property { cells: Vec<usize> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptResponse {
+    /// Index of the chosen choice.
+    Choice(usize),
+    Number(i64),
+    /// Cell of the chosen property.
+    Property(usize),
+    /// The player pressed the cancel button.
+    Cancel,
+}
+
+/// Move in the game which the first button of a prompt makes. Each has its own key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptAction {
+    Roll,
+    Buy,
+    /// Paying or taking the money which closes the turn.
+    EndTurn,
+}
+
+impl PromptAction {
+    /// Key which makes the move.
+    pub fn shortcut(self) -> &'static str {
+        match self {
+            PromptAction::Roll => "R",
+            PromptAction::Buy => "B",
+            PromptAction::EndTurn => "E",
+        }
+    }
+}
+
+/// Question to the player, shown in the modal once the ones before it are answered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Prompt {
+    pub text: String,
+    pub kind: PromptKind,
+    /// Move the first choice makes. Only such prompts can be answered from the keyboard.
+    pub action: Option<PromptAction>,
+    /// Text of the button that dismisses the prompt. Without it, the prompt has to be answered.
+    pub cancel_text: Option<String>,
+    /// Answer for the player who does not act, once their countdown expires.
+    pub default: PromptResponse,
+}
+
+impl Prompt {
+    /// Prompt with a button for every choice. Defaults to the first one.
+    pub fn choices(text: &str, choices: &[&str]) -> Self {
+        Self {
+            text: text.to_owned(),
+            kind: PromptKind::Choices(choices.iter().map(|&choice| choice.to_owned()).collect()),
+            action: None,
+            cancel_text: None,
+            default: PromptResponse::Choice(0),
+        }
+    }
+
+    /// Prompt for a number between `min` and `max`. Defaults to `min`.
+    pub fn number(text: &str, min: i64, max: i64, submit_text: &str) -> Self {
+        Self {
+            text: text.to_owned(),
+            kind: PromptKind::Number {
+                min,
+                max,
+                submit_text: submit_text.to_owned(),
+            },
+            action: None,
+            cancel_text: None,
+            default: PromptResponse::Number(min),
+        }
+    }
+
+    /// Prompt to pick one of the properties on the given cells. Defaults to the first one.
+    pub fn property(text: &str, cells: Vec<usize>) -> Self {
+        let default = cells.first().map_or(PromptResponse::Cancel, |cell| {
+            PromptResponse::Property(*cell)
+        });
+        Self {
+            text: text.to_owned(),
+            kind: PromptKind::Property { cells },
+            action: None,
+            cancel_text: None,
+            default,
+        }
+    }
+
+    /// Adds the cancel button, which is also the default answer.
+    pub fn with_cancel(self, cancel_text: &str) -> Self {
+        Self {
+            cancel_text: Some(cancel_text.to_owned()),
+            default: PromptResponse::Cancel,
+            ..self
+        }
+    }
+
+    /// Replaces the default answer, e.g. the cancel button's.
+    pub fn with_default(self, default: PromptResponse) -> Self {
+        Self { default, ..self }
+    }
+
+    pub fn with_action(self, action: PromptAction) -> Self {
+        Self {
+            action: Some(action),
+            ..self
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
-pub enum InGameModalStateType {
-    OneButton {
-        text: String,
-        button_text: String,
-        #[debug(skip)]
-        on_click: Arc<dyn Fn() + Send + Sync>,
-    },
-    TwoButtons {
-        text: String,
-        ok_button_text: String,
-        cancel_button_text: String,
-        #[debug(skip)]
-        on_ok_click: Arc<dyn Fn() + Send + Sync>,
-        #[debug(skip)]
-        on_cancel_click: Arc<dyn Fn() + Send + Sync>,
-    },
+struct QueuedPrompt {
+    id: usize,
+    prompt: Prompt,
+    #[debug(skip)]
+    respond: Arc<dyn Fn(PromptResponse) + Send + Sync>,
+}
+
+/// Prompts waiting for the player's answer, the shown one first.
+#[derive(Debug, Clone, Copy)]
+pub struct InGameModalState {
+    queue: RwSignal<VecDeque<QueuedPrompt>>,
+    next_id: StoredValue<usize>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -128,96 +329,112 @@ pub enum ModalResponse {
     Cancel,
 }
 
+impl Default for InGameModalState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl InGameModalState {
     pub fn new() -> Self {
         Self {
-            ty: RwSignal::new(None),
+            queue: RwSignal::new(VecDeque::new()),
+            next_id: StoredValue::new(0),
         }
     }
 
-    pub fn one_button(
+    /// Adds the prompt to the end of the queue. `respond` is called with the answer, after the
+    /// prompt is removed from the queue.
+    pub fn enqueue(
         &self,
-        text: &str,
-        button_text: &str,
-        on_click: impl Fn() + Send + Sync + 'static,
+        prompt: Prompt,
+        respond: impl Fn(PromptResponse) + Send + Sync + 'static,
     ) {
+        let id = self.next_id.get_value();
+        self.next_id.set_value(id + 1);
+
         let this = *self;
-        self.ty.set(Some(InGameModalStateType::OneButton {
-            text: text.to_owned(),
-            button_text: button_text.to_owned(),
-            on_click: Arc::new(move || {
-                this.ty.set(None);
-                on_click();
-            }),
-        }))
+        self.queue.update(|queue| {
+            queue.push_back(QueuedPrompt {
+                id,
+                prompt,
+                respond: Arc::new(move |response| {
+                    let is_queued = this.queue.try_update(|queue| {
+                        let index = queue.iter().position(|queued| queued.id == id)?;
+                        queue.remove(index)
+                    });
+                    // Answering twice, e.g. with a click right after the countdown expired, does nothing.
+                    if is_queued.flatten().is_some() {
+                        respond(response);
+                    }
+                }),
+            })
+        });
     }
 
-    pub fn two_buttons(
-        &self,
-        text: &str,
-        ok_button_text: &str,
-        cancel_button_text: &str,
-        on_ok_click: impl Fn() + Send + Sync + 'static,
-        on_cancel_click: impl Fn() + Send + Sync + 'static,
-    ) {
-        let this = *self;
-        self.ty.set(Some(InGameModalStateType::TwoButtons {
-            text: text.to_owned(),
-            ok_button_text: ok_button_text.to_owned(),
-            cancel_button_text: cancel_button_text.to_owned(),
-            on_ok_click: Arc::new(move || {
-                this.ty.set(None);
-                on_ok_click();
-            }),
-            on_cancel_click: Arc::new(move || {
-                this.ty.set(None);
-                on_cancel_click();
-            }),
-        }));
-    }
-
-    pub async fn one_button_async(&self, text: &str, button_text: &str) {
+    /// Asks the player and waits for the answer. Answers `Cancel`, if the prompt is removed
+    /// without an answer, e.g. when the game is over.
+    pub async fn ask(&self, prompt: Prompt) -> PromptResponse {
         let (sender, mut receiver) = mpsc::unbounded();
-        self.one_button(text, button_text, move || {
-            let mut sender = sender.clone();
-            spawn_local(async move {
-                sender.send(()).await.unwrap();
-            });
+        self.enqueue(prompt, move |response| {
+            let _ = sender.unbounded_send(response);
         });
-        receiver.next().await;
+
+        receiver.next().await.unwrap_or(PromptResponse::Cancel)
     }
 
-    pub async fn two_buttons_async(
-        &self,
-        text: &str,
-        ok_button_text: &str,
-        cancel_button_text: &str,
-    ) -> ModalResponse {
-        let (sender, mut receiver) = mpsc::unbounded();
-        self.two_buttons(
-            text,
-            ok_button_text,
-            cancel_button_text,
-            {
-                let sender = sender.clone();
-                move || {
-                    let mut sender = sender.clone();
-                    spawn_local(async move {
-                        sender.send(ModalResponse::Ok).await.unwrap();
-                    });
-                }
-            },
-            move || {
-                let mut sender = sender.clone();
-                spawn_local(async move {
-                    sender.send(ModalResponse::Cancel).await.unwrap();
-                });
-            },
-        );
+    /// Removes all prompts without answering them, e.g. when the game is over.
+    pub fn close(&self) {
+        self.queue.update(VecDeque::clear);
+    }
+
+    /// Answers the shown prompt the way that moves the game forward without the player:
+    /// presses the only button, or declines.
+    pub fn take_default_action(&self) {
+        let id = self
+            .queue
+            .with_untracked(|queue| queue.front().map(|queued| queued.id));
+        if let Some(id) = id {
+            self.take_default_action_of(id);
+        }
+    }
+
+    /// Answers the prompt with its default, if it is still shown.
+    fn take_default_action_of(&self, id: usize) {
+        let front = self.queue.with_untracked(|queue| {
+            queue
+                .front()
+                .filter(|queued| queued.id == id)
+                .map(|queued| (queued.respond.clone(), queued.prompt.default))
+        });
+
+        if let Some((respond, default)) = front {
+            respond(default);
+        }
+    }
+
+    /// Makes the move of the shown prompt, as if the player clicked its first button. Returns
+    /// whether the prompt offered that move.
+    pub fn press(&self, action: PromptAction) -> bool {
+        self.respond_to_front(|prompt| {
+            (prompt.action == Some(action)).then_some(PromptResponse::Choice(0))
+        })
+    }
+
+    /// Declines the move of the shown prompt, e.g. buying. Returns whether it could be declined.
+    pub fn decline(&self) -> bool {
+        self.respond_to_front(|prompt| {
+            (prompt.action.is_some() && prompt.cancel_text.is_some())
+                .then_some(PromptResponse::Cancel)
+        })
+    }
+
+    fn respond_to_front(&self, response: impl FnOnce(&Prompt) -> Option<PromptResponse>) -> bool {
+        let front = self.queue.with_untracked(|queue| {
+            let queued = queue.front()?;
+            Some((queued.respond.clone(), response(&queued.prompt)?))
+        });
 
-        receiver
-            .next()
-            .await
-            .expect("Receiver should not be closed")
+        front.map(|(respond, response)| respond(response)).is_some()
     }
 }
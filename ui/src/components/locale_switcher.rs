@@ -0,0 +1,33 @@
+use leptos::prelude::*;
+use tailwind_merge::tw;
+
+use crate::i18n::{I18n, Locale};
+
+/// Language of the interface, which can be changed at any time.
+#[component]
+pub fn LocaleSwitcher(#[prop(into, optional)] class: Signal<String>) -> impl IntoView {
+    let i18n = I18n::use_context();
+
+    view! {
+        <select
+            class=move || tw!("px-1 text-sm text-black rounded", class())
+            aria-label=move || i18n.t("locale.language", &[])
+            on:change=move |event| {
+                if let Some(locale) = Locale::from_code(&event_target_value(&event)) {
+                    i18n.set_locale(locale);
+                }
+            }
+        >
+            {Locale::VARIANTS
+                .into_iter()
+                .map(|locale| {
+                    view! {
+                        <option value=locale.code() selected=move || i18n.locale() == locale>
+                            {locale.name()}
+                        </option>
+                    }
+                })
+                .collect_view()}
+        </select>
+    }
+}
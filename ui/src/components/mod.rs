@@ -1,2 +1,9 @@
+pub mod chat;
+pub mod countdown;
 pub mod dice;
+pub mod game_summary;
 pub mod in_game_modal;
+pub mod locale_switcher;
+pub mod property_stats;
+pub mod standings;
+pub mod transactions;
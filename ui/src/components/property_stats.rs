@@ -0,0 +1,149 @@
+use leptos::prelude::*;
+
+use crate::{
+    cell::{Money, Property, PropertyType, CELLS_COUNT},
+    game_state::GameState,
+    i18n::I18n,
+};
+
+/// Most likely sum of two dice, which utilities' rent is estimated with.
+const AVERAGE_ROLL: usize = 7;
+
+/// Rent the property would bring per landing, if the owner kept it as it is. Property without an
+/// owner is estimated with its base rent.
+fn expected_rent(property: &Property, game_state: &GameState) -> Money {
+    property
+        .rent_for_roll(game_state, AVERAGE_ROLL)
+        .unwrap_or_else(|| match property.ty {
+            PropertyType::Simple { levels, .. } => levels[0],
+            PropertyType::Transport { levels } => levels[0],
+            PropertyType::Utility { levels } => levels[0]
+                .checked_mul(AVERAGE_ROLL as i64)
+                .expect("Rent for the utility should not overflow"),
+        })
+}
+
+/// Rounds it takes for the income to pay the cost back, if it ever does.
+fn rounds_to_break_even(cost: Money, income_per_round: f64) -> Option<usize> {
+    (income_per_round > 0.).then(|| (i64::from(cost) as f64 / income_per_round).ceil() as usize)
+}
+
+fn format_rounds(i18n: I18n, rounds: Option<usize>) -> String {
+    match rounds {
+        Some(rounds) => format!("~{}", i18n.t("property.rounds", &[("count", &rounds)])),
+        None => i18n.t("stats.never", &[]),
+    }
+}
+
+/// History of the property and estimates of whether buying or building on it pays off.
+#[component]
+pub fn PropertyStats(property: Property) -> impl IntoView {
+    let game_state = GameState::use_context();
+    let i18n = game_state.i18n;
+    let history = Memo::new(move |_| game_state.log.property_history(property.data.title));
+
+    // How many times per round opponents of the owner land on the property. Until anyone has,
+    // every cell is assumed to be equally likely.
+    let landing_rate = move || {
+        let owner = property.owner().map(|owner| owner.name.get_value());
+        let landings = history.with(|history| history.landings_by_others(owner.as_deref()));
+        if landings > 0 {
+            return landings as f64 / (game_state.current_round() + 1) as f64;
+        }
+
+        let players_left = game_state
+            .get_players()
+            .values()
+            .filter(|player| !player.has_lost())
+            .count();
+        players_left.saturating_sub(1).max(1) as f64 / CELLS_COUNT as f64
+    };
+
+    let buy_break_even = move || {
+        let income = i64::from(expected_rent(&property, &game_state)) as f64 * landing_rate();
+        rounds_to_break_even(property.data.price, income)
+    };
+    let build_break_even = move || {
+        let PropertyType::Simple {
+            levels,
+            level_price,
+            ..
+        } = property.ty
+        else {
+            return None;
+        };
+        property.owner()?;
+        let next_rent = levels.get(property.level() + 1)?;
+        let current_rent = expected_rent(&property, &game_state);
+        let rent_increase = next_rent.checked_sub(current_rent)?;
+        let income = i64::from(rent_increase) as f64 * landing_rate();
+        Some(rounds_to_break_even(level_price, income))
+    };
+
+    let owners = move || {
+        let bank = i18n.t("bank.title", &[]);
+        history.with(|history| {
+            std::iter::once(bank.as_str())
+                .chain(
+                    history
+                        .owners
+                        .iter()
+                        .map(|owner| owner.as_deref().unwrap_or(&bank)),
+                )
+                .collect::<Vec<_>>()
+                .join(" → ")
+        })
+    };
+
+    view! {
+        <div class="flex flex-col gap-0.5 text-xs">
+            <div class="flex justify-between">
+                <span>{move || i18n.t("stats.rent_collected", &[])}</span>
+                <span>
+                    {move || history.with(|history| history.rent_collected.to_string())}
+                    <span class="pl-0.5 opacity-70">"k"</span>
+                </span>
+            </div>
+            <div class="flex gap-2 justify-between">
+                <span>{move || i18n.t("stats.owners", &[])}</span>
+                <span class="text-right">{owners}</span>
+            </div>
+            {move || {
+                history
+                    .with(|history| {
+                        history
+                            .landings
+                            .iter()
+                            .map(|(player, landings)| {
+                                view! {
+                                    <div class="flex justify-between">
+                                        <span class="truncate">
+                                            {i18n.t("stats.landings", &[("player", player)])}
+                                        </span>
+                                        <span>{*landings}</span>
+                                    </div>
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                    })
+            }}
+            <Show when=move || property.owner().is_none()>
+                <div class="flex justify-between">
+                    <span>{move || i18n.t("stats.purchase_pays_off", &[])}</span>
+                    <span>{move || format_rounds(i18n, buy_break_even())}</span>
+                </div>
+            </Show>
+            {move || {
+                build_break_even()
+                    .map(|rounds| {
+                        view! {
+                            <div class="flex justify-between">
+                                <span>{i18n.t("stats.agency_pays_off", &[])}</span>
+                                <span>{format_rounds(i18n, rounds)}</span>
+                            </div>
+                        }
+                    })
+            }}
+        </div>
+    }
+}
@@ -0,0 +1,124 @@
+use std::cmp::Reverse;
+
+use leptos::prelude::*;
+use tailwind_merge::tw;
+
+use crate::{cell::Money, game_state::GameState, i18n::I18n, player::Player};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortBy {
+    Rank,
+    Name,
+    Cash,
+    NetWorth,
+}
+
+impl SortBy {
+    const VARIANTS: [SortBy; 4] = [SortBy::Rank, SortBy::Name, SortBy::Cash, SortBy::NetWorth];
+
+    fn label(&self, i18n: I18n) -> String {
+        match self {
+            SortBy::Rank => "#".to_owned(),
+            SortBy::Name => i18n.t("standings.player", &[]),
+            SortBy::Cash => i18n.t("standings.cash", &[]),
+            SortBy::NetWorth => i18n.t("standings.worth", &[]),
+        }
+    }
+
+    /// Sorts the rows in the natural order of the column: alphabetically or the richest first.
+    fn sort(&self, rows: &mut [(usize, Player, Money)]) {
+        match self {
+            SortBy::Rank => rows.sort_by_key(|(rank, ..)| *rank),
+            SortBy::Name => rows.sort_by_key(|(_, player, _)| player.name.get_value()),
+            SortBy::Cash => rows.sort_by_key(|(_, player, _)| Reverse(player.balance())),
+            SortBy::NetWorth => rows.sort_by_key(|(_, _, net_worth)| Reverse(*net_worth)),
+        }
+    }
+}
+
+/// Live ranking of the players by net worth, sortable by any column.
+#[component]
+pub fn Standings(#[prop(into, optional)] class: Signal<String>) -> impl IntoView {
+    let game_state = GameState::use_context();
+    let i18n = game_state.i18n;
+    let sort_by = RwSignal::new(SortBy::Rank);
+    let is_reversed = RwSignal::new(false);
+
+    let rows = move || {
+        let mut rows = game_state
+            .standings()
+            .into_iter()
+            .enumerate()
+            .map(|(index, (player, net_worth))| (index + 1, player, net_worth))
+            .collect::<Vec<_>>();
+        sort_by.get().sort(&mut rows);
+        if is_reversed.get() {
+            rows.reverse();
+        }
+        rows
+    };
+
+    let toggle_sort = move |column: SortBy| {
+        if sort_by.get_untracked() == column {
+            is_reversed.update(|is_reversed| *is_reversed = !*is_reversed);
+        } else {
+            sort_by.set(column);
+            is_reversed.set(false);
+        }
+    };
+
+    view! {
+        <table class=move || tw!("text-sm bg-gray-900 h-fit", class())>
+            <thead>
+                <tr>
+                    {SortBy::VARIANTS
+                        .into_iter()
+                        .map(|column| {
+                            view! {
+                                <th
+                                    class=tw!(
+                                        "p-1 text-right cursor-pointer select-none",
+                                        column == SortBy::Name => "text-left"
+                                    )
+                                    on:click=move |_| toggle_sort(column)
+                                >
+                                    {move || column.label(i18n)}
+                                    {move || match (sort_by.get() == column, is_reversed.get()) {
+                                        (false, _) => "",
+                                        (true, false) => " ▾",
+                                        (true, true) => " ▴",
+                                    }}
+                                </th>
+                            }
+                        })
+                        .collect_view()}
+                </tr>
+            </thead>
+            <tbody>
+                {move || {
+                    rows()
+                        .into_iter()
+                        .map(|(rank, player, net_worth)| {
+                            view! {
+                                <tr class=move || {
+                                    tw!("border-t border-white/10", player.has_lost() => "line-through opacity-50")
+                                }>
+                                    <td class="p-1 text-right">{rank}</td>
+                                    <td class="p-1 font-bold" style:color=player.color.get_text_color()>
+                                        {player.name.get_value()}
+                                    </td>
+                                    <td class="p-1 tabular-nums text-right">
+                                        {move || format!("${}k", i18n.money(player.balance()))}
+                                    </td>
+                                    <td class="p-1 tabular-nums text-right">
+                                        {format!("${}k", i18n.money(net_worth))}
+                                    </td>
+                                </tr>
+                            }
+                        })
+                        .collect_view()
+                }}
+            </tbody>
+        </table>
+    }
+}
@@ -0,0 +1,99 @@
+use leptos::prelude::*;
+use tailwind_merge::tw;
+
+use crate::{
+    game_state::GameState,
+    ledger::Party,
+    player::{Player, PlayerId},
+};
+
+/// Money the chosen player paid and received, the latest first.
+#[component]
+pub fn TransactionHistory(#[prop(into, optional)] class: Signal<String>) -> impl IntoView {
+    let game_state = GameState::use_context();
+    let i18n = game_state.i18n;
+    let selected = RwSignal::new(game_state.self_player.map(|player| player.id));
+
+    let players = move || {
+        let mut players = game_state.get_players().into_values().collect::<Vec<_>>();
+        players.sort_by_key(|player| player.id);
+        players
+    };
+    let player_id = move || {
+        selected
+            .get()
+            .or_else(|| players().first().map(|player| player.id))
+    };
+
+    let party_name = move |party: Party| match party {
+        Party::Bank => i18n.t("bank.title", &[]),
+        Party::Player(player_id) => game_state
+            .get_players()
+            .get(&player_id)
+            .map(|player| player.name.get_value())
+            .unwrap_or_else(|| i18n.t("transactions.unknown_player", &[("id", &player_id)])),
+    };
+
+    view! {
+        <div class=move || tw!("flex flex-col gap-1 p-2 text-sm bg-gray-900 min-h-0", class())>
+            <div class="flex gap-2 justify-between items-center">
+                <span class="font-bold">{move || i18n.t("transactions.title", &[])}</span>
+                <select
+                    class="px-1 text-black rounded"
+                    on:change=move |event| {
+                        selected.set(event_target_value(&event).parse::<PlayerId>().ok());
+                    }
+                >
+                    {move || {
+                        players()
+                            .into_iter()
+                            .map(|player: Player| {
+                                view! {
+                                    <option
+                                        value=player.id.to_string()
+                                        selected=move || player_id() == Some(player.id)
+                                    >
+                                        {player.name.get_value()}
+                                    </option>
+                                }
+                            })
+                            .collect_view()
+                    }}
+                </select>
+            </div>
+            <div class="overflow-y-auto flex-1">
+                {move || {
+                    let Some(player_id) = player_id() else {
+                        return Vec::new();
+                    };
+                    game_state
+                        .ledger
+                        .history(player_id)
+                        .into_iter()
+                        .filter_map(|transfer| {
+                            let change = transfer.change_for(player_id)?;
+                            let counterparty = if transfer.from == Party::Player(player_id) {
+                                transfer.to
+                            } else {
+                                transfer.from
+                            };
+                            Some(view! {
+                                <div class="flex gap-2 py-0.5 border-t border-white/10">
+                                    <span class="opacity-50">{format!("#{}", transfer.turn + 1)}</span>
+                                    <span class="min-w-0 grow" title=party_name(counterparty)>
+                                        {i18n.transfer_reason(&transfer.reason)}
+                                    </span>
+                                    <span class=tw!(
+                                        "tabular-nums",
+                                        change.is_negative() => "text-red-400",
+                                        change.is_positive() => "text-green-400"
+                                    )>{format!("{}$", i18n.money(change))}</span>
+                                </div>
+                            })
+                        })
+                        .collect::<Vec<_>>()
+                }}
+            </div>
+        </div>
+    }
+}
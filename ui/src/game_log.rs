@@ -0,0 +1,127 @@
+use std::collections::BTreeMap;
+
+use leptos::prelude::*;
+pub use shared::game::{GameEvent, LogEntry};
+
+use crate::{cell::Money, utils::clock};
+
+/// Feed of game events, in the order they happened.
+#[derive(Debug, Clone, Copy)]
+pub struct GameLog(RwSignal<Vec<LogEntry>>);
+
+impl Default for GameLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameLog {
+    pub fn new() -> Self {
+        Self(RwSignal::new(Vec::new()))
+    }
+
+    pub fn record(&self, event: GameEvent) {
+        tracing::debug!("Game event: {:?}", event);
+        let entry = LogEntry {
+            at: clock::unix_ms(),
+            event,
+        };
+        self.0.update(|entries| entries.push(entry));
+    }
+
+    /// Replaces the whole feed, e.g. with the one received from the server.
+    pub fn replace(&self, entries: Vec<LogEntry>) {
+        self.0.set(entries);
+    }
+
+    pub fn entries(&self) -> ReadSignal<Vec<LogEntry>> {
+        self.0.read_only()
+    }
+
+    /// What happened to the property during the game, according to the log.
+    pub fn property_history(&self, property: &str) -> PropertyHistory {
+        let mut history = PropertyHistory::default();
+        let mut owner = None::<String>;
+        let mut is_auctioned = false;
+
+        self.0.with(|entries| {
+            for entry in entries {
+                match &entry.event {
+                    GameEvent::BoughtProperty {
+                        player,
+                        property: title,
+                        ..
+                    } if title == property => {
+                        // Auctioned property is bought without landing on it.
+                        if !std::mem::take(&mut is_auctioned) {
+                            history.land(player);
+                        }
+                        owner = Some(player.clone());
+                        history.owners.push(owner.clone());
+                    }
+                    GameEvent::WonPropertyAuction {
+                        property: title, ..
+                    } if title == property => is_auctioned = true,
+                    GameEvent::PaidRent {
+                        player,
+                        property: title,
+                        rent,
+                        ..
+                    } if title == property => {
+                        history.land(player);
+                        history.rent_collected = history
+                            .rent_collected
+                            .checked_add(*rent)
+                            .expect("Collected rent should not overflow");
+                    }
+                    GameEvent::SteppedOnOwnProperty {
+                        player,
+                        property: title,
+                    }
+                    | GameEvent::DeclinedProperty {
+                        player,
+                        property: title,
+                    } if title == property => history.land(player),
+                    GameEvent::LostMortgagedProperty {
+                        property: title, ..
+                    } if title == property => {
+                        owner = None;
+                        history.owners.push(None);
+                    }
+                    // Properties of the surrendered player return to the bank.
+                    GameEvent::Surrendered { player } if owner.as_ref() == Some(player) => {
+                        owner = None;
+                        history.owners.push(None);
+                    }
+                    _ => {}
+                }
+            }
+        });
+        history
+    }
+}
+
+/// Statistics of one property, collected from the game log.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PropertyHistory {
+    /// Names of the owners in the order they got the property. `None` is the bank.
+    pub owners: Vec<Option<String>>,
+    pub rent_collected: Money,
+    /// Times every player landed on the property, by their name.
+    pub landings: BTreeMap<String, usize>,
+}
+
+impl PropertyHistory {
+    fn land(&mut self, player: &str) {
+        *self.landings.entry(player.to_owned()).or_default() += 1;
+    }
+
+    /// Landings of the players other than the owner.
+    pub fn landings_by_others(&self, owner: Option<&str>) -> usize {
+        self.landings
+            .iter()
+            .filter(|(player, _)| Some(player.as_str()) != owner)
+            .map(|(_, landings)| landings)
+            .sum()
+    }
+}
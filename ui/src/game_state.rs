@@ -1,58 +1,195 @@
-use std::{collections::HashMap, future::Future};
+use std::{cmp::Reverse, collections::HashMap, future::Future};
 
 use futures::{
+    channel::oneshot,
     stream::{AbortHandle, Abortable},
     FutureExt,
 };
 use leptos::{prelude::*, task::spawn_local};
+use shared::{
+    game::{AuctionLot, BidRequest, DrawVote, GameSnapshot, GameStats},
+    room::RulesPreset,
+    rules::{AgencyTerms, MortgageTerms},
+};
 
 use crate::{
-    cell::{Cell, Property, PropertyGroup, CELLS_COUNT},
-    components::in_game_modal::InGameModalState,
+    cell::{
+        AgencySupply, BuildAgencyError, Cell, Money, Property, PropertyGroup, AGENCY_SUPPLY,
+        CELLS_COUNT, PASSED_START_REWARD, TOP_LEVEL_SUPPLY,
+    },
+    components::in_game_modal::{
+        InGameModalState, ModalResponse, Prompt, PromptAction, PromptResponse,
+    },
     game_data::init_cells,
-    player::{Player, PlayerColor, PlayerId},
+    game_log::{GameEvent, GameLog, LogEntry},
+    i18n::I18n,
+    ledger::{Ledger, Party, Transfer, TransferReason},
+    player::{NotEnoughMoneyError, Player, PlayerColor, PlayerId},
+    server::{ExpiredCountdown, ServerConnection},
     utils::{oneshot_event_emitter::OneShotEventEmitter, rand},
 };
 
+/// Amount each bid of an auction raises the price by, at least.
+const AUCTION_STEP: i64 = 100;
+
 #[derive(Debug, Clone, Copy)]
 pub struct GameState {
     cells: [Cell; CELLS_COUNT],
     players: RwSignal<HashMap<PlayerId, Player>>,
-    pub self_player: Player,
+    /// Player of the signed-in user. `None` when the game is only watched. Only the client of the
+    /// player whose turn it is runs the game, everyone else mirrors the snapshots it publishes.
+    pub self_player: Option<Player>,
     current_player: RwSignal<Player>,
     current_turn: RwSignal<usize>,
     current_round: RwSignal<usize>,
     rolled_dice: RwSignal<Option<(usize, usize)>>,
+    stats: RwSignal<GameStats>,
+    is_drawn: RwSignal<bool>,
+    mortgage_terms: MortgageTerms,
+    agency_terms: AgencyTerms,
     player_token_transition_end: OneShotEventEmitter,
     dice_transition_end: OneShotEventEmitter,
     pub in_game_modal_state: InGameModalState,
+    pub log: GameLog,
+    /// Amount of log entries that are known to the server already.
+    published_events: StoredValue<usize>,
+    /// Revision of the latest snapshot published or followed.
+    revision: StoredValue<u64>,
+    pub ledger: Ledger,
+    pub i18n: I18n,
+    connection: ServerConnection,
+    /// Bidder the auction run by this client waits for, and where their answer goes.
+    pending_bid: StoredValue<Option<(PlayerId, oneshot::Sender<Option<Money>>)>>,
     abort_handlers: RwSignal<Vec<AbortHandle>>,
 }
 
 impl GameState {
-    pub fn new() -> Self {
-        let mut players = HashMap::new();
-        players.insert(0, Player::new(0, "Ar4ys", PlayerColor::Blue));
-        players.insert(1, Player::new(1, "Madeline", PlayerColor::Green));
-        players.insert(2, Player::new(2, "Asmodeus", PlayerColor::Red));
-
-        let game_state = Self {
-            self_player: players[&0],
-            current_player: RwSignal::new(players[&0]),
+    /// Creates the game of the joined room: restores it from the latest snapshot, or starts a new
+    /// one with the seated players.
+    pub fn new(spectating: bool) -> Self {
+        let connection = ServerConnection::use_context();
+        let (snapshot, seats, self_player_id, rules) = untrack(|| {
+            (
+                connection.game(),
+                connection.seats(),
+                connection.self_player().map(|(player_id, _)| player_id),
+                connection.rules(),
+            )
+        });
+
+        let players = match &snapshot {
+            Some(snapshot) => snapshot
+                .players
+                .iter()
+                .map(|player| Player::new(player.id, &player.name, player.color))
+                .collect::<Vec<_>>(),
+            None => seats
+                .iter()
+                .zip(PlayerColor::VARIANTS)
+                .map(|((player_id, name), color)| Player::new(*player_id, name, color))
+                .collect(),
+        };
+        let game_state = Self::with_players(
+            players,
+            self_player_id.filter(|_| !spectating),
+            rules,
+            connection,
+            I18n::use_context(),
+        );
+
+        // Everything the player whose turn it is did is published before the server accepts
+        // anything else, so the room's snapshot is where the game left off.
+        if let Some(snapshot) = snapshot {
+            game_state.apply_snapshot(&snapshot);
+        }
+        game_state.mirror_log();
+
+        game_state
+    }
+
+    /// Game that is not connected to any room, only watched, for testing the rules.
+    #[cfg(test)]
+    pub fn local(names: &[&str]) -> Self {
+        let players = names
+            .iter()
+            .zip(PlayerColor::VARIANTS)
+            .enumerate()
+            .map(|(index, (name, color))| Player::new(index as PlayerId, name, color))
+            .collect();
+        Self::with_players(
+            players,
+            None,
+            RulesPreset::default(),
+            ServerConnection::new(),
+            I18n::with_locale(crate::i18n::Locale::En),
+        )
+    }
+
+    /// New game of the players, the first of which starts.
+    fn with_players(
+        players: Vec<Player>,
+        self_player_id: Option<PlayerId>,
+        rules: RulesPreset,
+        connection: ServerConnection,
+        i18n: I18n,
+    ) -> Self {
+        let first_player = *players
+            .first()
+            .expect("GameState should not be created for a room without players");
+        let players = players
+            .into_iter()
+            .map(|player| (player.id, player))
+            .collect::<HashMap<_, _>>();
+        let self_player = self_player_id.and_then(|player_id| players.get(&player_id).copied());
+
+        Self {
+            self_player,
+            current_player: RwSignal::new(first_player),
             current_turn: RwSignal::new(0),
             current_round: RwSignal::new(0),
             cells: init_cells(),
             players: RwSignal::new(players),
             rolled_dice: RwSignal::new(None),
+            stats: RwSignal::new(GameStats::default()),
+            is_drawn: RwSignal::new(false),
+            mortgage_terms: rules.mortgage_terms(),
+            agency_terms: rules.agency_terms(),
             player_token_transition_end: OneShotEventEmitter::new(),
             dice_transition_end: OneShotEventEmitter::new(),
             in_game_modal_state: InGameModalState::new(),
+            log: GameLog::new(),
+            published_events: StoredValue::new(0),
+            revision: StoredValue::new(0),
+            ledger: Ledger::new(),
+            i18n,
+            connection,
+            pending_bid: StoredValue::new(None),
             abort_handlers: RwSignal::new(Vec::new()),
-        };
-
-        game_state.ask_to_roll_dice();
+        }
+    }
 
-        game_state
+    /// Starts the turn of the current player, if it is the signed-in user, unless the game is
+    /// already over. A turn that was interrupted after rolling the dice, e.g. by a reload, ends
+    /// instead, because the roll was used already.
+    pub fn start(&self) {
+        if !untrack(|| self.is_own_turn()) {
+            return;
+        }
+        let players_left = untrack(|| {
+            self.players
+                .with(|players| players.values().filter(|player| !player.has_lost()).count())
+        });
+        if untrack(|| self.stats.with(|stats| stats.net_worth_history.is_empty())) {
+            self.record_net_worth();
+        }
+        if players_left <= 1 || self.is_drawn.get_untracked() {
+            return;
+        }
+        if self.rolled_dice.get_untracked().is_some() {
+            self.finish_turn();
+        } else {
+            self.ask_to_roll_dice();
+        }
     }
 
     pub fn provide_context(&self) {
@@ -63,6 +200,126 @@ impl GameState {
         expect_context::<Self>()
     }
 
+    pub fn is_spectating(&self) -> bool {
+        self.self_player.is_none()
+    }
+
+    /// Whether the signed-in user runs the game right now, because it is their turn.
+    pub fn is_own_turn(&self) -> bool {
+        self.self_player.is_some_and(|self_player| {
+            self_player == self.current_player.get() && !self_player.has_lost()
+        })
+    }
+
+    /// Mirrors the snapshot published by the player whose turn it is, and starts the turn once it
+    /// passes to the signed-in user.
+    pub fn follow(&self, snapshot: &GameSnapshot) {
+        // The game is run here, so the snapshot can only be older.
+        if untrack(|| self.is_own_turn()) {
+            return;
+        }
+
+        self.apply_snapshot(snapshot);
+        self.mirror_log();
+        self.start();
+    }
+
+    /// Replaces the log with the one published to the room.
+    fn mirror_log(&self) {
+        let entries = self.connection.game_log().get_untracked();
+        self.published_events.set_value(entries.len());
+        self.log.replace(entries);
+    }
+
+    /// Log entries recorded here since the log was last published or mirrored, which are then
+    /// considered published.
+    pub fn take_unpublished_events(&self) -> Vec<LogEntry> {
+        self.log.entries().with(|entries| {
+            let events = entries
+                .get(self.published_events.get_value()..)
+                .unwrap_or_default()
+                .to_vec();
+            self.published_events.set_value(entries.len());
+            events
+        })
+    }
+
+    pub fn snapshot(&self) -> GameSnapshot {
+        let mut players = self
+            .players
+            .with(|players| players.values().map(Player::snapshot).collect::<Vec<_>>());
+        players.sort_by_key(|player| player.id);
+
+        GameSnapshot {
+            revision: self.revision.get_value(),
+            players,
+            properties: self
+                .cells
+                .iter()
+                .enumerate()
+                .filter_map(|(index, cell)| {
+                    let property = cell.try_unwrap_property().ok()?;
+                    Some(property.snapshot(index))
+                })
+                .collect(),
+            current_player: self.current_player.get().id,
+            current_turn: self.current_turn.get(),
+            current_round: self.current_round.get(),
+            rolled_dice: self.rolled_dice.get(),
+            stats: self.stats.get(),
+            ledger: self.ledger.transfers().get(),
+            is_drawn: self.is_drawn.get(),
+        }
+    }
+
+    /// Makes the board look like the published snapshot. Used by everyone who does not run the
+    /// game right now.
+    pub fn apply_snapshot(&self, snapshot: &GameSnapshot) {
+        let players = snapshot
+            .players
+            .iter()
+            .map(|player_snapshot| {
+                let player = self
+                    .players
+                    .with_untracked(|players| players.get(&player_snapshot.id).copied())
+                    .unwrap_or_else(|| {
+                        Player::new(
+                            player_snapshot.id,
+                            &player_snapshot.name,
+                            player_snapshot.color,
+                        )
+                    });
+                player.apply_snapshot(player_snapshot);
+                (player.id, player)
+            })
+            .collect::<HashMap<_, _>>();
+
+        for property_snapshot in &snapshot.properties {
+            if let Some(Cell::Property(property)) = self.cells.get(property_snapshot.cell) {
+                property.apply_snapshot(property_snapshot, &players);
+            }
+        }
+
+        if let Some(current_player) = players.get(&snapshot.current_player) {
+            self.current_player.set(*current_player);
+        }
+        self.current_turn.set(snapshot.current_turn);
+        self.current_round.set(snapshot.current_round);
+        self.rolled_dice.set(snapshot.rolled_dice);
+        self.stats.set(snapshot.stats.clone());
+        self.ledger.replace(snapshot.ledger.clone());
+        self.is_drawn.set(snapshot.is_drawn);
+        self.players.set(players);
+        self.revision.set_value(snapshot.revision);
+    }
+
+    /// Numbers the next snapshot to publish, after every one published or followed so far.
+    pub fn next_revision(&self) -> u64 {
+        let revision = self.revision.get_value() + 1;
+        self.revision.set_value(revision);
+        revision
+    }
+
     pub fn current_player(&self) -> Player {
         self.current_player.get()
     }
@@ -79,6 +336,160 @@ impl GameState {
         self.current_turn.get()
     }
 
+    pub fn current_round(&self) -> usize {
+        self.current_round.get()
+    }
+
+    /// Moves the money and records it in the ledger. Nothing changes, if the payer cannot afford
+    /// it. The bank can always pay.
+    pub fn transfer(
+        &self,
+        from: Party,
+        to: Party,
+        amount: Money,
+        reason: TransferReason,
+    ) -> Result<(), NotEnoughMoneyError> {
+        let player = |party| match party {
+            Party::Bank => None,
+            Party::Player(player_id) => Some(self.get_player_by_id(player_id)),
+        };
+        let total_before = self.total_balance();
+
+        if let Some(payer) = player(from) {
+            payer.withdraw(amount)?;
+        }
+        if let Some(payee) = player(to) {
+            payee.deposit(amount);
+        }
+
+        // Money only appears from the bank and disappears into it.
+        let expected_change = match (from, to) {
+            (Party::Bank, Party::Player(_)) => amount,
+            (Party::Player(_), Party::Bank) => -amount,
+            _ => Money::default(),
+        };
+        debug_assert_eq!(
+            self.total_balance().checked_sub(total_before),
+            Some(expected_change),
+            "Transfer should conserve money"
+        );
+
+        self.ledger.record(Transfer {
+            turn: self.current_turn.get_untracked(),
+            from,
+            to,
+            amount,
+            reason,
+        });
+        Ok(())
+    }
+
+    /// Pays the player from the bank, which can always pay.
+    pub fn pay_from_bank(&self, player: &Player, amount: Money, reason: TransferReason) {
+        self.transfer(Party::Bank, Party::Player(player.id), amount, reason)
+            .expect("Bank should always be able to pay");
+    }
+
+    fn total_balance(&self) -> Money {
+        untrack(|| {
+            self.players.with(|players| {
+                players.values().fold(Money::default(), |total, player| {
+                    total
+                        .checked_add(player.balance())
+                        .expect("Total balance should not overflow")
+                })
+            })
+        })
+    }
+
+    pub fn mortgage_terms(&self) -> MortgageTerms {
+        self.mortgage_terms
+    }
+
+    pub fn agency_terms(&self) -> AgencyTerms {
+        self.agency_terms
+    }
+
+    pub fn stats(&self) -> GameStats {
+        self.stats.get()
+    }
+
+    /// Whether the players agreed to end the game in a draw.
+    pub fn is_drawn(&self) -> bool {
+        self.is_drawn.get()
+    }
+
+    /// Cash of the player plus the price of every property they own and the agencies on it, minus
+    /// what they owe for the mortgaged ones.
+    pub fn net_worth(&self, player: &Player) -> Money {
+        self.cells
+            .iter()
+            .filter_map(|cell| cell.try_unwrap_property().ok())
+            .filter(|property| property.owner().is_some_and(|owner| owner == *player))
+            .fold(player.balance(), |net_worth, property| {
+                net_worth
+                    .checked_add(property.data.price)
+                    .and_then(|net_worth| net_worth.checked_add(property.agencies_value()))
+                    .and_then(|net_worth| net_worth.checked_sub(property.mortgage_debt(self)))
+                    .expect("Net worth should not overflow")
+            })
+    }
+
+    /// Players with their net worth, richest first. Players who lost are at the end.
+    pub fn standings(&self) -> Vec<(Player, Money)> {
+        let mut standings = self.players.with(|players| {
+            players
+                .values()
+                .map(|player| (*player, self.net_worth(player)))
+                .collect::<Vec<_>>()
+        });
+        standings
+            .sort_by_key(|(player, net_worth)| (player.has_lost(), Reverse(*net_worth), player.id));
+        standings
+    }
+
+    /// Place of the player in the standings, starting from 1.
+    pub fn rank(&self, player: &Player) -> usize {
+        self.standings()
+            .iter()
+            .position(|(other, _)| other == player)
+            .map_or(0, |index| index + 1)
+    }
+
+    /// Adds the current net worth of every player to the history.
+    fn record_net_worth(&self) {
+        let mut net_worths = untrack(|| {
+            self.players.with(|players| {
+                players
+                    .values()
+                    .map(|player| (player.id, self.net_worth(player)))
+                    .collect::<Vec<_>>()
+            })
+        });
+        net_worths.sort_by_key(|(player_id, _)| *player_id);
+        self.stats
+            .update(|stats| stats.net_worth_history.push(net_worths));
+    }
+
+    /// Counts the rent towards the statistics of both players and the property.
+    pub fn record_rent(&self, player: &Player, owner: &Player, property: &str, rent: Money) {
+        let add_rent = |total: &mut Money| {
+            *total = total
+                .checked_add(rent)
+                .expect("Rent statistics should not overflow");
+        };
+        self.stats.update(|stats| {
+            add_rent(stats.rent_paid.entry(player.id).or_default());
+            add_rent(stats.rent_received.entry(owner.id).or_default());
+            add_rent(
+                stats
+                    .property_income
+                    .entry(property.to_owned())
+                    .or_default(),
+            );
+        });
+    }
+
     pub fn get_players(&self) -> HashMap<PlayerId, Player> {
         self.players.get()
     }
@@ -107,80 +518,170 @@ impl GameState {
         let dice2 = rand::get_usize(1..=6);
         self.rolled_dice.set(Some((dice1, dice2)));
         self.dice_transition_end.listen_async().await;
-        let (prev_position, new_position) = self
-            .current_player
-            .get_untracked()
-            .append_position(dice1 + dice2);
+        let current_player = self.current_player.get_untracked();
+        self.log.record(GameEvent::RolledDice {
+            player: current_player.name.get_value(),
+            dice: (dice1, dice2),
+        });
+        let (prev_position, new_position) = current_player.append_position(dice1 + dice2);
         self.player_token_transition_end.listen_async().await;
 
         if prev_position + dice1 + dice2 >= CELLS_COUNT {
-            self.current_player.get_untracked().deposit(2000.into())
+            let reward = PASSED_START_REWARD.into();
+            self.pay_from_bank(&current_player, reward, TransferReason::PassedStart);
+            self.log.record(GameEvent::PassedStart {
+                player: current_player.name.get_value(),
+                reward,
+            });
         }
 
         let current_cell = self.get_cell(new_position);
         current_cell.trigger(self).await;
 
-        self.finish_turn();
+        // The turn of a bankrupt player has passed already.
+        if !untrack(|| current_player.has_lost()) {
+            self.finish_turn();
+        }
     }
 
+    /// Ends the round if needed, and passes the turn to the next player. The turn passes last, so
+    /// that this client can still publish everything that happened at the end of the round.
     pub fn finish_turn(&self) {
-        let is_round_ended = untrack(|| self.next_player());
+        let (next_player, is_round_ended) = untrack(|| self.next_player());
         self.current_turn.update(|turn| *turn += 1);
 
         if is_round_ended {
             self.current_round.update(|round| *round += 1);
+            let mut forfeited = Vec::new();
             for cell in self.cells.iter() {
                 if let Cell::Property(property) = cell {
-                    property.tick();
+                    if let Some(former_owner) = property.tick(self) {
+                        forfeited.push((*property, former_owner));
+                    }
                 };
             }
+            self.record_net_worth();
+
+            // The server does not take changes from a player who lost, so the properties of this
+            // round stay with the bank.
+            let is_current_playing = untrack(|| !self.current_player.get().has_lost());
+            if self.mortgage_terms.auction_forfeited && !forfeited.is_empty() && is_current_playing
+            {
+                let this = *self;
+                self.spawn_local_abortable(async move {
+                    for (property, former_owner) in forfeited {
+                        this.auction_property(property, former_owner).await;
+                    }
+                    this.pass_turn(next_player);
+                });
+                return;
+            }
         }
 
-        self.ask_to_roll_dice();
+        self.pass_turn(next_player);
     }
 
-    pub fn next_player(&self) -> bool {
-        let players_left: Vec<_> = self.players.with(|players| {
+    /// Player whose turn comes after the current one, and whether the round ends with the current
+    /// turn. Players take turns in the order of their ids, like on the server.
+    pub fn next_player(&self) -> (Player, bool) {
+        let current_player = self.current_player.get();
+        let mut players_left = self.players.with(|players| {
             players
                 .values()
-                // Skip all players before current one
-                .skip_while(|player| **player != self.current_player())
-                // Skip current player
-                .skip(1)
                 .filter(|player| untrack(|| !player.has_lost()))
                 .copied()
-                .collect()
+                .collect::<Vec<_>>()
         });
+        players_left.sort_by_key(|player| player.id);
 
-        let (next_player, is_round_ended) = if players_left.is_empty() {
-            (
-                self.players.with(|players| {
-                    players
-                        .values()
-                        .find(|player| untrack(|| !player.has_lost()))
-                        .copied()
-                        .expect("There should be players!")
-                }),
+        match players_left
+            .iter()
+            .find(|player| player.id > current_player.id)
+        {
+            Some(next_player) => (*next_player, false),
+            None => (
+                *players_left.first().expect("There should be players!"),
                 true,
-            )
-        } else {
-            (players_left[0], false)
-        };
+            ),
+        }
+    }
 
+    /// Hands the game over to the next player, whose client runs it from now on.
+    fn pass_turn(&self, next_player: Player) {
         self.current_player.set(next_player);
         self.rolled_dice.set(None);
-
-        is_round_ended
+        self.start();
     }
 
     pub fn ask_to_roll_dice(&self) {
-        // TODO: Use this if when networking
-        // if game_state.self_player == game_state.current_player()
         let this = *self;
-        self.in_game_modal_state
-            .one_button("Roll thy dice!", "Roll", move || {
-                this.spawn_local_abortable(async move { this.roll_dice().await });
-            });
+        let prompt = Prompt::choices(
+            &self.i18n.t("prompt.roll", &[]),
+            &[&self.i18n.t("button.roll", &[])],
+        )
+        .with_action(PromptAction::Roll);
+        self.in_game_modal_state.enqueue(prompt, move |_| {
+            this.spawn_local_abortable(async move { this.roll_dice().await });
+        });
+    }
+
+    /// Asks the player, limited by the decision timer.
+    pub async fn ask(&self, prompt: Prompt) -> PromptResponse {
+        self.start_countdown();
+        let response = self.in_game_modal_state.ask(prompt).await;
+        self.stop_countdown();
+        response
+    }
+
+    /// Shows a prompt with one button, which makes the move, limited by the decision timer.
+    pub async fn prompt(&self, text: &str, button_text: &str, action: PromptAction) {
+        self.ask(Prompt::choices(text, &[button_text]).with_action(action))
+            .await;
+    }
+
+    /// Shows a prompt with two buttons, to make the move or not, limited by the decision timer.
+    pub async fn prompt_choice(
+        &self,
+        text: &str,
+        ok_button_text: &str,
+        cancel_button_text: &str,
+        action: PromptAction,
+    ) -> ModalResponse {
+        let prompt = Prompt::choices(text, &[ok_button_text])
+            .with_cancel(cancel_button_text)
+            .with_action(action);
+        match self.ask(prompt).await {
+            PromptResponse::Choice(_) => ModalResponse::Ok,
+            _ => ModalResponse::Cancel,
+        }
+    }
+
+    /// Acts for the player, if it was their countdown that expired. A player who ran out of
+    /// timeouts is taken out of the game by the server instead.
+    pub fn countdown_expired(&self, expired: ExpiredCountdown) {
+        let is_self = untrack(|| self.connection.self_player())
+            .is_some_and(|(player_id, _)| player_id == expired.countdown.player_id);
+        if is_self && !expired.forfeit {
+            self.in_game_modal_state.take_default_action();
+        }
+    }
+
+    fn start_countdown(&self) {
+        let connection = self.connection;
+        spawn_local(async move {
+            if let Err(error) = connection.start_countdown().await {
+                tracing::warn!("Unable to start countdown: {}", error);
+            }
+        });
+    }
+
+    fn stop_countdown(&self) {
+        let connection = self.connection;
+        spawn_local(async move {
+            if let Err(error) = connection.stop_countdown().await {
+                tracing::warn!("Unable to stop countdown: {}", error);
+            }
+        });
     }
 
     pub fn player_token_transition_end(&self) {
@@ -219,19 +720,410 @@ impl GameState {
         owns == total
     }
 
+    /// Agencies left in the bank: the supply minus the ones built on the board.
+    pub fn agency_supply(&self) -> AgencySupply {
+        self.cells
+            .iter()
+            .filter_map(|cell| cell.try_unwrap_property().ok())
+            .fold(
+                AgencySupply {
+                    agencies: AGENCY_SUPPLY,
+                    top_levels: TOP_LEVEL_SUPPLY,
+                },
+                |supply, property| {
+                    let used = property.supply_used();
+                    AgencySupply {
+                        agencies: supply.agencies.saturating_sub(used.agencies),
+                        top_levels: supply.top_levels.saturating_sub(used.top_levels),
+                    }
+                },
+            )
+    }
+
+    /// Builds an agency on the property. When the bank has fewer agencies of its kind left than
+    /// there are players who could build one, the agency is auctioned among them instead.
+    pub async fn build_agency(&self, property: Property) -> Result<(), BuildAgencyError> {
+        let owner = untrack(|| property.check_build_agency(self))?;
+        let is_top_level = |property: &Property| property.level() + 1 == property.max_level();
+        let (bidders, left) = untrack(|| {
+            let bidders = self
+                .cells
+                .iter()
+                .filter_map(|cell| cell.try_unwrap_property().ok())
+                .filter(|other| is_top_level(other) == is_top_level(&property))
+                .filter_map(|other| Some((other.check_build_agency(self).ok()?, other)))
+                .fold(vec![(owner, property)], |mut bidders, (player, other)| {
+                    if bidders.iter().all(|(bidder, _)| *bidder != player) {
+                        bidders.push((player, other));
+                    }
+                    bidders
+                });
+            let supply = self.agency_supply();
+            let left = if is_top_level(&property) {
+                supply.top_levels
+            } else {
+                supply.agencies
+            };
+            (bidders, left)
+        });
+
+        if bidders.len() <= left {
+            return property.build_agency(self);
+        }
+
+        let (leader, others) = bidders
+            .split_first()
+            .expect("Bidders should start with the owner of the property");
+        let Some((winner, price)) = self
+            .auction(
+                AuctionLot::ScarceAgency,
+                Some(leader.0),
+                property.agency_price(),
+                others.iter().map(|(player, _)| *player).collect(),
+            )
+            .await
+        else {
+            unreachable!("Auction with a leader should have a winner");
+        };
+        let (_, property) = *bidders
+            .iter()
+            .find(|(player, _)| *player == winner)
+            .expect("Winner should be one of the bidders");
+
+        self.log.record(GameEvent::WonAgencyAuction {
+            player: winner.name.get_value(),
+            property: property.data.title.to_owned(),
+            price,
+        });
+        property.build_agency_for(price, self)
+    }
+
+    /// Ascending auction: every bidder in turn can outbid the leader by any amount they can afford,
+    /// until no one does. Without a leader, the first bid can be the starting price. Returns the
+    /// winner with their price, unless no one bid at all.
+    async fn auction(
+        &self,
+        lot: AuctionLot,
+        mut leader: Option<Player>,
+        mut price: Money,
+        mut bidders: Vec<Player>,
+    ) -> Option<(Player, Money)> {
+        while !bidders.is_empty() {
+            let mut still_bidding = Vec::new();
+            for player in bidders {
+                let min_bid = if leader.is_some() {
+                    price
+                        .checked_add(AUCTION_STEP.into())
+                        .expect("Bid should not overflow")
+                } else {
+                    price
+                };
+                let balance = untrack(|| player.balance());
+                if balance < min_bid {
+                    continue;
+                }
+
+                let request = BidRequest {
+                    bidder: player.id,
+                    lot: lot.clone(),
+                    min_bid,
+                    max_bid: balance,
+                };
+                if let Some(bid) = self.request_bid(request).await {
+                    still_bidding.extend(leader);
+                    leader = Some(player);
+                    price = bid;
+                }
+            }
+            bidders = still_bidding;
+        }
+
+        leader.map(|leader| (leader, price))
+    }
+
+    /// Asks the bidder through the server, which passes for them once their countdown expires.
+    /// A request the server refuses counts as a pass.
+    async fn request_bid(&self, request: BidRequest) -> Option<Money> {
+        let (sender, receiver) = oneshot::channel();
+        self.pending_bid.set_value(Some((request.bidder, sender)));
+        if let Err(error) = self.connection.request_bid(request).await {
+            tracing::warn!("Unable to ask for a bid: {}", error);
+            self.pending_bid.set_value(None);
+            return None;
+        }
+
+        receiver.await.ok().flatten()
+    }
+
+    /// Hands the bidder's answer to the auction waiting for it.
+    pub fn bid_placed(&self, bidder: PlayerId, bid: Option<Money>) {
+        let pending = self
+            .pending_bid
+            .try_update_value(|pending| pending.take_if(|(waiting_for, _)| *waiting_for == bidder))
+            .flatten();
+        match pending {
+            Some((_, sender)) => {
+                let _ = sender.send(bid);
+            }
+            None => tracing::warn!("Player {} bid in no auction", bidder),
+        }
+    }
+
+    /// Asks the signed-in player to bid, until the countdown the server started for it expires.
+    pub fn bid_requested(&self, request: BidRequest) {
+        let Some(player) = self
+            .self_player
+            .filter(|player| player.id == request.bidder)
+        else {
+            return;
+        };
+
+        let subject = match request.lot {
+            AuctionLot::ScarceAgency => self.i18n.t("auction.scarce_agency", &[]),
+            AuctionLot::Property { property } => property,
+        };
+        let text = self.i18n.t(
+            "prompt.auction",
+            &[("subject", &subject), ("player", &player.name.get_value())],
+        );
+        let prompt = Prompt::number(
+            &text,
+            request.min_bid.into(),
+            request.max_bid.into(),
+            &self.i18n.t("button.bid", &[]),
+        )
+        .with_cancel(&self.i18n.t("button.pass", &[]));
+        let connection = self.connection;
+        self.in_game_modal_state.enqueue(prompt, move |response| {
+            let bid = match response {
+                PromptResponse::Number(bid) => Some(bid.into()),
+                _ => None,
+            };
+            spawn_local(async move {
+                if let Err(error) = connection.place_bid(bid).await {
+                    tracing::warn!("Unable to bid: {}", error);
+                }
+            });
+        });
+    }
+
+    /// Auctions the forfeited property among the players, except for its former owner. The
+    /// property stays with the bank, if no one bids.
+    async fn auction_property(&self, property: Property, former_owner: Player) {
+        let mut bidders = untrack(|| {
+            self.players.with(|players| {
+                players
+                    .values()
+                    .filter(|player| **player != former_owner && !player.has_lost())
+                    .copied()
+                    .collect::<Vec<_>>()
+            })
+        });
+        bidders.sort_by_key(|player| player.id);
+
+        let Some((winner, price)) = self
+            .auction(
+                AuctionLot::Property {
+                    property: property.data.title.to_owned(),
+                },
+                None,
+                property.reward_for_mortgaging(),
+                bidders,
+            )
+            .await
+        else {
+            return;
+        };
+
+        self.log.record(GameEvent::WonPropertyAuction {
+            player: winner.name.get_value(),
+            property: property.data.title.to_owned(),
+            price,
+        });
+        if let Err(error) = property.buy_for(&winner, price, self) {
+            tracing::warn!("Unable to sell the auctioned property: {}", error);
+        }
+    }
+
+    /// Lets the player mortgage properties and sell agencies, until they have `amount` or go
+    /// bankrupt.
+    pub async fn raise_money(&self, player: &Player, amount: Money) {
+        #[derive(Clone, Copy)]
+        enum Action {
+            Mortgage,
+            SellAgency,
+            GoBankrupt,
+        }
+
+        loop {
+            let balance = untrack(|| player.balance());
+            if balance >= amount {
+                return;
+            }
+
+            let owned = untrack(|| {
+                self.cells
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, cell)| Some((index, cell.try_unwrap_property().ok()?)))
+                    .filter(|(_, property)| property.owner() == Some(*player))
+                    .collect::<Vec<_>>()
+            });
+            let mortgageable = untrack(|| {
+                owned
+                    .iter()
+                    .filter(|(_, property)| property.check_mortgage(self).is_ok())
+                    .map(|(index, _)| *index)
+                    .collect::<Vec<_>>()
+            });
+            let sellable = untrack(|| {
+                owned
+                    .iter()
+                    .filter(|(_, property)| property.check_sell_agency(self).is_ok())
+                    .map(|(index, _)| *index)
+                    .collect::<Vec<_>>()
+            });
+
+            let mut actions = Vec::new();
+            if !mortgageable.is_empty() {
+                actions.push((
+                    Action::Mortgage,
+                    self.i18n.t("button.mortgage_property", &[]),
+                ));
+            }
+            if !sellable.is_empty() {
+                actions.push((
+                    Action::SellAgency,
+                    self.i18n.t("button.sell_an_agency", &[]),
+                ));
+            }
+            actions.push((Action::GoBankrupt, self.i18n.t("button.go_bankrupt", &[])));
+
+            let text = self.i18n.t(
+                "prompt.raise_money",
+                &[
+                    ("player", &player.name.get_value()),
+                    (
+                        "amount",
+                        &self.i18n.money(
+                            amount
+                                .checked_sub(balance)
+                                .expect("Debt should not overflow"),
+                        ),
+                    ),
+                ],
+            );
+            let labels = actions
+                .iter()
+                .map(|(_, label)| label.as_str())
+                .collect::<Vec<_>>();
+            let response = self.ask(Prompt::choices(&text, &labels)).await;
+            let action = match response {
+                PromptResponse::Choice(choice) => actions.get(choice).map(|(action, _)| *action),
+                _ => None,
+            };
+            let cells = match action {
+                Some(Action::Mortgage) => mortgageable,
+                Some(Action::SellAgency) => sellable,
+                Some(Action::GoBankrupt) | None => return,
+            };
+
+            // Going back would only ask again, so the player who does not act raises the money.
+            let default = PromptResponse::Property(cells[0]);
+            let prompt = Prompt::property(&self.i18n.t("prompt.which_property", &[]), cells)
+                .with_cancel(&self.i18n.t("button.back", &[]))
+                .with_default(default);
+            let PromptResponse::Property(cell) = self.ask(prompt).await else {
+                continue;
+            };
+            let Cell::Property(property) = self.get_cell(cell) else {
+                continue;
+            };
+            let result = if let Some(Action::SellAgency) = action {
+                property
+                    .sell_agency(self)
+                    .map_err(|error| error.to_string())
+            } else {
+                property.mortgage(self).map_err(|error| error.to_string())
+            };
+            if let Err(error) = result {
+                tracing::warn!("Unable to raise money: {}", error);
+            }
+        }
+    }
+
+    /// Surrenders the player, once the server confirmed it.
+    pub fn player_surrendered(&self, player_id: PlayerId) {
+        let player = self
+            .players
+            .with_untracked(|players| players.get(&player_id).copied());
+        match player {
+            Some(player) if !player.has_lost() => self.surrender_player(&player),
+            Some(_) => {}
+            None => tracing::warn!("Unknown player {} surrendered", player_id),
+        }
+    }
+
+    /// Records the vote on a draw, and ends the game if everyone agreed.
+    pub fn draw_voted(&self, vote: &DrawVote) {
+        let Some(player) = self
+            .players
+            .with_untracked(|players| players.get(&vote.player_id).copied())
+        else {
+            tracing::warn!("Unknown player {} voted on a draw", vote.player_id);
+            return;
+        };
+        let player = player.name.get_value();
+
+        self.log.record(match (vote.agree, vote.votes.len()) {
+            (false, _) => GameEvent::DeclinedDraw { player },
+            (true, 1) => GameEvent::OfferedDraw { player },
+            (true, _) => GameEvent::AgreedToDraw { player },
+        });
+
+        if vote.is_agreed {
+            self.log.record(GameEvent::Drawn);
+            self.is_drawn.set(true);
+            self.abort_all_tasks();
+            self.in_game_modal_state.close();
+        }
+    }
+
+    /// Gives whatever the player has left to their creditor, unless it is the bank, and takes the
+    /// player out of the game.
+    pub fn go_bankrupt(&self, player: &Player, creditor: Option<Player>) {
+        if let Some(creditor) = creditor {
+            let left = untrack(|| player.balance());
+            self.transfer(
+                Party::Player(player.id),
+                Party::Player(creditor.id),
+                left,
+                TransferReason::Bankruptcy,
+            )
+            .expect("Player should be able to pay what they have");
+        }
+        self.surrender_player(player);
+    }
+
     pub fn surrender_player(&self, player: &Player) {
         player.surrender();
+        self.stats.update(|stats| stats.eliminated.push(player.id));
+        self.log.record(GameEvent::Surrendered {
+            player: player.name.get_value(),
+        });
         self.cells
             .iter()
             .filter_map(|cell| cell.try_unwrap_property().ok())
             .filter(|p| untrack(|| p.owner()).is_some_and(|owner| owner == *player))
-            .for_each(|p| p.remove_owner());
+            .for_each(|p| p.return_to_bank());
 
-        if self.self_player == *player {
+        let is_current = isolog::dbg!(self.current_player.get_untracked() == *player);
+        // Tasks and prompts of the turn belong to the player that is gone.
+        if self.self_player == Some(*player) || is_current {
             self.abort_all_tasks();
         }
-
-        if isolog::dbg!(self.current_player.get_untracked() == *player) {
+        if is_current {
+            self.in_game_modal_state.close();
             self.finish_turn();
         }
     }
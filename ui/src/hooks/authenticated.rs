@@ -0,0 +1,30 @@
+use leptos::prelude::*;
+use leptos_router::hooks::{use_location, use_navigate};
+
+use crate::server::ServerConnection;
+
+/// Calls `on_ready` once the player is logged in. Otherwise sends them to the login page, which
+/// brings them back here afterwards.
+pub fn use_authenticated(on_ready: impl Fn() + 'static) {
+    let connection = ServerConnection::use_context();
+    let navigate = use_navigate();
+    let location = use_location();
+    let is_done = StoredValue::new(false);
+
+    Effect::new(move |_| {
+        if is_done.get_value() || !connection.is_auth_checked() {
+            return;
+        }
+        is_done.set_value(true);
+
+        if connection.self_player().is_some() {
+            on_ready();
+        } else {
+            let next = location.pathname.get_untracked();
+            navigate(
+                &format!("/?next={}", js_sys::encode_uri_component(&next)),
+                Default::default(),
+            );
+        }
+    });
+}
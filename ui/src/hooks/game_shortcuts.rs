@@ -0,0 +1,38 @@
+use leptos::{ev, prelude::*};
+use web_sys::Element;
+
+use crate::{components::in_game_modal::PromptAction, game_state::GameState};
+
+/// Answers the signed-in player's prompts from the keyboard: R rolls the dice, B buys, E ends the
+/// turn, D or Escape decline. A key does nothing, unless the shown prompt offers its move.
+pub fn use_game_shortcuts(game_state: GameState) {
+    let handle = window_event_listener(ev::keydown, move |event| {
+        if event.repeat() || event.ctrl_key() || event.alt_key() || event.meta_key() {
+            return;
+        }
+        // Typing into the chat should not make moves.
+        let is_typing = event_target::<Element>(&event)
+            .closest("input, textarea, select, [contenteditable]")
+            .ok()
+            .flatten()
+            .is_some();
+        // Moves are only offered to the player whose turn it is.
+        if is_typing || !untrack(|| game_state.is_own_turn()) {
+            return;
+        }
+
+        let modal_state = game_state.in_game_modal_state;
+        let is_answered = match event.key().to_lowercase().as_str() {
+            "r" => modal_state.press(PromptAction::Roll),
+            "b" => modal_state.press(PromptAction::Buy),
+            "e" => modal_state.press(PromptAction::EndTurn),
+            "d" | "escape" => modal_state.decline(),
+            _ => return,
+        };
+        if is_answered {
+            event.prevent_default();
+        }
+    });
+
+    on_cleanup(move || handle.remove());
+}
@@ -1,2 +1,4 @@
+pub mod authenticated;
+pub mod game_shortcuts;
 pub mod redirect;
-pub mod window_scroll;
+pub mod window_size;
@@ -0,0 +1,16 @@
+use leptos::{ev, prelude::*};
+
+/// Inner size of the window, which also changes when a phone is rotated.
+pub fn use_window_size() -> (Signal<f64>, Signal<f64>) {
+    let width = RwSignal::new(window().inner_width().unwrap().as_f64().unwrap());
+    let height = RwSignal::new(window().inner_height().unwrap().as_f64().unwrap());
+
+    let handler = window_event_listener(ev::resize, move |_| {
+        width.set(window().inner_width().unwrap().as_f64().unwrap());
+        height.set(window().inner_height().unwrap().as_f64().unwrap());
+    });
+
+    on_cleanup(move || handler.remove());
+
+    (width.into(), height.into())
+}
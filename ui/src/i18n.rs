@@ -0,0 +1,815 @@
+use std::fmt::Display;
+
+use leptos::prelude::*;
+use shared::money::MoneyFormat;
+
+use crate::{
+    cell::{Money, PropertyGroup},
+    game_log::GameEvent,
+    ledger::TransferReason,
+    preview::LandingEffect,
+};
+
+/// Key in the local storage, under which the chosen locale is saved.
+const LOCALE_KEY: &str = "rustopoly.locale";
+
+/// Language of the interface.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+}
+
+impl Locale {
+    pub const VARIANTS: [Locale; 2] = [Locale::En, Locale::De];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::De => "de",
+        }
+    }
+
+    /// Name of the language in the language itself.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Locale::En => "English",
+            Locale::De => "Deutsch",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::VARIANTS
+            .into_iter()
+            .find(|locale| locale.code() == code)
+    }
+
+    /// How the locale groups digits and writes fractions of amounts.
+    pub fn money_format(&self) -> MoneyFormat {
+        match self {
+            Locale::En => MoneyFormat::default(),
+            Locale::De => MoneyFormat::default()
+                .with_separator(Some('.'))
+                .with_decimal_point(','),
+        }
+    }
+
+    fn messages(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::En => EN,
+            Locale::De => DE,
+        }
+    }
+}
+
+/// Messages by their keys. `{name}` is replaced with the argument of the same name.
+static EN: &[(&str, &str)] = &[
+    ("prompt.roll", "Roll thy dice!"),
+    (
+        "prompt.rent",
+        "Oi! You owe this fine lad some moneh: {rent}$",
+    ),
+    (
+        "prompt.buy",
+        "Oi! You wanna buy this fine land? It's gonna cost ya {price}$",
+    ),
+    ("prompt.chance", "Your chance is: {amount}$"),
+    ("prompt.tax", "You owe me: {amount}$"),
+    (
+        "prompt.auction",
+        "Auction for {subject}! {player}, how much do you bid?",
+    ),
+    (
+        "prompt.raise_money",
+        "{player}, you need {amount}$ more. How will you raise it?",
+    ),
+    ("prompt.which_property", "Which one?"),
+    ("auction.scarce_agency", "a scarce agency"),
+    ("button.roll", "Roll"),
+    ("button.pay", "Pay moneh"),
+    ("button.get", "Get moneh"),
+    ("button.buy", "Buy"),
+    ("button.decline", "Decline"),
+    ("button.bid", "Bid"),
+    ("button.pass", "Pass"),
+    ("button.mortgage_property", "Mortgage a property"),
+    ("button.sell_an_agency", "Sell an agency"),
+    ("button.go_bankrupt", "Go bankrupt"),
+    ("button.back", "Back"),
+    ("button.recover", "Recover"),
+    ("button.mortgage", "Mortgage"),
+    ("button.build_agency", "Build agency"),
+    ("button.sell_agency", "Sell agency"),
+    ("button.resign", "Resign"),
+    ("button.keep_playing", "Keep playing"),
+    ("button.offer_draw", "Offer a draw"),
+    ("button.agree", "Agree"),
+    ("button.withdraw", "Withdraw"),
+    ("cell.start", "Start"),
+    ("cell.jail", "Jail"),
+    ("cell.free_parking", "Free parking"),
+    ("cell.go_to_jail", "Go to jail"),
+    ("cell.tax", "Tax: {amount}"),
+    ("cell.chance", "Chance"),
+    ("property.simple", "Build agencies to make rent higher."),
+    (
+        "property.transport",
+        "Rent depends on amount of autos you have.",
+    ),
+    (
+        "property.utility",
+        "Rent depends on the dice sum and amount of developers you have.",
+    ),
+    ("property.base_rent", "Base rent"),
+    ("property.one_field", "1 field"),
+    ("property.fields", "{count} fields"),
+    ("property.price", "Field's price"),
+    ("property.mortgage", "Field's mortgage"),
+    ("property.recovery", "Field's recovery"),
+    ("property.forfeited_in", "Forfeited in"),
+    ("property.rounds", "{count} rounds"),
+    ("property.agency_price", "Agency price"),
+    (
+        "player.resign_warning",
+        "Give up? Your properties return to the bank.",
+    ),
+    ("draw.offered", "Draw offered: {votes}/{players} agree"),
+    ("board.players", "Players"),
+    ("board.chat", "Chat"),
+    ("board.zoom", "Zoom"),
+    ("board.zoom_in", "Zoom in"),
+    ("board.zoom_out", "Zoom out"),
+    ("bank.title", "Bank"),
+    ("bank.agencies", "Agencies"),
+    ("bank.top_levels", "Top-level agencies"),
+    (
+        "app.restarting",
+        "Server is restarting. Reload the page in a minute.",
+    ),
+    ("room.waiting", "Waiting for players…"),
+    ("room.joining", "Joining the room…"),
+    ("board.title", "Board"),
+    ("board.rolled", "Rolled {a} and {b}"),
+    ("cell.property", "Property: {title}"),
+    ("property.agency_sell_price", "The bank pays {price}k back"),
+    ("player.rank", "#{rank} · worth {net_worth}"),
+    ("player.rank_title", "Place by net worth"),
+    ("modal.your_move", "Your move"),
+    ("modal.waiting", "{count} more waiting"),
+    ("modal.number_range", "From {min} to {max}"),
+    ("modal.decline_shortcut", "D or Escape"),
+    ("chat.all", "All"),
+    ("chat.chat", "Chat"),
+    ("chat.spectators", "Spectators"),
+    ("chat.log", "Log"),
+    ("chat.export", "Export"),
+    ("chat.muted", "Muted:"),
+    ("chat.unmute", "Unmute"),
+    ("chat.new_messages", "{count} new ↓"),
+    ("chat.message", "Message"),
+    ("chat.message_spectators", "Message spectators"),
+    ("chat.send", "Send"),
+    ("chat.spectator", "spectator"),
+    ("chat.mute", "mute"),
+    ("chat.mute_title", "Hide messages of this player"),
+    ("summary.draw", "Draw"),
+    ("summary.finished", "Game Finished"),
+    ("summary.rounds_played", "Rounds played: {count}"),
+    (
+        "summary.most_profitable",
+        "Most profitable: {property} ({income}$)",
+    ),
+    ("summary.statistics", "Statistics"),
+    ("summary.replay", "Replay"),
+    ("summary.back_to_lobby", "Back to lobby"),
+    ("summary.net_worth", "Net worth by round"),
+    ("summary.player", "Player"),
+    ("summary.rent_paid", "Rent paid"),
+    ("summary.rent_received", "Rent received"),
+    ("summary.watch_rematch", "Watch rematch"),
+    ("summary.join_rematch", "Join rematch"),
+    ("summary.rematch", "Rematch"),
+    ("summary.pause", "Pause"),
+    ("summary.play", "Play"),
+    ("stats.rent_collected", "Rent collected"),
+    ("stats.owners", "Owners"),
+    ("stats.landings", "Landings of {player}"),
+    ("stats.purchase_pays_off", "Purchase pays off in"),
+    ("stats.agency_pays_off", "Agency pays off in"),
+    ("stats.never", "never"),
+    ("standings.player", "Player"),
+    ("standings.cash", "Cash"),
+    ("standings.worth", "Worth"),
+    ("transactions.title", "Transactions"),
+    ("transactions.unknown_player", "Player {id}"),
+    ("lobby.rooms", "Rooms"),
+    ("lobby.room_name", "Room name"),
+    ("lobby.create", "Create"),
+    ("lobby.join", "Join"),
+    ("lobby.spectate", "Spectate"),
+    ("lobby.classic", "Classic"),
+    ("lobby.blitz", "Blitz"),
+    ("lobby.open", "Open"),
+    ("lobby.running", "Running"),
+    ("lobby.finished", "Finished"),
+    ("login.your_name", "Your name"),
+    ("login.play", "Play"),
+    ("locale.language", "Language"),
+    ("cell.owned_by", ", owned by {owner}"),
+    ("cell.mortgaged", ", mortgaged"),
+    ("cell.for_sale", ", for sale for {price}k"),
+    ("cell.and", " and "),
+    ("cell.players_here", ", {players} here"),
+    ("preview.nothing", "Nothing happens"),
+    ("preview.start_reward", "Get {reward}$"),
+    ("preview.go_to_jail", "Go to jail"),
+    ("preview.tax", "Pay {tax}$ tax"),
+    ("preview.chance", "Chance from {min}$ to {max}$"),
+    ("preview.rent", "Pay {rent}$ rent to {owner}"),
+    ("preview.own_property", "Own property"),
+    ("preview.can_buy", "Can buy for {price}$"),
+    ("preview.start_bonus", ", get {reward}$ for passing Start"),
+    ("event.rolled_dice", "{player} rolled {a} and {b}"),
+    (
+        "event.passed_start",
+        "{player} passed Start and got {reward}$",
+    ),
+    (
+        "event.stepped_on_start",
+        "{player} stepped on Start and got {reward}$",
+    ),
+    (
+        "event.stepped_on_own_property",
+        "{player} stepped on their own property {property}",
+    ),
+    (
+        "event.paid_rent",
+        "{player} paid {rent}$ rent to {owner} for {property}",
+    ),
+    (
+        "event.bought_property",
+        "{player} bought {property} for {price}$",
+    ),
+    (
+        "event.declined_property",
+        "{player} declined to buy {property}",
+    ),
+    (
+        "event.built_agency",
+        "{player} built an agency on {property}",
+    ),
+    ("event.sold_agency", "{player} sold an agency on {property}"),
+    (
+        "event.won_agency_auction",
+        "{player} won the agency auction for {property} with {price}$",
+    ),
+    (
+        "event.mortgaged_property",
+        "{player} mortgaged {property} for {reward}$",
+    ),
+    (
+        "event.recovered_property",
+        "{player} recovered {property} for {price}$",
+    ),
+    (
+        "event.mortgage_expiring",
+        "{player} loses mortgaged {property} in {rounds_left} rounds, unless they recover it",
+    ),
+    (
+        "event.lost_mortgaged_property",
+        "{player} lost mortgaged {property}",
+    ),
+    (
+        "event.won_property_auction",
+        "{player} won the auction for {property} with {price}$",
+    ),
+    ("event.lost_on_chance", "{player} lost {amount}$ on Chance"),
+    ("event.won_on_chance", "{player} won {amount}$ on Chance"),
+    ("event.paid_tax", "{player} paid {amount}$ tax"),
+    ("event.went_to_jail", "{player} went to jail"),
+    ("event.surrendered", "{player} surrendered"),
+    ("event.offered_draw", "{player} offered a draw"),
+    ("event.agreed_to_draw", "{player} agreed to a draw"),
+    ("event.declined_draw", "{player} declined the draw"),
+    ("event.drawn", "The game ended in a draw"),
+    ("transfer.passed_start", "Passed Start"),
+    ("transfer.stepped_on_start", "Stepped on Start"),
+    ("transfer.rent", "Rent for {property}"),
+    ("transfer.bankruptcy", "Bankruptcy"),
+    ("transfer.purchase", "Purchase of {property}"),
+    ("transfer.agency", "Agency on {property}"),
+    ("transfer.agency_sale", "Sold agency on {property}"),
+    ("transfer.mortgage", "Mortgage of {property}"),
+    ("transfer.recovery", "Recovery of {property}"),
+    ("transfer.chance", "Chance"),
+    ("transfer.tax", "Tax"),
+    ("group.Perfumes", "Perfumes"),
+    ("group.Clothing", "Clothing"),
+    ("group.Web Services", "Web Services"),
+    ("group.Drinks", "Drinks"),
+    ("group.Airlines", "Airlines"),
+    ("group.Restaurants", "Restaurants"),
+    ("group.Hotels", "Hotels"),
+    ("group.Electronics", "Electronics"),
+    ("group.Autos", "Autos"),
+    ("group.Game Developers", "Game Developers"),
+];
+
+static DE: &[(&str, &str)] = &[
+    ("prompt.roll", "Wirf die Würfel!"),
+    (
+        "prompt.rent",
+        "He! Du schuldest dem feinen Kerl Kohle: {rent}$",
+    ),
+    (
+        "prompt.buy",
+        "He! Willst du das feine Land kaufen? Kostet dich {price}$",
+    ),
+    ("prompt.chance", "Deine Chance: {amount}$"),
+    ("prompt.tax", "Du schuldest mir: {amount}$"),
+    (
+        "prompt.auction",
+        "Auktion für {subject}! {player}, wie viel bietest du?",
+    ),
+    (
+        "prompt.raise_money",
+        "{player}, dir fehlen {amount}$. Wie besorgst du sie?",
+    ),
+    ("prompt.which_property", "Welches?"),
+    ("auction.scarce_agency", "eine knappe Filiale"),
+    ("button.roll", "Würfeln"),
+    ("button.pay", "Kohle zahlen"),
+    ("button.get", "Kohle holen"),
+    ("button.buy", "Kaufen"),
+    ("button.decline", "Ablehnen"),
+    ("button.bid", "Bieten"),
+    ("button.pass", "Passen"),
+    ("button.mortgage_property", "Feld verpfänden"),
+    ("button.sell_an_agency", "Filiale verkaufen"),
+    ("button.go_bankrupt", "Bankrott gehen"),
+    ("button.back", "Zurück"),
+    ("button.recover", "Auslösen"),
+    ("button.mortgage", "Verpfänden"),
+    ("button.build_agency", "Filiale bauen"),
+    ("button.sell_agency", "Filiale verkaufen"),
+    ("button.resign", "Aufgeben"),
+    ("button.keep_playing", "Weiterspielen"),
+    ("button.offer_draw", "Remis anbieten"),
+    ("button.agree", "Zustimmen"),
+    ("button.withdraw", "Zurückziehen"),
+    ("cell.start", "Start"),
+    ("cell.jail", "Gefängnis"),
+    ("cell.free_parking", "Frei parken"),
+    ("cell.go_to_jail", "Ins Gefängnis"),
+    ("cell.tax", "Steuer: {amount}"),
+    ("cell.chance", "Chance"),
+    ("property.simple", "Baue Filialen, um die Miete zu erhöhen."),
+    (
+        "property.transport",
+        "Die Miete hängt von der Anzahl deiner Autos ab.",
+    ),
+    (
+        "property.utility",
+        "Die Miete hängt von der Augensumme und der Anzahl deiner Entwickler ab.",
+    ),
+    ("property.base_rent", "Grundmiete"),
+    ("property.one_field", "1 Feld"),
+    ("property.fields", "{count} Felder"),
+    ("property.price", "Preis des Feldes"),
+    ("property.mortgage", "Hypothek des Feldes"),
+    ("property.recovery", "Auslösung des Feldes"),
+    ("property.forfeited_in", "Verfällt in"),
+    ("property.rounds", "{count} Runden"),
+    ("property.agency_price", "Preis der Filiale"),
+    (
+        "player.resign_warning",
+        "Aufgeben? Deine Felder gehen an die Bank zurück.",
+    ),
+    (
+        "draw.offered",
+        "Remis angeboten: {votes}/{players} stimmen zu",
+    ),
+    ("board.players", "Spieler"),
+    ("board.chat", "Chat"),
+    ("board.zoom", "Zoom"),
+    ("board.zoom_in", "Vergrößern"),
+    ("board.zoom_out", "Verkleinern"),
+    ("bank.title", "Bank"),
+    ("bank.agencies", "Filialen"),
+    ("bank.top_levels", "Filialen der höchsten Stufe"),
+    ("app.restarting", "Der Server startet neu. Lade die Seite in einer Minute neu."),
+    ("room.waiting", "Warte auf Spieler…"),
+    ("room.joining", "Betrete den Raum…"),
+    ("board.title", "Spielbrett"),
+    ("board.rolled", "{a} und {b} gewürfelt"),
+    ("cell.property", "Feld: {title}"),
+    ("property.agency_sell_price", "Die Bank zahlt {price}k zurück"),
+    ("player.rank", "#{rank} · Wert {net_worth}"),
+    ("player.rank_title", "Platz nach Vermögen"),
+    ("modal.your_move", "Dein Zug"),
+    ("modal.waiting", "{count} weitere warten"),
+    ("modal.number_range", "Von {min} bis {max}"),
+    ("modal.decline_shortcut", "D oder Escape"),
+    ("chat.all", "Alle"),
+    ("chat.chat", "Chat"),
+    ("chat.spectators", "Zuschauer"),
+    ("chat.log", "Verlauf"),
+    ("chat.export", "Exportieren"),
+    ("chat.muted", "Stummgeschaltet:"),
+    ("chat.unmute", "Stummschaltung aufheben"),
+    ("chat.new_messages", "{count} neu ↓"),
+    ("chat.message", "Nachricht"),
+    ("chat.message_spectators", "Nachricht an Zuschauer"),
+    ("chat.send", "Senden"),
+    ("chat.spectator", "Zuschauer"),
+    ("chat.mute", "stumm"),
+    ("chat.mute_title", "Nachrichten dieses Spielers ausblenden"),
+    ("summary.draw", "Remis"),
+    ("summary.finished", "Spiel beendet"),
+    ("summary.rounds_played", "Gespielte Runden: {count}"),
+    ("summary.most_profitable", "Am profitabelsten: {property} ({income}$)"),
+    ("summary.statistics", "Statistik"),
+    ("summary.replay", "Wiederholung"),
+    ("summary.back_to_lobby", "Zurück zur Lobby"),
+    ("summary.net_worth", "Vermögen pro Runde"),
+    ("summary.player", "Spieler"),
+    ("summary.rent_paid", "Miete gezahlt"),
+    ("summary.rent_received", "Miete erhalten"),
+    ("summary.watch_rematch", "Revanche ansehen"),
+    ("summary.join_rematch", "Revanche beitreten"),
+    ("summary.rematch", "Revanche"),
+    ("summary.pause", "Pause"),
+    ("summary.play", "Abspielen"),
+    ("stats.rent_collected", "Eingenommene Miete"),
+    ("stats.owners", "Besitzer"),
+    ("stats.landings", "Besuche von {player}"),
+    ("stats.purchase_pays_off", "Kauf lohnt sich in"),
+    ("stats.agency_pays_off", "Filiale lohnt sich in"),
+    ("stats.never", "nie"),
+    ("standings.player", "Spieler"),
+    ("standings.cash", "Bargeld"),
+    ("standings.worth", "Wert"),
+    ("transactions.title", "Überweisungen"),
+    ("transactions.unknown_player", "Spieler {id}"),
+    ("lobby.rooms", "Räume"),
+    ("lobby.room_name", "Name des Raums"),
+    ("lobby.create", "Erstellen"),
+    ("lobby.join", "Beitreten"),
+    ("lobby.spectate", "Zuschauen"),
+    ("lobby.classic", "Klassisch"),
+    ("lobby.blitz", "Blitz"),
+    ("lobby.open", "Offen"),
+    ("lobby.running", "Läuft"),
+    ("lobby.finished", "Beendet"),
+    ("login.your_name", "Dein Name"),
+    ("login.play", "Spielen"),
+    ("locale.language", "Sprache"),
+    ("cell.owned_by", ", gehört {owner}"),
+    ("cell.mortgaged", ", verpfändet"),
+    ("cell.for_sale", ", zu verkaufen für {price}k"),
+    ("cell.and", " und "),
+    ("cell.players_here", ", hier: {players}"),
+    ("preview.nothing", "Nichts passiert"),
+    ("preview.start_reward", "Bekomme {reward}$"),
+    ("preview.go_to_jail", "Ins Gefängnis"),
+    ("preview.tax", "Zahle {tax}$ Steuer"),
+    ("preview.chance", "Chance von {min}$ bis {max}$"),
+    ("preview.rent", "Zahle {owner} {rent}$ Miete"),
+    ("preview.own_property", "Eigenes Feld"),
+    ("preview.can_buy", "Für {price}$ kaufbar"),
+    ("preview.start_bonus", ", bekomme {reward}$ für das Überqueren von Start"),
+    ("event.rolled_dice", "{player} hat {a} und {b} gewürfelt"),
+    ("event.passed_start", "{player} ist über Start gegangen und hat {reward}$ bekommen"),
+    ("event.stepped_on_start", "{player} ist auf Start gelandet und hat {reward}$ bekommen"),
+    ("event.stepped_on_own_property", "{player} ist auf dem eigenen Feld {property} gelandet"),
+    ("event.paid_rent", "{player} hat {owner} {rent}$ Miete für {property} gezahlt"),
+    ("event.bought_property", "{player} hat {property} für {price}$ gekauft"),
+    ("event.declined_property", "{player} hat {property} nicht gekauft"),
+    ("event.built_agency", "{player} hat eine Filiale auf {property} gebaut"),
+    ("event.sold_agency", "{player} hat eine Filiale auf {property} verkauft"),
+    ("event.won_agency_auction", "{player} hat die Versteigerung der Filiale für {property} mit {price}$ gewonnen"),
+    ("event.mortgaged_property", "{player} hat {property} für {reward}$ verpfändet"),
+    ("event.recovered_property", "{player} hat {property} für {price}$ ausgelöst"),
+    ("event.mortgage_expiring", "{player} verliert das verpfändete {property} in {rounds_left} Runden, wenn es nicht ausgelöst wird"),
+    ("event.lost_mortgaged_property", "{player} hat das verpfändete {property} verloren"),
+    ("event.won_property_auction", "{player} hat die Versteigerung von {property} mit {price}$ gewonnen"),
+    ("event.lost_on_chance", "{player} hat bei der Chance {amount}$ verloren"),
+    ("event.won_on_chance", "{player} hat bei der Chance {amount}$ gewonnen"),
+    ("event.paid_tax", "{player} hat {amount}$ Steuer gezahlt"),
+    ("event.went_to_jail", "{player} ist ins Gefängnis gegangen"),
+    ("event.surrendered", "{player} hat aufgegeben"),
+    ("event.offered_draw", "{player} hat Remis angeboten"),
+    ("event.agreed_to_draw", "{player} hat dem Remis zugestimmt"),
+    ("event.declined_draw", "{player} hat das Remis abgelehnt"),
+    ("event.drawn", "Das Spiel endete remis"),
+    ("transfer.passed_start", "Über Start gegangen"),
+    ("transfer.stepped_on_start", "Auf Start gelandet"),
+    ("transfer.rent", "Miete für {property}"),
+    ("transfer.bankruptcy", "Bankrott"),
+    ("transfer.purchase", "Kauf von {property}"),
+    ("transfer.agency", "Filiale auf {property}"),
+    ("transfer.agency_sale", "Filiale auf {property} verkauft"),
+    ("transfer.mortgage", "Hypothek auf {property}"),
+    ("transfer.recovery", "Auslösung von {property}"),
+    ("transfer.chance", "Chance"),
+    ("transfer.tax", "Steuer"),
+    ("group.Perfumes", "Parfüms"),
+    ("group.Clothing", "Kleidung"),
+    ("group.Web Services", "Webdienste"),
+    ("group.Drinks", "Getränke"),
+    ("group.Airlines", "Fluglinien"),
+    ("group.Restaurants", "Restaurants"),
+    ("group.Hotels", "Hotels"),
+    ("group.Electronics", "Elektronik"),
+    ("group.Autos", "Autos"),
+    ("group.Game Developers", "Spieleentwickler"),
+];
+
+/// Translations into the chosen locale, shared through the whole app.
+#[derive(Debug, Clone, Copy)]
+pub struct I18n(RwSignal<Locale>);
+
+impl Default for I18n {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl I18n {
+    /// Starts with the locale chosen last time, or English.
+    pub fn new() -> Self {
+        let saved = local_storage()
+            .and_then(|storage| storage.get(LOCALE_KEY).ok().flatten())
+            .and_then(|code| Locale::from_code(&code));
+        Self::with_locale(saved.unwrap_or_default())
+    }
+
+    pub fn with_locale(locale: Locale) -> Self {
+        Self(RwSignal::new(locale))
+    }
+
+    pub fn provide_context(&self) {
+        provide_context(*self);
+    }
+
+    pub fn use_context() -> Self {
+        expect_context::<Self>()
+    }
+
+    pub fn locale(&self) -> Locale {
+        self.0.get()
+    }
+
+    pub fn set_locale(&self, locale: Locale) {
+        if let Some(storage) = local_storage() {
+            if let Err(error) = storage.set(LOCALE_KEY, locale.code()) {
+                tracing::warn!("Unable to save the locale: {:?}", error);
+            }
+        }
+        self.0.set(locale);
+    }
+
+    /// Message by its key, with the arguments put into it. Messages missing from the locale are
+    /// taken from English, unknown keys are shown as they are.
+    pub fn t(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        let find = |locale: Locale| {
+            locale
+                .messages()
+                .iter()
+                .find(|(message_key, _)| *message_key == key)
+                .map(|(_, message)| *message)
+        };
+        let Some(message) = find(self.locale()).or_else(|| find(Locale::En)) else {
+            tracing::warn!("Missing translation for \"{}\"", key);
+            return key.to_owned();
+        };
+
+        args.iter()
+            .fold(message.to_owned(), |message, (name, value)| {
+                message.replace(&format!("{{{name}}}"), &value.to_string())
+            })
+    }
+
+    pub fn money_format(&self) -> MoneyFormat {
+        self.locale().money_format()
+    }
+
+    /// Amount with the digits grouped the way the locale does it.
+    pub fn money(&self, amount: Money) -> String {
+        amount.format(&self.money_format())
+    }
+
+    pub fn group_title(&self, group: &PropertyGroup) -> String {
+        self.t(&format!("group.{}", group.title), &[])
+    }
+
+    /// Entry of the game log.
+    pub fn event(&self, event: &GameEvent) -> String {
+        match event {
+            GameEvent::RolledDice {
+                player,
+                dice: (a, b),
+            } => self.t(
+                "event.rolled_dice",
+                &[("player", player), ("a", a), ("b", b)],
+            ),
+            GameEvent::PassedStart { player, reward } => self.t(
+                "event.passed_start",
+                &[("player", player), ("reward", &self.money(*reward))],
+            ),
+            GameEvent::SteppedOnStart { player, reward } => self.t(
+                "event.stepped_on_start",
+                &[("player", player), ("reward", &self.money(*reward))],
+            ),
+            GameEvent::SteppedOnOwnProperty { player, property } => self.t(
+                "event.stepped_on_own_property",
+                &[("player", player), ("property", property)],
+            ),
+            GameEvent::PaidRent {
+                player,
+                owner,
+                property,
+                rent,
+            } => self.t(
+                "event.paid_rent",
+                &[
+                    ("player", player),
+                    ("owner", owner),
+                    ("property", property),
+                    ("rent", &self.money(*rent)),
+                ],
+            ),
+            GameEvent::BoughtProperty {
+                player,
+                property,
+                price,
+            } => self.t(
+                "event.bought_property",
+                &[
+                    ("player", player),
+                    ("property", property),
+                    ("price", &self.money(*price)),
+                ],
+            ),
+            GameEvent::DeclinedProperty { player, property } => self.t(
+                "event.declined_property",
+                &[("player", player), ("property", property)],
+            ),
+            GameEvent::BuiltAgency { player, property } => self.t(
+                "event.built_agency",
+                &[("player", player), ("property", property)],
+            ),
+            GameEvent::SoldAgency { player, property } => self.t(
+                "event.sold_agency",
+                &[("player", player), ("property", property)],
+            ),
+            GameEvent::WonAgencyAuction {
+                player,
+                property,
+                price,
+            } => self.t(
+                "event.won_agency_auction",
+                &[
+                    ("player", player),
+                    ("property", property),
+                    ("price", &self.money(*price)),
+                ],
+            ),
+            GameEvent::MortgagedProperty {
+                player,
+                property,
+                reward,
+            } => self.t(
+                "event.mortgaged_property",
+                &[
+                    ("player", player),
+                    ("property", property),
+                    ("reward", &self.money(*reward)),
+                ],
+            ),
+            GameEvent::RecoveredProperty {
+                player,
+                property,
+                price,
+            } => self.t(
+                "event.recovered_property",
+                &[
+                    ("player", player),
+                    ("property", property),
+                    ("price", &self.money(*price)),
+                ],
+            ),
+            GameEvent::MortgageExpiring {
+                player,
+                property,
+                rounds_left,
+            } => self.t(
+                "event.mortgage_expiring",
+                &[
+                    ("player", player),
+                    ("property", property),
+                    ("rounds_left", rounds_left),
+                ],
+            ),
+            GameEvent::LostMortgagedProperty { player, property } => self.t(
+                "event.lost_mortgaged_property",
+                &[("player", player), ("property", property)],
+            ),
+            GameEvent::WonPropertyAuction {
+                player,
+                property,
+                price,
+            } => self.t(
+                "event.won_property_auction",
+                &[
+                    ("player", player),
+                    ("property", property),
+                    ("price", &self.money(*price)),
+                ],
+            ),
+            GameEvent::Chance { player, amount } if amount.is_negative() => self.t(
+                "event.lost_on_chance",
+                &[("player", player), ("amount", &self.money(-*amount))],
+            ),
+            GameEvent::Chance { player, amount } => self.t(
+                "event.won_on_chance",
+                &[("player", player), ("amount", &self.money(*amount))],
+            ),
+            GameEvent::PaidTax { player, amount } => self.t(
+                "event.paid_tax",
+                &[("player", player), ("amount", &self.money(*amount))],
+            ),
+            GameEvent::WentToJail { player } => self.t("event.went_to_jail", &[("player", player)]),
+            GameEvent::Surrendered { player } => self.t("event.surrendered", &[("player", player)]),
+            GameEvent::OfferedDraw { player } => {
+                self.t("event.offered_draw", &[("player", player)])
+            }
+            GameEvent::AgreedToDraw { player } => {
+                self.t("event.agreed_to_draw", &[("player", player)])
+            }
+            GameEvent::DeclinedDraw { player } => {
+                self.t("event.declined_draw", &[("player", player)])
+            }
+            GameEvent::Drawn => self.t("event.drawn", &[]),
+        }
+    }
+
+    /// What landing on a cell would do to the player.
+    pub fn landing_effect(&self, effect: &LandingEffect) -> String {
+        match effect {
+            LandingEffect::Nothing => self.t("preview.nothing", &[]),
+            LandingEffect::StartReward(reward) => {
+                self.t("preview.start_reward", &[("reward", &self.money(*reward))])
+            }
+            LandingEffect::GoToJail => self.t("preview.go_to_jail", &[]),
+            LandingEffect::Tax(tax) => self.t("preview.tax", &[("tax", &self.money(*tax))]),
+            LandingEffect::Chance { min, max } => self.t(
+                "preview.chance",
+                &[("min", &self.money(*min)), ("max", &self.money(*max))],
+            ),
+            LandingEffect::Rent { owner, rent } => self.t(
+                "preview.rent",
+                &[
+                    ("rent", &self.money(*rent)),
+                    ("owner", &owner.name.get_value()),
+                ],
+            ),
+            LandingEffect::OwnProperty => self.t("preview.own_property", &[]),
+            LandingEffect::CanBuy { price } => {
+                self.t("preview.can_buy", &[("price", &self.money(*price))])
+            }
+        }
+    }
+
+    /// What the money of a transfer was for.
+    pub fn transfer_reason(&self, reason: &TransferReason) -> String {
+        match reason {
+            TransferReason::PassedStart => self.t("transfer.passed_start", &[]),
+            TransferReason::SteppedOnStart => self.t("transfer.stepped_on_start", &[]),
+            TransferReason::Rent { property } => self.t("transfer.rent", &[("property", property)]),
+            TransferReason::Bankruptcy => self.t("transfer.bankruptcy", &[]),
+            TransferReason::Purchase { property } => {
+                self.t("transfer.purchase", &[("property", property)])
+            }
+            TransferReason::Agency { property } => {
+                self.t("transfer.agency", &[("property", property)])
+            }
+            TransferReason::AgencySale { property } => {
+                self.t("transfer.agency_sale", &[("property", property)])
+            }
+            TransferReason::Mortgage { property } => {
+                self.t("transfer.mortgage", &[("property", property)])
+            }
+            TransferReason::Recovery { property } => {
+                self.t("transfer.recovery", &[("property", property)])
+            }
+            TransferReason::Chance => self.t("transfer.chance", &[]),
+            TransferReason::Tax => self.t("transfer.tax", &[]),
+        }
+    }
+}
+
+fn local_storage() -> Option<web_sys::Storage> {
+    window().local_storage().ok().flatten()
+}
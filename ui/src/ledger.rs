@@ -0,0 +1,220 @@
+use leptos::prelude::*;
+pub use shared::ledger::{Party, Transfer, TransferReason};
+
+use crate::player::PlayerId;
+
+/// Every money transfer of the game, in the order they happened.
+#[derive(Debug, Clone, Copy)]
+pub struct Ledger(RwSignal<Vec<Transfer>>);
+
+impl Default for Ledger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Ledger {
+    pub fn new() -> Self {
+        Self(RwSignal::new(Vec::new()))
+    }
+
+    pub fn record(&self, transfer: Transfer) {
+        tracing::debug!(
+            "Transfer: {:?} -> {:?}, {}$ ({:?})",
+            transfer.from,
+            transfer.to,
+            transfer.amount,
+            transfer.reason
+        );
+        self.0.update(|transfers| transfers.push(transfer));
+    }
+
+    /// Replaces the whole ledger, e.g. with the one from a snapshot.
+    pub fn replace(&self, transfers: Vec<Transfer>) {
+        self.0.set(transfers);
+    }
+
+    pub fn transfers(&self) -> ReadSignal<Vec<Transfer>> {
+        self.0.read_only()
+    }
+
+    /// Transfers the player paid or received, the latest first.
+    pub fn history(&self, player_id: PlayerId) -> Vec<Transfer> {
+        let player = Party::Player(player_id);
+        self.0.with(|transfers| {
+            transfers
+                .iter()
+                .rev()
+                .filter(|transfer| transfer.from == player || transfer.to == player)
+                .cloned()
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cell::{Money, Property},
+        game_state::GameState,
+        player::Player,
+    };
+
+    const CHANEL: usize = 1;
+    const HUGO_BOSS: usize = 3;
+
+    /// Money of the players, plus what the bank took in minus what it paid out. Nothing the
+    /// players do changes it.
+    fn total_money(game_state: &GameState) -> i64 {
+        let balances = game_state
+            .get_players()
+            .values()
+            .map(|player| i64::from(player.balance()))
+            .sum::<i64>();
+        let bank = game_state.ledger.transfers().with_untracked(|transfers| {
+            transfers
+                .iter()
+                .map(|transfer| match (transfer.from, transfer.to) {
+                    (Party::Player(_), Party::Bank) => i64::from(transfer.amount),
+                    (Party::Bank, Party::Player(_)) => -i64::from(transfer.amount),
+                    _ => 0,
+                })
+                .sum::<i64>()
+        });
+        balances + bank
+    }
+
+    fn game() -> (GameState, Player, Player) {
+        let game_state = GameState::local(&["Alice", "Bob"]);
+        let alice = game_state.get_player_by_id(0);
+        let bob = game_state.get_player_by_id(1);
+        (game_state, alice, bob)
+    }
+
+    fn property(game_state: &GameState, cell: usize) -> Property {
+        game_state
+            .get_cell(cell)
+            .try_unwrap_property()
+            .expect("Cell should be a property")
+    }
+
+    #[test]
+    fn buying_conserves_money() {
+        let (game_state, alice, _) = game();
+        let total = total_money(&game_state);
+
+        property(&game_state, CHANEL)
+            .buy(&alice, &game_state)
+            .unwrap();
+
+        assert_eq!(total_money(&game_state), total);
+        assert_eq!(game_state.ledger.history(alice.id).len(), 1);
+    }
+
+    #[test]
+    fn rent_conserves_money() {
+        let (game_state, alice, bob) = game();
+        let chanel = property(&game_state, CHANEL);
+        chanel.buy(&alice, &game_state).unwrap();
+        let total = total_money(&game_state);
+        let alice_balance = alice.balance();
+
+        chanel.pay_rent(&bob, &game_state).unwrap();
+
+        assert_eq!(total_money(&game_state), total);
+        assert!(alice.balance() > alice_balance);
+    }
+
+    #[test]
+    fn tax_conserves_money() {
+        let (game_state, alice, _) = game();
+        let total = total_money(&game_state);
+
+        game_state
+            .transfer(
+                Party::Player(alice.id),
+                Party::Bank,
+                Money::new(2000),
+                TransferReason::Tax,
+            )
+            .unwrap();
+
+        assert_eq!(total_money(&game_state), total);
+    }
+
+    #[test]
+    fn mortgage_and_recovery_conserve_money() {
+        let (game_state, alice, _) = game();
+        let chanel = property(&game_state, CHANEL);
+        chanel.buy(&alice, &game_state).unwrap();
+        let total = total_money(&game_state);
+
+        chanel.mortgage(&game_state).unwrap();
+        assert_eq!(total_money(&game_state), total);
+        assert!(chanel.mortgage(&game_state).is_err());
+
+        chanel.recover(&game_state).unwrap();
+        assert_eq!(total_money(&game_state), total);
+    }
+
+    #[test]
+    fn building_and_selling_agencies_conserve_money() {
+        let (game_state, alice, _) = game();
+        let chanel = property(&game_state, CHANEL);
+        chanel.buy(&alice, &game_state).unwrap();
+        property(&game_state, HUGO_BOSS)
+            .buy(&alice, &game_state)
+            .unwrap();
+        let total = total_money(&game_state);
+
+        chanel.build_agency(&game_state).unwrap();
+        assert_eq!(chanel.level(), 1);
+        assert_eq!(total_money(&game_state), total);
+        assert!(property(&game_state, HUGO_BOSS)
+            .mortgage(&game_state)
+            .is_err());
+
+        chanel.sell_agency(&game_state).unwrap();
+        assert_eq!(chanel.level(), 0);
+        assert_eq!(total_money(&game_state), total);
+    }
+
+    #[test]
+    fn bankruptcy_conserves_money() {
+        let (game_state, alice, bob) = game();
+        let chanel = property(&game_state, CHANEL);
+        chanel.buy(&alice, &game_state).unwrap();
+        game_state
+            .transfer(
+                Party::Player(bob.id),
+                Party::Bank,
+                bob.balance(),
+                TransferReason::Tax,
+            )
+            .unwrap();
+        let total = total_money(&game_state);
+
+        assert!(chanel.pay_rent(&bob, &game_state).is_err());
+        assert_eq!(total_money(&game_state), total);
+
+        game_state.go_bankrupt(&bob, Some(alice));
+        assert_eq!(total_money(&game_state), total);
+        assert!(bob.has_lost());
+        assert_eq!(bob.balance(), Money::new(0));
+    }
+
+    #[test]
+    fn snapshots_follow_the_ledger() {
+        let (game_state, alice, bob) = game();
+        let chanel = property(&game_state, CHANEL);
+        let before = game_state.snapshot();
+
+        chanel.buy(&alice, &game_state).unwrap();
+        chanel.pay_rent(&bob, &game_state).unwrap();
+        let after = game_state.snapshot();
+
+        assert!(after.follows(&before));
+        assert!(!before.follows(&after));
+    }
+}
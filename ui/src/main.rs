@@ -12,10 +12,14 @@ mod app;
 mod cell;
 mod components;
 mod game_data;
+mod game_log;
 mod game_state;
 mod hooks;
+mod i18n;
+mod ledger;
 mod pages;
 mod player;
+mod preview;
 mod server;
 mod utils;
 
@@ -6,22 +6,37 @@ use leptos::{
     html::Div,
     portal::Portal,
     prelude::*,
+    task::spawn_local,
 };
+use leptos_router::hooks::use_params_map;
+use shared::room::{RoomId, RoomRole};
 use tailwind_merge::tw;
 use web_sys::{HtmlDivElement, Node};
 
 use crate::{
-    cell::{
-        BuildAgencyError, Cell, OwnedPropertyError, Property, PropertyType, SellAgencyError,
-        CELLS_COUNT,
+    cell::{Cell, Money, Property, PropertyType, AGENCY_SUPPLY, CELLS_COUNT, TOP_LEVEL_SUPPLY},
+    components::{
+        chat::ChatPanel, countdown::CountdownTimer, dice::Dice, game_summary::GameSummary,
+        in_game_modal::InGameModal, property_stats::PropertyStats, standings::Standings,
+        transactions::TransactionHistory,
     },
-    components::{dice::Dice, in_game_modal::InGameModal},
     game_state::GameState,
-    hooks::window_scroll::use_window_scroll,
+    hooks::{
+        authenticated::use_authenticated, game_shortcuts::use_game_shortcuts,
+        redirect::use_redirect, window_size::use_window_size,
+    },
+    i18n::I18n,
     player::Player,
+    preview::roll_probability,
+    server::{RoomEvent, ServerConnection},
     utils::into_either_of::IntoEitherOf7,
 };
 
+/// Zoom of the board on small screens, where 1 fits the board into 640 pixels.
+const MIN_BOARD_ZOOM: f64 = 0.75;
+const MAX_BOARD_ZOOM: f64 = 2.;
+const BOARD_ZOOM_STEP: f64 = 0.25;
+
 #[derive(Debug, Clone, Copy)]
 struct CellsRefs(StoredValue<[NodeRef<Div>; CELLS_COUNT]>);
 
@@ -48,6 +63,11 @@ impl CellsRefs {
 #[derive(Debug, Clone, Copy)]
 struct GamePageRefs {
     pub cells: CellsRefs,
+    /// Tokens and popups are positioned relative to the board, so that they move along with it
+    /// when the page or the board's viewport scrolls.
+    pub board: NodeRef<Div>,
+    /// Zoom of the board on small screens, which resizes the cells without resizing the window.
+    pub board_zoom: RwSignal<f64>,
     pub chat: NodeRef<Div>,
     pub cell_popups: NodeRef<Div>,
 }
@@ -63,67 +83,290 @@ impl GamePageRefs {
 }
 
 #[component]
-pub fn GamePage() -> impl IntoView {
+pub fn GamePage(
+    /// Watch the game read-only, instead of taking a seat.
+    #[prop(optional)]
+    spectating: bool,
+) -> impl IntoView {
+    let params = use_params_map();
+    let room_id = Memo::new(move |_| {
+        params.with(|params| {
+            params
+                .get("room_id")
+                .and_then(|room_id| room_id.parse::<RoomId>().ok())
+        })
+    });
+
+    // The room is joined from scratch whenever the id changes, e.g. when following a rematch.
+    move || match room_id.get() {
+        Some(room_id) => view! { <GameRoom room_id spectating /> }.into_any(),
+        None => {
+            use_redirect("/lobby", Default::default());
+            ().into_any()
+        }
+    }
+}
+
+#[component]
+fn GameRoom(room_id: RoomId, spectating: bool) -> impl IntoView {
+    let connection = ServerConnection::use_context();
+    let i18n = I18n::use_context();
+    let role = if spectating {
+        RoomRole::Spectator
+    } else {
+        RoomRole::Player
+    };
+    let is_joined = RwSignal::new(false);
+    let error = RwSignal::new(None::<String>);
+    use_authenticated(move || {
+        spawn_local(async move {
+            match connection.join_room(room_id, role).await {
+                Ok(()) => is_joined.set(true),
+                Err(err) => error.set(Some(err.to_string())),
+            }
+        });
+    });
+
+    // Spectators of a room without players have nothing to look at, until someone takes a seat
+    // and publishes the game.
+    let has_players = move || connection.game().is_some() || !connection.seats().is_empty();
+
+    view! {
+        <Show
+            when=move || is_joined.get() && has_players()
+            fallback=move || {
+                view! {
+                    <div class="p-7">
+                        {move || match error.get() {
+                            Some(error) => error,
+                            None if is_joined.get() => i18n.t("room.waiting", &[]),
+                            None => i18n.t("room.joining", &[]),
+                        }}
+                    </div>
+                }
+            }
+        >
+            <GameBoard spectating />
+        </Show>
+    }
+}
+
+/// Board of the joined room. Only rendered once the room is joined, because the game is restored
+/// from the room's snapshot.
+#[component]
+fn GameBoard(spectating: bool) -> impl IntoView {
+    let connection = ServerConnection::use_context();
     let is_dice_shown = RwSignal::new(false);
-    let game_state = GameState::new();
+    let game_state = GameState::new(spectating);
     let refs = GamePageRefs {
         cells: CellsRefs::new(),
+        board: NodeRef::<Div>::new(),
+        board_zoom: RwSignal::new(1.),
         chat: NodeRef::<Div>::new(),
         cell_popups: NodeRef::<Div>::new(),
     };
+    // Phones show the players in a drawer, and may fold the chat to see the board behind it.
+    let is_players_open = RwSignal::new(false);
+    let is_chat_open = RwSignal::new(true);
+    let i18n = game_state.i18n;
 
     game_state.provide_context();
     refs.provide_context();
 
+    if game_state.is_spectating() {
+        Effect::new(move |_| {
+            if let Some(snapshot) = connection.game() {
+                game_state.apply_snapshot(&snapshot);
+            }
+        });
+        Effect::new(move |_| game_state.log.replace(connection.game_log().get()));
+        // The snapshots already include whatever the announced events changed.
+        Effect::new(move |_| {
+            connection.take_room_events();
+        });
+    } else {
+        game_state.start();
+
+        // Snapshot of the room was restored already, only the ones published afterwards are new.
+        Effect::new(move |previous_run: Option<()>| {
+            let snapshot = connection.game();
+            if let Some(snapshot) = snapshot.filter(|_| previous_run.is_some()) {
+                untrack(|| game_state.follow(&snapshot));
+            }
+        });
+
+        // Surrenders, draws, expired countdowns and bids change the game once the server has
+        // validated and announced them, in the order they were announced.
+        Effect::new(move |_| {
+            for event in connection.take_room_events() {
+                untrack(|| match event {
+                    RoomEvent::Surrendered(player_id) => game_state.player_surrendered(player_id),
+                    RoomEvent::DrawVoted(vote) => game_state.draw_voted(&vote),
+                    RoomEvent::CountdownExpired(expired) => game_state.countdown_expired(expired),
+                    RoomEvent::BidRequested(request) => game_state.bid_requested(request),
+                    RoomEvent::BidPlaced { bidder, bid } => game_state.bid_placed(bidder, bid),
+                });
+            }
+        });
+
+        // Everyone else follows the game through the snapshots published after every change of
+        // it, by the client of the player whose turn it is. The snapshot that passes the turn on
+        // is still published by the player who passes it.
+        let was_own_turn = StoredValue::new(false);
+        Effect::new(move |_| {
+            let mut snapshot = game_state.snapshot();
+            let is_own_turn = game_state.is_own_turn();
+            game_state.log.entries().track();
+            if !is_own_turn && !was_own_turn.get_value() {
+                return;
+            }
+            was_own_turn.set_value(is_own_turn);
+            // Requests may overtake each other, the server keeps the latest revision only.
+            snapshot.revision = game_state.next_revision();
+
+            let events = untrack(|| game_state.take_unpublished_events());
+            spawn_local(async move {
+                if let Err(error) = connection.update_game(snapshot, events).await {
+                    tracing::warn!("Unable to publish the game: {}", error);
+                }
+            });
+        });
+    }
+
     Effect::new(move |_| is_dice_shown.set(game_state.rolled_dice().is_some()));
+    use_game_shortcuts(game_state);
 
     view! {
-        <div class="grid gap-9 p-7 h-screen grid-cols-[200px_auto] grid-rows-[repeat(5,1fr)] grow">
-            {move || {
-                game_state
-                    .get_players()
-                    .into_values()
-                    .map(|player| view! { <PlayerCard player class="col-[1]".to_owned() /> })
-                    .collect_view()
-            }}
-            <div class="grid relative gap-0.5 min-h-full max-w-[50vw] grid-columns-[2fr_repeat(9,21fr)_2fr] grid-rows-[2fr_repeat(9,1fr)_2fr] col-[2] row-[1/6]">
-                <Rows />
-                <Chat node_ref=refs.chat class="col-[2/11] row-[2/11]".to_owned() />
+        <div class="flex flex-col gap-3 p-2 min-h-screen lg:grid lg:gap-9 lg:p-7 lg:h-screen lg:grid-cols-[200px_auto_240px] lg:grid-rows-[repeat(5,1fr)] grow">
+            <div class="flex gap-2 items-center text-sm lg:hidden">
+                <button
+                    class="py-1 px-2 rounded border"
+                    aria-expanded=move || is_players_open.get().to_string()
+                    on:click=move |_| is_players_open.update(|is_open| *is_open = !*is_open)
+                >
+                    {move || i18n.t("board.players", &[])}
+                </button>
+                <button
+                    class="py-1 px-2 rounded border"
+                    aria-expanded=move || is_chat_open.get().to_string()
+                    on:click=move |_| is_chat_open.update(|is_open| *is_open = !*is_open)
+                >
+                    {move || i18n.t("board.chat", &[])}
+                </button>
+                <div class="flex gap-1 items-center ml-auto" role="group" aria-label=move || i18n.t("board.zoom", &[])>
+                    <button
+                        class="px-2 rounded border"
+                        aria-label=move || i18n.t("board.zoom_out", &[])
+                        disabled=move || refs.board_zoom.get() <= MIN_BOARD_ZOOM
+                        on:click=move |_| {
+                            refs.board_zoom.update(|zoom| *zoom = (*zoom - BOARD_ZOOM_STEP).max(MIN_BOARD_ZOOM))
+                        }
+                    >
+                        "−"
+                    </button>
+                    <span class="w-10 text-center tabular-nums">
+                        {move || format!("{:.0}%", refs.board_zoom.get() * 100.)}
+                    </span>
+                    <button
+                        class="px-2 rounded border"
+                        aria-label=move || i18n.t("board.zoom_in", &[])
+                        disabled=move || refs.board_zoom.get() >= MAX_BOARD_ZOOM
+                        on:click=move |_| {
+                            refs.board_zoom.update(|zoom| *zoom = (*zoom + BOARD_ZOOM_STEP).min(MAX_BOARD_ZOOM))
+                        }
+                    >
+                        "+"
+                    </button>
+                </div>
+            </div>
+            <div
+                class=move || {
+                    tw!(
+                        "flex overflow-y-auto fixed inset-y-0 left-0 z-30 flex-col gap-3 p-3 w-56 bg-gray-800 transition-transform lg:contents",
+                        !is_players_open.get() => "-translate-x-full"
+                    )
+                }
+                role="region"
+                aria-label=move || i18n.t("board.players", &[])
+            >
                 {move || {
                     game_state
-                        .rolled_dice()
-                        .filter(|_| is_dice_shown.get())
-                        .map(|(a, b)| {
-                            view! {
-                                <div class="flex absolute top-1/2 left-1/2 gap-4 -translate-x-1/2 -translate-y-1/2">
-                                    <Dice
-                                        side=a
-                                        animated=true
-                                        on_animation_end=move || {
-                                            set_timeout(
-                                                move || {
-                                                    is_dice_shown.set(false);
-                                                    game_state.dice_transition_end();
-                                                },
-                                                Duration::from_millis(200),
-                                            );
-                                        }
-                                    />
-                                    <Dice side=b animated=true />
-                                </div>
-                            }
-                        })
+                        .get_players()
+                        .into_values()
+                        .map(|player| view! { <PlayerCard player class="col-[1]".to_owned() /> })
+                        .collect_view()
                 }}
             </div>
-            {move || {
-                game_state
-                    .get_players()
-                    .into_values()
-                    .map(|player| view! { <PlayerToken player /> })
-                    .collect_view()
-            }}
+            <Show when=move || is_players_open.get()>
+                <div
+                    class="fixed inset-0 z-20 lg:hidden bg-black/50"
+                    on:click=move |_| is_players_open.set(false)
+                />
+            </Show>
+            // On phones the board keeps its size, and the viewport scrolls around it instead.
+            <div class="overflow-auto lg:overflow-visible col-[2] row-[1/6]">
+                <div
+                    node_ref=refs.board
+                    class="grid relative gap-0.5 aspect-square min-w-[calc(var(--board-zoom)*640px)] lg:aspect-auto lg:min-h-full lg:min-w-0 lg:max-w-[50vw] grid-columns-[2fr_repeat(9,21fr)_2fr] grid-rows-[2fr_repeat(9,1fr)_2fr]"
+                    style=("--board-zoom", move || refs.board_zoom.get().to_string())
+                    role="region"
+                    aria-label=move || i18n.t("board.title", &[])
+                >
+                    <Rows />
+                    <Chat
+                        node_ref=refs.chat
+                        is_chat_open
+                        class="col-[2/11] row-[2/11]".to_owned()
+                    />
+                    {move || {
+                        game_state
+                            .rolled_dice()
+                            .filter(|_| is_dice_shown.get())
+                            .map(|(a, b)| {
+                                view! {
+                                    <div
+                                        class="flex absolute top-1/2 left-1/2 gap-4 -translate-x-1/2 -translate-y-1/2"
+                                        role="img"
+                                        aria-label=move || {
+                                            i18n.t("board.rolled", &[("a", &a), ("b", &b)])
+                                        }
+                                    >
+                                        <Dice
+                                            side=a
+                                            animated=true
+                                            on_animation_end=move || {
+                                                set_timeout(
+                                                    move || {
+                                                        is_dice_shown.set(false);
+                                                        game_state.dice_transition_end();
+                                                    },
+                                                    Duration::from_millis(200),
+                                                );
+                                            }
+                                        />
+                                        <Dice side=b animated=true />
+                                    </div>
+                                }
+                            })
+                    }}
+                    {move || {
+                        game_state
+                            .get_players()
+                            .into_values()
+                            .map(|player| view! { <PlayerToken player /> })
+                            .collect_view()
+                    }}
+                    <div node_ref=refs.cell_popups />
+                </div>
+            </div>
+            <div class="flex flex-col gap-3 col-[3] row-[1/6]">
+                <Standings />
+                <DrawOffer />
+                <BankSupply />
+                <TransactionHistory class="flex-1" />
+            </div>
         </div>
-        <div node_ref=refs.cell_popups />
+        <GameAnnouncer />
         <GameFinished />
     }
 }
@@ -194,6 +437,7 @@ fn Rows() -> impl IntoView {
 fn Cell(index: usize, node_ref: NodeRef<Div>) -> impl IntoView {
     let game_page_refs = GamePageRefs::use_context();
     let game_state = GameState::use_context();
+    let i18n = game_state.i18n;
     let is_info_open = RwSignal::new(false);
     let current_cell = game_state.get_cell(index);
     let is_property = current_cell.try_unwrap_property().is_ok();
@@ -234,6 +478,47 @@ fn Cell(index: usize, node_ref: NodeRef<Div>) -> impl IntoView {
     //     </>
     // }
 
+    // Cells the current player can reach with the roll they are about to make.
+    let preview = move || {
+        let player = game_state.current_player();
+        if game_state.rolled_dice().is_some() || player.has_lost() {
+            return None;
+        }
+        let dice_total = (index + CELLS_COUNT - player.position()) % CELLS_COUNT;
+        (2..=12).contains(&dice_total).then(|| {
+            (
+                roll_probability(dice_total),
+                game_state.preview_roll(&player, dice_total),
+                player.balance(),
+            )
+        })
+    };
+
+    // Arrows move the focus around the board clockwise, Enter or Space open the property.
+    let on_keydown = move |event: ev::KeyboardEvent| {
+        let next = match event.key().as_str() {
+            "ArrowRight" | "ArrowDown" => (index + 1) % CELLS_COUNT,
+            "ArrowLeft" | "ArrowUp" => (index + CELLS_COUNT - 1) % CELLS_COUNT,
+            "Home" => 0,
+            "End" => CELLS_COUNT - 1,
+            "Enter" | " " if is_property => {
+                event.prevent_default();
+                is_info_open.update(|x| *x = !*x);
+                return;
+            }
+            "Escape" if is_info_open.get_untracked() => {
+                event.stop_propagation();
+                is_info_open.set(false);
+                return;
+            }
+            _ => return,
+        };
+        event.prevent_default();
+        if let Err(error) = game_page_refs.cells.get(next).focus() {
+            tracing::warn!("Unable to focus cell {}: {:?}", next, error);
+        }
+    };
+
     Effect::new(move |_| {
         if is_info_open.get() && game_page_refs.cell_popups.get().is_none() {
             tracing::warn!(
@@ -247,25 +532,33 @@ fn Cell(index: usize, node_ref: NodeRef<Div>) -> impl IntoView {
             node_ref=node_ref
             class=move || {
                 tw!(
-                    "relative p-1",
+                    "relative p-1 focus-visible:outline focus-visible:outline-2 focus-visible:outline-sky-400 focus-visible:z-10",
                     is_property => "cursor-pointer",
                     cell_bg() == "#fff" => "text-black",
                 )
             }
             style:background=cell_bg
+            role=if is_property { "button" } else { "group" }
+            tabindex="0"
+            aria-label=move || cell_label(&game_state, index)
+            aria-haspopup=is_property.then_some("dialog")
+            aria-expanded=move || is_property.then(|| is_info_open.get().to_string())
             on:click=move |_| is_info_open.update(|x| *x = !*x)
+            on:keydown=on_keydown
         >
             {match current_cell {
-                Cell::Start => "Start".into_either_of_7a(),
-                Cell::Jail => "Jail".into_either_of_7b(),
-                Cell::FreeParking => "FreeParking".into_either_of_7c(),
-                Cell::GoToJail => "GoToJail".into_either_of_7d(),
-                Cell::Tax(tax) => format!("Tax: {}", tax).into_either_of_7e(),
-                Cell::Chance => "Chance".into_either_of_7f(),
+                Cell::Start => (move || i18n.t("cell.start", &[])).into_either_of_7a(),
+                Cell::Jail => (move || i18n.t("cell.jail", &[])).into_either_of_7b(),
+                Cell::FreeParking => (move || i18n.t("cell.free_parking", &[])).into_either_of_7c(),
+                Cell::GoToJail => (move || i18n.t("cell.go_to_jail", &[])).into_either_of_7d(),
+                Cell::Tax(tax) => {
+                    (move || i18n.t("cell.tax", &[("amount", &i18n.money(tax))])).into_either_of_7e()
+                }
+                Cell::Chance => (move || i18n.t("cell.chance", &[])).into_either_of_7f(),
                 Cell::Property(prop) => {
                     view! {
                         <>
-                            {format!("Property: {}", prop.data.title)}
+                            {move || i18n.t("cell.property", &[("title", &prop.data.title)])}
                             <div
                                 style:background=rent_bg
                                 class=tw!(
@@ -299,6 +592,30 @@ fn Cell(index: usize, node_ref: NodeRef<Div>) -> impl IntoView {
                         .into_either_of_7g()
                 }
             }}
+            {move || {
+                preview()
+                    .map(|(probability, preview, balance)| {
+                        let change = preview.balance_change();
+                        let mut description = i18n.landing_effect(&preview.effect);
+                        if preview.start_bonus.is_positive() {
+                            description += &i18n
+                                .t("preview.start_bonus", &[("reward", &i18n.money(preview.start_bonus))]);
+                        }
+                        view! {
+                            <div
+                                class="absolute inset-0 pointer-events-none"
+                                style:background=danger_color(change, balance)
+                            >
+                                <div
+                                    class="absolute right-0.5 bottom-0.5 px-1 text-xs text-white rounded pointer-events-auto bg-black/60"
+                                    title=description
+                                >
+                                    {format!("{:.0}%", probability * 100.)}
+                                </div>
+                            </div>
+                        }
+                    })
+            }}
             {move || {
                 current_cell
                     .try_unwrap_property()
@@ -316,6 +633,74 @@ fn Cell(index: usize, node_ref: NodeRef<Div>) -> impl IntoView {
     }
 }
 
+/// Description of the cell for screen readers: what it is, who owns it and who stands on it.
+fn cell_label(game_state: &GameState, index: usize) -> String {
+    let i18n = game_state.i18n;
+    let mut label = match game_state.get_cell(index) {
+        Cell::Start => i18n.t("cell.start", &[]),
+        Cell::Jail => i18n.t("cell.jail", &[]),
+        Cell::FreeParking => i18n.t("cell.free_parking", &[]),
+        Cell::GoToJail => i18n.t("cell.go_to_jail", &[]),
+        Cell::Tax(tax) => i18n.t("cell.tax", &[("amount", &format!("{}k", i18n.money(tax)))]),
+        Cell::Chance => i18n.t("cell.chance", &[]),
+        Cell::Property(property) => {
+            let mut label = format!(
+                "{}, {}",
+                property.data.title,
+                i18n.group_title(&property.data.group)
+            );
+            match property.owner() {
+                Some(owner) => {
+                    label += &i18n.t("cell.owned_by", &[("owner", &owner.name.get_value())]);
+                    if property.mortgaged_for().is_some() {
+                        label += &i18n.t("cell.mortgaged", &[]);
+                    }
+                }
+                None => {
+                    label += &i18n.t(
+                        "cell.for_sale",
+                        &[("price", &i18n.money(property.data.price))],
+                    )
+                }
+            }
+            label
+        }
+    };
+
+    let players = game_state.get_players_by_cell(index);
+    if !players.is_empty() {
+        let names = players
+            .iter()
+            .map(|player| player.name.get_value())
+            .collect::<Vec<_>>();
+        let names = names.join(&i18n.t("cell.and", &[]));
+        label += &i18n.t("cell.players_here", &[("players", &names)]);
+    }
+    label
+}
+
+/// Keeps the center of the popup within the bounds, or halfway between them, when the popup is
+/// bigger than the space it has. `f64::clamp` would panic then.
+fn clamp_or_center(value: f64, min: f64, max: f64) -> f64 {
+    if min > max {
+        (min + max) / 2.
+    } else {
+        value.clamp(min, max)
+    }
+}
+
+/// Red for losing money, more intense the bigger part of the balance it is, or green for getting it.
+fn danger_color(change: Money, balance: Money) -> String {
+    if change.is_positive() {
+        return "rgb(34 197 94 / 0.3)".to_owned();
+    }
+    if change.is_negative() {
+        let share = -(i64::from(change) as f64) / i64::from(balance).max(1) as f64;
+        return format!("rgb(239 68 68 / {:.2})", 0.2 + 0.6 * share.min(1.));
+    }
+    "rgb(255 255 255 / 0.15)".to_owned()
+}
+
 #[component]
 pub fn PropertyInfo(
     cell_node_ref: NodeRef<Div>,
@@ -323,9 +708,10 @@ pub fn PropertyInfo(
     property: Property,
 ) -> impl IntoView {
     let game_state = GameState::use_context();
+    let i18n = game_state.i18n;
     let game_page_refs = GamePageRefs::use_context();
     let node_ref = NodeRef::<Div>::new();
-    let (scroll_x, scroll_y) = use_window_scroll();
+    let (window_width, window_height) = use_window_size();
     let coordinates = RwSignal::new(None::<(f64, f64)>);
 
     let handle = window_event_listener(ev::click, move |event| {
@@ -345,8 +731,12 @@ pub fn PropertyInfo(
     });
 
     Effect::new(move |_| {
-        let Some(((cell_node_ref, chat_ref), node_ref)) = cell_node_ref
+        window_width.track();
+        window_height.track();
+        game_page_refs.board_zoom.track();
+        let Some((((cell_node_ref, board_ref), chat_ref), node_ref)) = cell_node_ref
             .get()
+            .zip(game_page_refs.board.get())
             .zip(game_page_refs.chat.get())
             .zip(node_ref.get())
         else {
@@ -354,21 +744,25 @@ pub fn PropertyInfo(
         };
 
         let cell_rect = cell_node_ref.get_bounding_client_rect();
+        let board_rect = board_ref.get_bounding_client_rect();
         let chat_rect = chat_ref.get_bounding_client_rect();
         let node_rect = node_ref.get_bounding_client_rect();
 
-        let cell_x_center = scroll_x() + cell_rect.x() + cell_rect.width() / 2f64;
-        let cell_y_center = scroll_y() + cell_rect.y() + cell_rect.height() / 2f64;
+        let (board_x, board_y) = (board_rect.x(), board_rect.y());
+        let cell_x_center = cell_rect.x() - board_x + cell_rect.width() / 2f64;
+        let cell_y_center = cell_rect.y() - board_y + cell_rect.height() / 2f64;
         let padding = 8.;
 
         coordinates.set(Some((
-            cell_x_center.clamp(
-                scroll_x() + chat_rect.left() + (node_rect.width() / 2.) + padding,
-                scroll_x() + chat_rect.right() - (node_rect.width() / 2.) - padding,
+            clamp_or_center(
+                cell_x_center,
+                chat_rect.left() - board_x + (node_rect.width() / 2.) + padding,
+                chat_rect.right() - board_x - (node_rect.width() / 2.) - padding,
             ),
-            cell_y_center.clamp(
-                scroll_y() + chat_rect.top() + (node_rect.height() / 2.) + padding,
-                scroll_y() + chat_rect.bottom() - (node_rect.height() / 2.) - padding,
+            clamp_or_center(
+                cell_y_center,
+                chat_rect.top() - board_y + (node_rect.height() / 2.) + padding,
+                chat_rect.bottom() - board_y - (node_rect.height() / 2.) - padding,
             ),
         )));
     });
@@ -380,6 +774,17 @@ pub fn PropertyInfo(
             <div
                 node_ref=node_ref
                 class="overflow-hidden absolute z-10 w-52 rounded-md -translate-x-1/2 -translate-y-1/2"
+                role="dialog"
+                aria-label=property.data.title
+                on:keydown=move |event| {
+                    if event.key() == "Escape" {
+                        event.stop_propagation();
+                        is_info_open.set(false);
+                        if let Some(cell) = cell_node_ref.get_untracked() {
+                            let _ = cell.focus();
+                        }
+                    }
+                }
                 style:left=move || coordinates().map(|(x, _)| format!("{x}px")).unwrap_or_default()
                 style:top=move || coordinates().map(|(_, y)| format!("{y}px")).unwrap_or_default()
             >
@@ -389,36 +794,39 @@ pub fn PropertyInfo(
                 <div class="py-3 px-4 text-white" style:background=property.data.group.color>
                     <div class="text-lg font-bold">{property.data.title}</div>
                     <div class="-mt-0.5 text-xs font-bold opacity-75">
-                        {property.data.group.title}
+                        {move || i18n.group_title(&property.data.group)}
                     </div>
                 </div>
                 <div class="flex flex-col gap-2.5 py-3 px-4 text-gray-500 bg-white">
                     <div class="leading-[14px]">
-                        {move || match property.ty {
-                            PropertyType::Simple { .. } => "Build agencies to make rent higher.",
-                            PropertyType::Transport { .. } => {
-                                "Rent depends on amount of autos you have."
-                            }
-                            PropertyType::Utility { .. } => {
-                                "Rent depends on the dice sum and amount of developers you have."
-                            }
+                        {move || {
+                            let key = match property.ty {
+                                PropertyType::Simple { .. } => "property.simple",
+                                PropertyType::Transport { .. } => "property.transport",
+                                PropertyType::Utility { .. } => "property.utility",
+                            };
+                            i18n.t(key, &[])
                         }}
                     </div>
                     <Show
                         when=move || {
-                            game_state.self_player == game_state.current_player()
-                                && property.owner() == Some(game_state.self_player)
+                            game_state
+                                .self_player
+                                .is_some_and(|self_player| {
+                                    self_player == game_state.current_player()
+                                        && property.owner() == Some(self_player)
+                                })
                         }
                         fallback=move || {
-                            property
-                                .owner()
-                                .is_none()
-                                .then(|| {
+                            game_state
+                                .self_player
+                                .filter(|_| property.owner().is_none() && game_state.is_own_turn())
+                                .map(|self_player| {
                                     view! {
                                         <button
                                             class="p-2 rounded border-2"
                                             on:click=move |_| {
-                                                let _ = property.buy(&game_state.self_player);
+                                                let _ = property.buy(&self_player, &game_state);
                                             }
                                         >
                                             "[Debug] Buy"
@@ -435,21 +843,12 @@ pub fn PropertyInfo(
                                             <button
                                                 class="p-2 rounded border-2"
                                                 on:click=move |_| {
-                                                    if let Err(error) = property.recover() {
-                                                        match error {
-                                                            OwnedPropertyError::NoOwner { .. } => {
-                                                                unreachable!(
-                                                                    "Property must have owner - we checked it in the Show above",
-                                                                );
-                                                            }
-                                                            OwnedPropertyError::NotEnoughMoney { .. } => {
-                                                                todo!("Show modal");
-                                                            }
-                                                        }
+                                                    if let Err(error) = property.recover(&game_state) {
+                                                        tracing::warn!("Unable to recover property: {}", error);
                                                     }
                                                 }
                                             >
-                                                "Recover"
+                                                {move || i18n.t("button.recover", &[])}
                                             </button>
                                         }
                                             .into_any()
@@ -458,137 +857,89 @@ pub fn PropertyInfo(
                                             <button
                                                 class="p-2 rounded border-2"
                                                 on:click=move |_| {
-                                                    property
-                                                        .mortgage()
-                                                        .expect(
-                                                            "Property must have owner - we checked it in the Show above",
-                                                        )
+                                                    if let Err(error) = property.mortgage(&game_state) {
+                                                        tracing::warn!("Unable to mortgage property: {}", error);
+                                                    }
                                                 }
                                             >
-                                                "Mortgage"
+                                                {move || i18n.t("button.mortgage", &[])}
                                             </button>
                                         }
                                             .into_any()
                                     }
                                 }
-                                PropertyType::Simple { levels, level, .. } => {
+                                PropertyType::Simple { .. } => {
                                     if property.mortgaged_for().is_some() {
                                         view! {
                                             <button
                                                 class="p-2 rounded border-2"
                                                 on:click=move |_| {
-                                                    if let Err(error) = property.recover() {
-                                                        match error {
-                                                            OwnedPropertyError::NoOwner { .. } => {
-                                                                unreachable!(
-                                                                    "Property must have owner - we checked it in the Show above",
-                                                                );
-                                                            }
-                                                            OwnedPropertyError::NotEnoughMoney { .. } => {
-                                                                todo!("Show modal");
-                                                            }
-                                                        }
+                                                    if let Err(error) = property.recover(&game_state) {
+                                                        tracing::warn!("Unable to recover property: {}", error);
                                                     }
                                                 }
                                             >
-                                                "Recover"
+                                                {move || i18n.t("button.recover", &[])}
                                             </button>
                                         }
                                             .into_any()
                                     } else {
                                         (move || {
-                                            let has_monopoly = game_state
-                                                .has_monopoly_on(
-                                                    &game_state.self_player,
-                                                    &property.data.group,
-                                                );
-                                            let other_props = game_state
+                                            let group_has_agencies = game_state
                                                 .get_properties_by_group(&property.data.group)
-                                                .into_iter()
-                                                .filter(|prop| {
-                                                    prop.owner()
-                                                        .is_some_and(|owner| owner == game_state.self_player)
-                                                })
-                                                .filter_map(|prop| {
-                                                    if let PropertyType::Simple { level, .. } = prop.ty {
-                                                        Some((level, prop))
-                                                    } else {
-                                                        None
-                                                    }
-                                                })
-                                                .collect::<Vec<_>>();
+                                                .iter()
+                                                .any(|prop| prop.level() > 0);
                                             view! {
                                                 <>
-                                                    <Show when={
-                                                        let other_props = other_props.clone();
-                                                        move || {
-                                                            has_monopoly && level() < levels.len() - 1
-                                                                && !other_props
-                                                                    .iter()
-                                                                    .any(|(x, p)| x() < level() || p.mortgaged_for().is_some())
-                                                                && !game_state
-                                                                    .get_properties_by_group(&property.data.group)
-                                                                    .into_iter()
-                                                                    .any(|prop| prop.is_agency_built())
-                                                        }
+                                                    <Show when=move || {
+                                                        property.check_build_agency(&game_state).is_ok()
                                                     }>
                                                         <button
                                                             class="p-2 rounded border-2"
                                                             on:click=move |_| {
-                                                                if let Err(error) = property.build_agency(&game_state) {
-                                                                    match error {
-                                                                        BuildAgencyError::NoOwner { .. } => {
-                                                                            unreachable!(
-                                                                                "Property must have owner - we checked it in the Show above",
-                                                                            );
-                                                                        }
-                                                                        BuildAgencyError::NotEnoughMoney { .. } => {
-                                                                            todo!("Show modal")
+                                                                game_state
+                                                                    .spawn_local_abortable(async move {
+                                                                        if let Err(error) = game_state.build_agency(property).await {
+                                                                            tracing::warn!("Unable to build agency: {}", error);
                                                                         }
-                                                                        BuildAgencyError::AlreadyBuilt { .. } => todo!("Show modal"),
-                                                                    }
-                                                                }
+                                                                    });
                                                             }
                                                         >
-                                                            "Build agency"
+                                                            {move || i18n.t("button.build_agency", &[])}
                                                         </button>
                                                     </Show>
 
-                                                    {if has_monopoly && level() > 0
-                                                        && !other_props.iter().any(|(x, _)| x() > level())
-                                                    {
+                                                    {if property.check_sell_agency(&game_state).is_ok() {
                                                         view! {
                                                             <button
                                                                 class="p-2 rounded border-2"
+                                                                title=move || {
+                                                                    i18n.t(
+                                                                        "property.agency_sell_price",
+                                                                        &[("price", &i18n.money(property.agency_sell_price(&game_state)))],
+                                                                    )
+                                                                }
                                                                 on:click=move |_| {
-                                                                    if let Err(error) = property.sell_agency() {
-                                                                        match error {
-                                                                            SellAgencyError::NoOwner { .. } => {
-                                                                                unreachable!(
-                                                                                    "Property must have owner - we checked it in the Show above",
-                                                                                );
-                                                                            }
-                                                                        }
+                                                                    if let Err(error) = property.sell_agency(&game_state) {
+                                                                        tracing::warn!("Unable to sell agency: {}", error);
                                                                     }
                                                                 }
                                                             >
-                                                                "Sell agency"
+                                                                {move || i18n.t("button.sell_agency", &[])}
                                                             </button>
                                                         }
                                                             .into_any()
-                                                    } else if other_props.iter().all(|(x, _)| x() == 0) {
+                                                    } else if !group_has_agencies {
                                                         view! {
                                                             <button
                                                                 class="p-2 rounded border-2"
                                                                 on:click=move |_| {
-                                                                    property
-                                                                        .mortgage()
-                                                                        .expect(
-                                                                            "Property must have owner - we checked it in the Show above",
-                                                                        );
+                                                                    if let Err(error) = property.mortgage(&game_state) {
+                                                                        tracing::warn!("Unable to mortgage property: {}", error);
+                                                                    }
                                                                 }
                                                             >
-                                                                "Mortgage"
+                                                                {move || i18n.t("button.mortgage", &[])}
                                                             </button>
                                                         }
                                                             .into_any()
@@ -611,44 +962,44 @@ pub fn PropertyInfo(
                                     view! {
                                         <>
                                             <div class="flex justify-between">
-                                                <span>"Base rent"</span>
+                                                <span>{move || i18n.t("property.base_rent", &[])}</span>
                                                 <span>
-                                                    {move || levels[0].to_string()}
+                                                    {move || i18n.money(levels[0])}
                                                     <span class="opacity-70">"k"</span>
                                                 </span>
                                             </div>
                                             <div class="flex justify-between">
                                                 <span>"★"</span>
                                                 <span>
-                                                    {move || levels[1].to_string()}
+                                                    {move || i18n.money(levels[1])}
                                                     <span class="opacity-70">"k"</span>
                                                 </span>
                                             </div>
                                             <div class="flex justify-between">
                                                 <span>"★ ★"</span>
                                                 <span>
-                                                    {move || levels[2].to_string()}
+                                                    {move || i18n.money(levels[2])}
                                                     <span class="opacity-70">"k"</span>
                                                 </span>
                                             </div>
                                             <div class="flex justify-between">
                                                 <span>"★ ★ ★"</span>
                                                 <span>
-                                                    {move || levels[3].to_string()}
+                                                    {move || i18n.money(levels[3])}
                                                     <span class="opacity-70">"k"</span>
                                                 </span>
                                             </div>
                                             <div class="flex justify-between">
                                                 <span>"★ ★ ★ ★"</span>
                                                 <span>
-                                                    {move || levels[4].to_string()}
+                                                    {move || i18n.money(levels[4])}
                                                     <span class="opacity-70">"k"</span>
                                                 </span>
                                             </div>
                                             <div class="flex justify-between">
                                                 <span class="text-yellow-500 scale-150">"★"</span>
                                                 <span>
-                                                    {move || levels[5].to_string()}
+                                                    {move || i18n.money(levels[5])}
                                                     <span class="opacity-70">"k"</span>
                                                 </span>
                                             </div>
@@ -661,30 +1012,30 @@ pub fn PropertyInfo(
                                     view! {
                                         <>
                                             <div class="flex justify-between">
-                                                <span>"1 field"</span>
+                                                <span>{move || i18n.t("property.one_field", &[])}</span>
                                                 <span>
-                                                    {move || levels[0].to_string()}
+                                                    {move || i18n.money(levels[0])}
                                                     <span class="opacity-70">"k"</span>
                                                 </span>
                                             </div>
                                             <div class="flex justify-between">
-                                                <span>"2 fields"</span>
+                                                <span>{move || i18n.t("property.fields", &[("count", &2)])}</span>
                                                 <span>
-                                                    {move || levels[1].to_string()}
+                                                    {move || i18n.money(levels[1])}
                                                     <span class="opacity-70">"k"</span>
                                                 </span>
                                             </div>
                                             <div class="flex justify-between">
-                                                <span>"3 fields"</span>
+                                                <span>{move || i18n.t("property.fields", &[("count", &3)])}</span>
                                                 <span>
-                                                    {move || levels[2].to_string()}
+                                                    {move || i18n.money(levels[2])}
                                                     <span class="opacity-70">"k"</span>
                                                 </span>
                                             </div>
                                             <div class="flex justify-between">
-                                                <span>"4 fields"</span>
+                                                <span>{move || i18n.t("property.fields", &[("count", &4)])}</span>
                                                 <span>
-                                                    {move || levels[3].to_string()}
+                                                    {move || i18n.money(levels[3])}
                                                     <span class="opacity-70">"k"</span>
                                                 </span>
                                             </div>
@@ -697,14 +1048,14 @@ pub fn PropertyInfo(
                                     view! {
                                         <>
                                             <div class="flex justify-between">
-                                                <span>"1 field"</span>
+                                                <span>{move || i18n.t("property.one_field", &[])}</span>
                                                 <span>
                                                     <span class="opacity-70">"x"</span>
                                                     {move || levels[0].to_string()}
                                                 </span>
                                             </div>
                                             <div class="flex justify-between">
-                                                <span>"2 fields"</span>
+                                                <span>{move || i18n.t("property.fields", &[("count", &2)])}</span>
                                                 <span>
                                                     <span class="opacity-70">"x"</span>
                                                     {move || levels[1].to_string()}
@@ -718,34 +1069,50 @@ pub fn PropertyInfo(
                     </div>
                     <div>
                         <div class="flex justify-between">
-                            <span>"Field's price"</span>
+                            <span>{move || i18n.t("property.price", &[])}</span>
                             <span>
-                                {move || property.data.price.to_string()}
+                                {move || i18n.money(property.data.price)}
                                 <span class="pl-0.5 opacity-70">"k"</span>
                             </span>
                         </div>
                         <div class="flex justify-between">
-                            <span>"Field's mortgage"</span>
+                            <span>{move || i18n.t("property.mortgage", &[])}</span>
                             <span>
-                                {move || property.reward_for_mortgaging().to_string()}
+                                {move || i18n.money(property.reward_for_mortgaging())}
                                 <span class="pl-0.5 opacity-70">"k"</span>
                             </span>
                         </div>
                         <div class="flex justify-between">
-                            <span>"Field's recovery"</span>
+                            <span>{move || i18n.t("property.recovery", &[])}</span>
                             <span>
-                                {move || property.recovery_price().to_string()}
+                                {move || i18n.money(property.recovery_price(&game_state))}
                                 <span class="pl-0.5 opacity-70">"k"</span>
                             </span>
                         </div>
+                        {move || {
+                            property
+                                .mortgaged_for()
+                                .map(|rounds_left| {
+                                    let is_expiring = rounds_left
+                                        <= game_state.mortgage_terms().warning_rounds;
+                                    view! {
+                                        <div class=tw!(
+                                            "flex justify-between", is_expiring => "font-bold text-red-500"
+                                        )>
+                                            <span>{move || i18n.t("property.forfeited_in", &[])}</span>
+                                            <span>{move || i18n.t("property.rounds", &[("count", &rounds_left)])}</span>
+                                        </div>
+                                    }
+                                })
+                        }}
                         {move || {
                             if let PropertyType::Simple { level_price, .. } = property.ty {
                                 Either::Left(
                                     view! {
                                         <div class="flex justify-between">
-                                            <span>"Agency price"</span>
+                                            <span>{move || i18n.t("property.agency_price", &[])}</span>
                                             <span>
-                                                {move || level_price.to_string()}
+                                                {move || i18n.money(level_price)}
                                                 <span class="pl-0.5 opacity-70">"k"</span>
                                             </span>
                                         </div>
@@ -756,6 +1123,7 @@ pub fn PropertyInfo(
                             }
                         }}
                     </div>
+                    <PropertyStats property=property/>
                 </div>
             </div>
         </Show>
@@ -765,21 +1133,17 @@ pub fn PropertyInfo(
 #[component]
 pub fn PlayerCard(player: Player, #[prop(into, optional)] class: Signal<String>) -> impl IntoView {
     let game_state = GameState::use_context();
+    let connection = ServerConnection::use_context();
+    let i18n = game_state.i18n;
 
     let is_current_player = move || game_state.current_player() == player;
-    let is_self_player = game_state.self_player == player;
+    let is_self_player = game_state.self_player == Some(player);
     let bg = move || {
         is_current_player()
             .then(|| player.color.get_player_gradient())
             .unwrap_or_default()
     };
 
-    let surrender = move |_| {
-        if is_self_player {
-            game_state.surrender_player(&player);
-        }
-    };
-
     view! {
         <div
             style:background=bg
@@ -787,11 +1151,12 @@ pub fn PlayerCard(player: Player, #[prop(into, optional)] class: Signal<String>)
                 tw!(
                     "flex flex-col items-center justify-center p-3 bg-gray-900 transition-all",
                     is_current_player() => "scale-105",
-                    is_self_player => "cursor-pointer",
                     class()
                 )
             }
-            on:click=surrender
+            role="group"
+            aria-label=player.name.get_value()
+            aria-current=move || is_current_player().then_some("true")
         >
             <div
                 class="w-14 h-14 bg-gray-900 rounded-full"
@@ -802,11 +1167,230 @@ pub fn PlayerCard(player: Player, #[prop(into, optional)] class: Signal<String>)
                 }
             />
             <div class="mt-2 text-sm font-bold">{player.id}": "{player.name.get_value()}</div>
-            <div class="mt-4 text-2xl">
+            <div class="mt-4 text-2xl" title=move || format!("${}k", i18n.money(player.balance()))>
                 <span class="inline-block pr-0.5 opacity-50 scale-75">"$"</span>
-                {move || player.balance().to_string()}
-                <span class="pl-0.5 opacity-70">"k"</span>
+                {move || player.balance().format(&i18n.money_format().compact())}
+            </div>
+            <div class="text-xs opacity-70" title=move || i18n.t("player.rank_title", &[])>
+                {move || {
+                    let net_worth = game_state
+                        .net_worth(&player)
+                        .format(&i18n.money_format().with_symbol("$").compact());
+                    i18n.t(
+                        "player.rank",
+                        &[("rank", &game_state.rank(&player)), ("net_worth", &net_worth)],
+                    )
+                }}
             </div>
+            <ConnectionQuality latency=Signal::derive(move || connection.latency(player.id)) />
+            {move || {
+                connection
+                    .countdown(player.id)
+                    .map(|active| {
+                        view! { <CountdownTimer deadline=active.deadline class="mt-1 text-sm" /> }
+                    })
+            }}
+            <Show when=move || is_self_player && !player.has_lost()>
+                <ResignButton />
+            </Show>
+        </div>
+    }
+}
+
+/// Gives up the game, after the player confirms it.
+#[component]
+fn ResignButton() -> impl IntoView {
+    let connection = ServerConnection::use_context();
+    let i18n = I18n::use_context();
+    let is_confirming = RwSignal::new(false);
+    let error = RwSignal::new(None::<String>);
+
+    let resign = move |_| {
+        is_confirming.set(false);
+        spawn_local(async move {
+            match connection.surrender().await {
+                Ok(()) => error.set(None),
+                Err(request_error) => error.set(Some(request_error.to_string())),
+            }
+        });
+    };
+
+    view! {
+        <Show
+            when=move || is_confirming.get()
+            fallback=move || {
+                view! {
+                    <button
+                        class="px-2 mt-2 text-xs rounded border opacity-70 hover:opacity-100"
+                        on:click=move |_| is_confirming.set(true)
+                    >
+                        {move || i18n.t("button.resign", &[])}
+                    </button>
+                }
+            }
+        >
+            <div class="flex flex-col gap-1 items-center mt-2 text-xs" role="alertdialog" aria-label=move || i18n.t("button.resign", &[])>
+                <span>{move || i18n.t("player.resign_warning", &[])}</span>
+                <div class="flex gap-2">
+                    <button class="px-2 text-red-400 rounded border border-red-400" on:click=resign>
+                        {move || i18n.t("button.resign", &[])}
+                    </button>
+                    <button class="px-2 rounded border" on:click=move |_| is_confirming.set(false)>
+                        {move || i18n.t("button.keep_playing", &[])}
+                    </button>
+                </div>
+            </div>
+        </Show>
+        {move || error.get().map(|error| view! { <span class="text-xs text-red-400">{error}</span> })}
+    }
+}
+
+/// Offer to end the game in a draw, which everyone left in it has to agree to.
+#[component]
+fn DrawOffer() -> impl IntoView {
+    let connection = ServerConnection::use_context();
+    let game_state = GameState::use_context();
+    let i18n = game_state.i18n;
+    let error = RwSignal::new(None::<String>);
+
+    let votes = move || {
+        connection
+            .draw_vote()
+            .filter(|vote| !vote.is_agreed)
+            .map(|vote| vote.votes)
+            .unwrap_or_default()
+    };
+    let players_left = move || {
+        game_state
+            .get_players()
+            .into_values()
+            .filter(|player| !player.has_lost())
+            .count()
+    };
+    let can_vote = move || {
+        game_state
+            .self_player
+            .is_some_and(|self_player| !self_player.has_lost() && !game_state.is_drawn())
+    };
+    let has_voted = move || {
+        game_state
+            .self_player
+            .is_some_and(|self_player| votes().contains(&self_player.id))
+    };
+    let vote = move |agree: bool| {
+        spawn_local(async move {
+            match connection.vote_draw(agree).await {
+                Ok(()) => error.set(None),
+                Err(request_error) => error.set(Some(request_error.to_string())),
+            }
+        });
+    };
+
+    view! {
+        <Show when=can_vote>
+            <div class="flex flex-col gap-1 p-2 text-sm bg-gray-900">
+                {move || {
+                    if votes().is_empty() {
+                        Either::Left(
+                            view! {
+                                <button class="p-1 rounded border" on:click=move |_| vote(true)>
+                                    {move || i18n.t("button.offer_draw", &[])}
+                                </button>
+                            },
+                        )
+                    } else {
+                        Either::Right(
+                            view! {
+                                <span>
+                                    {move || {
+                                        i18n.t("draw.offered", &[("votes", &votes().len()), ("players", &players_left())])
+                                    }}
+                                </span>
+                                <div class="flex gap-2">
+                                    <Show when=move || !has_voted()>
+                                        <button class="px-2 rounded border" on:click=move |_| vote(true)>
+                                            {move || i18n.t("button.agree", &[])}
+                                        </button>
+                                    </Show>
+                                    <button class="px-2 rounded border" on:click=move |_| vote(false)>
+                                        {move || i18n.t(if has_voted() { "button.withdraw" } else { "button.decline" }, &[])}
+                                    </button>
+                                </div>
+                            },
+                        )
+                    }
+                }}
+                {move || error.get().map(|error| view! { <span class="text-xs text-red-400">{error}</span> })}
+            </div>
+        </Show>
+    }
+}
+
+/// Reads the game's events out to screen readers, as they happen.
+#[component]
+fn GameAnnouncer() -> impl IntoView {
+    let game_state = GameState::use_context();
+    let i18n = game_state.i18n;
+
+    view! {
+        <div class="sr-only" role="status" aria-live="polite" aria-atomic="true">
+            {move || {
+                game_state
+                    .log
+                    .entries()
+                    .with(|entries| entries.last().map(|entry| i18n.event(&entry.event)))
+            }}
+        </div>
+    }
+}
+
+/// Agencies the bank has left for the whole board.
+#[component]
+fn BankSupply() -> impl IntoView {
+    let game_state = GameState::use_context();
+    let i18n = game_state.i18n;
+
+    view! {
+        <div class="flex flex-col gap-1 p-2 text-sm bg-gray-900">
+            <div class="font-bold">{move || i18n.t("bank.title", &[])}</div>
+            <div class="flex justify-between">
+                <span>{move || i18n.t("bank.agencies", &[])}</span>
+                <span class="tabular-nums">
+                    {move || format!("{}/{AGENCY_SUPPLY}", game_state.agency_supply().agencies)}
+                </span>
+            </div>
+            <div class="flex justify-between">
+                <span>{move || i18n.t("bank.top_levels", &[])}</span>
+                <span class="tabular-nums">
+                    {move || {
+                        format!("{}/{TOP_LEVEL_SUPPLY}", game_state.agency_supply().top_levels)
+                    }}
+                </span>
+            </div>
+        </div>
+    }
+}
+
+#[component]
+fn ConnectionQuality(latency: Signal<Option<Duration>>) -> impl IntoView {
+    view! {
+        <div class="flex gap-1 items-center mt-2 text-xs opacity-70">
+            <div class=move || {
+                tw!(
+                    "w-2 h-2 rounded-full",
+                    match latency().map(|latency| latency.as_millis()) {
+                        None => "bg-gray-500",
+                        Some(0..150) => "bg-green-500",
+                        Some(150..400) => "bg-yellow-500",
+                        Some(_) => "bg-red-500",
+                    }
+                )
+            } />
+            {move || {
+                latency()
+                    .map(|latency| format!("{}ms", latency.as_millis()))
+                    .unwrap_or("offline".to_owned())
+            }}
         </div>
     }
 }
@@ -819,10 +1403,19 @@ fn PlayerToken(player: Player) -> impl IntoView {
     let gap = 6f64;
 
     let game_state = GameState::use_context();
-    let (scroll_x, scroll_y) = use_window_scroll();
+    let (window_width, window_height) = use_window_size();
     let coordinates = RwSignal::new(None);
 
     Effect::new(move |_| {
+        // Cells move when the window is resized or the board is zoomed.
+        window_width.track();
+        window_height.track();
+        game_page_refs.board_zoom.track();
+        let Some(board) = game_page_refs.board.get() else {
+            return;
+        };
+        let board_rect = board.get_bounding_client_rect();
+
         let players = game_state.get_players_by_cell(player.position());
         let current_player_index = players
             .iter()
@@ -831,8 +1424,8 @@ fn PlayerToken(player: Player) -> impl IntoView {
         let current_cell = game_page_refs.cells.get(player.position());
         let cell_rect = current_cell.get_bounding_client_rect();
 
-        let cell_x_center = scroll_x() + cell_rect.x() + cell_rect.width() / 2f64;
-        let cell_y_center = scroll_y() + cell_rect.y() + cell_rect.height() / 2f64;
+        let cell_x_center = cell_rect.x() - board_rect.x() + cell_rect.width() / 2f64;
+        let cell_y_center = cell_rect.y() - board_rect.y() + cell_rect.height() / 2f64;
 
         let token_box_height = height * players.len() as f64 + gap * (players.len() - 1) as f64;
 
@@ -865,12 +1458,19 @@ fn PlayerToken(player: Player) -> impl IntoView {
 #[component]
 pub fn Chat(
     node_ref: NodeRef<Div>,
+    /// Folded chat keeps showing the prompts, but only on small screens.
+    #[prop(into)]
+    is_chat_open: Signal<bool>,
     #[prop(into, optional)] class: Signal<String>,
 ) -> impl IntoView {
+    let game_state = GameState::use_context();
+
     view! {
         <div node_ref=node_ref class=move || { tw!("relative bg-cyan-700", class()) }>
-            "Chat"
-            <InGameModal />
+            <div class=move || tw!("contents", !is_chat_open.get() => "hidden lg:contents")>
+                <ChatPanel />
+            </div>
+            {(!game_state.is_spectating()).then(|| view! { <InGameModal /> })}
         </div>
     }
 }
@@ -888,10 +1488,9 @@ pub fn GameFinished() -> impl IntoView {
     };
 
     view! {
-        <Show when=move || left_players().len() == 1>
+        <Show when=move || left_players().len() == 1 || game_state.is_drawn()>
             <div class="flex absolute top-0 left-0 z-20 flex-col justify-center items-center w-screen h-screen bg-black/50">
-                <div>"Game Finished"</div>
-                <div>"Winner: " {move || left_players()[0].name.get_value()}</div>
+                <GameSummary />
             </div>
         </Show>
     }
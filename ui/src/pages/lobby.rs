@@ -0,0 +1,137 @@
+use leptos::{prelude::*, task::spawn_local};
+use leptos_router::hooks::use_navigate;
+use shared::room::{RoomInfo, RoomStatus, RulesPreset, MAX_PLAYERS, MAX_ROOM_NAME_LENGTH};
+use tailwind_merge::tw;
+
+use crate::{hooks::authenticated::use_authenticated, i18n::I18n, server::ServerConnection};
+
+#[component]
+pub fn LobbyPage() -> impl IntoView {
+    let connection = ServerConnection::use_context();
+    let i18n = I18n::use_context();
+    let navigate = use_navigate();
+    let name = RwSignal::new(String::new());
+    let rules = RwSignal::new(RulesPreset::default());
+    let error = RwSignal::new(None::<String>);
+
+    // Rooms are pushed by the server after this, until the player joins one.
+    use_authenticated(move || {
+        spawn_local(async move {
+            if let Err(err) = connection.list_rooms().await {
+                error.set(Some(err.to_string()));
+            }
+        });
+    });
+
+    let create = move || {
+        let name = name.get_untracked().trim().to_owned();
+        if name.is_empty() {
+            return;
+        }
+
+        let navigate = navigate.clone();
+        spawn_local(async move {
+            match connection.create_room(name, rules.get_untracked()).await {
+                Ok(room_id) => navigate(&format!("/game/{room_id}"), Default::default()),
+                Err(err) => error.set(Some(err.to_string())),
+            }
+        });
+    };
+
+    view! {
+        <div class="flex flex-col gap-6 p-7 mx-auto w-full max-w-3xl">
+            <h1 class="text-2xl font-bold">{move || i18n.t("lobby.rooms", &[])}</h1>
+            <div class="flex flex-col gap-2">
+                {move || {
+                    connection
+                        .rooms()
+                        .into_iter()
+                        .map(|room| view! { <RoomRow room /> })
+                        .collect_view()
+                }}
+            </div>
+            <form
+                class="flex gap-3 items-center"
+                on:submit=move |event| {
+                    event.prevent_default();
+                    create();
+                }
+            >
+                <input
+                    class="p-2 text-black rounded grow"
+                    placeholder=move || i18n.t("lobby.room_name", &[])
+                    maxlength=MAX_ROOM_NAME_LENGTH.to_string()
+                    prop:value=move || name.get()
+                    on:input=move |event| name.set(event_target_value(&event))
+                />
+                <select
+                    class="p-2 text-black rounded"
+                    on:change=move |event| {
+                        let index = event_target_value(&event).parse::<usize>().unwrap_or_default();
+                        rules.set(RulesPreset::VARIANTS[index]);
+                    }
+                >
+                    {RulesPreset::VARIANTS
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, preset)| {
+                            view! {
+                                <option value=index.to_string() selected=move || rules.get() == preset>
+                                    {move || rules_title(i18n, preset)}
+                                </option>
+                            }
+                        })
+                        .collect_view()}
+                </select>
+                <button class="p-2 rounded border-2" type="submit">
+                    {move || i18n.t("lobby.create", &[])}
+                </button>
+            </form>
+            {move || error.get().map(|error| view! { <div class="text-red-400">{error}</div> })}
+        </div>
+    }
+}
+
+#[component]
+fn RoomRow(room: RoomInfo) -> impl IntoView {
+    let i18n = I18n::use_context();
+    let can_join = room.status != RoomStatus::Finished && room.players < MAX_PLAYERS;
+    let status = match room.status {
+        RoomStatus::Open => "lobby.open",
+        RoomStatus::Running => "lobby.running",
+        RoomStatus::Finished => "lobby.finished",
+    };
+
+    view! {
+        <div class="flex gap-4 items-center p-3 bg-gray-900 rounded-md">
+            <div class="font-bold grow">{room.name}</div>
+            <div class="text-sm opacity-70">{format!("{}/{MAX_PLAYERS}", room.players)}</div>
+            <div class="text-sm opacity-70">{move || rules_title(i18n, room.rules)}</div>
+            <div class=tw!(
+                "text-sm",
+                match room.status {
+                    RoomStatus::Open => "text-green-400",
+                    RoomStatus::Running => "text-yellow-400",
+                    RoomStatus::Finished => "text-gray-500",
+                }
+            )>{move || i18n.t(status, &[])}</div>
+            <a
+                class=tw!("p-2 rounded border-2", !can_join => "invisible")
+                href=format!("/game/{}", room.id)
+            >
+                {move || i18n.t("lobby.join", &[])}
+            </a>
+            <a class="p-2 rounded border-2" href=format!("/watch/{}", room.id)>
+                {move || i18n.t("lobby.spectate", &[])}
+            </a>
+        </div>
+    }
+}
+
+fn rules_title(i18n: I18n, rules: RulesPreset) -> String {
+    let key = match rules {
+        RulesPreset::Classic => "lobby.classic",
+        RulesPreset::Blitz => "lobby.blitz",
+    };
+    i18n.t(key, &[])
+}
@@ -1,6 +1,55 @@
-use leptos::prelude::*;
+use leptos::{prelude::*, task::spawn_local};
+use leptos_router::hooks::{use_navigate, use_query_map};
+
+use crate::{i18n::I18n, server::ServerConnection};
 
 #[component]
 pub fn LoginPage() -> impl IntoView {
-    view! { <div>"Hello World!"</div> }
+    let connection = ServerConnection::use_context();
+    let i18n = I18n::use_context();
+    let navigate = use_navigate();
+    let name = RwSignal::new(String::new());
+    let error = RwSignal::new(None::<String>);
+    // Page the player was sent here from. Only local paths, so that links cannot lead elsewhere.
+    let next = use_query_map()
+        .with_untracked(|query| query.get("next"))
+        .filter(|next| next.starts_with('/') && !next.starts_with("//"))
+        .unwrap_or_else(|| "/lobby".to_owned());
+
+    let login = move || {
+        let name = name.get_untracked().trim().to_owned();
+        if name.is_empty() {
+            return;
+        }
+
+        let navigate = navigate.clone();
+        let next = next.clone();
+        spawn_local(async move {
+            match connection.authenticate(name).await {
+                Ok(_) => navigate(&next, Default::default()),
+                Err(err) => error.set(Some(err.to_string())),
+            }
+        });
+    };
+
+    view! {
+        <form
+            class="flex flex-col gap-3 items-center"
+            on:submit=move |event| {
+                event.prevent_default();
+                login();
+            }
+        >
+            <input
+                class="p-2 text-black rounded"
+                placeholder=move || i18n.t("login.your_name", &[])
+                prop:value=move || name.get()
+                on:input=move |event| name.set(event_target_value(&event))
+            />
+            <button class="p-2 rounded border-2" type="submit">
+                {move || i18n.t("login.play", &[])}
+            </button>
+            {move || error.get().map(|error| view! { <div class="text-red-400">{error}</div> })}
+        </form>
+    }
 }
@@ -1,4 +1,6 @@
 use leptos::prelude::*;
+use shared::game::PlayerSnapshot;
+pub use shared::player::{PlayerColor, PlayerId};
 use snafu::prelude::*;
 
 use crate::cell::{Money, CELLS_COUNT};
@@ -9,39 +11,6 @@ pub enum ConnectionStatus {
     Disconnected,
 }
 
-pub type PlayerId = u64;
-
-#[derive(Debug, Clone, Copy)]
-pub enum PlayerColor {
-    Red,
-    Blue,
-    Green,
-    Purple,
-    Yellow,
-}
-
-impl PlayerColor {
-    pub fn get_player_gradient(&self) -> &'static str {
-        match self {
-            PlayerColor::Red => "linear-gradient(45deg,#cd3747,#f26b61)",
-            PlayerColor::Blue => "linear-gradient(45deg,#54c9f0,#2191e1)",
-            PlayerColor::Green => "linear-gradient(45deg,#66b343,#b0e372)",
-            PlayerColor::Purple => "linear-gradient(45deg,#a17fef,#d188e3)",
-            PlayerColor::Yellow => todo!("Add player card gradient to yellow"),
-        }
-    }
-
-    pub fn get_cell_gradient(&self) -> &'static str {
-        match self {
-            PlayerColor::Red => "linear-gradient(45deg,#d96975,#f59088)",
-            PlayerColor::Blue => "linear-gradient(45deg,#7ed6f3,#58ace8)",
-            PlayerColor::Green => "linear-gradient(45deg,#8cc672,#c3ea95)",
-            PlayerColor::Purple => "linear-gradient(45deg,#b89ff3,#dca5ea)",
-            PlayerColor::Yellow => todo!("Add cell gradient to yellow"),
-        }
-    }
-}
-
 #[derive(Debug, Clone, Copy)]
 pub struct Player {
     pub id: PlayerId,
@@ -105,7 +74,11 @@ impl Player {
     }
 
     pub fn deposit(&self, amount: Money) {
-        self.balance.update(|balance| *balance += amount)
+        self.balance.update(|balance| {
+            *balance = balance
+                .checked_add(amount)
+                .expect("Balance should not overflow")
+        })
     }
 
     pub fn withdraw(&self, amount: Money) -> Result<(), NotEnoughMoneyError> {
@@ -118,7 +91,11 @@ impl Player {
             }
         );
 
-        self.balance.update(|balance| *balance -= amount);
+        self.balance.update(|balance| {
+            *balance = balance
+                .checked_sub(amount)
+                .expect("Balance should not overflow")
+        });
 
         Ok(())
     }
@@ -141,6 +118,28 @@ impl Player {
     pub fn surrender(&self) {
         self.has_lost.set(true);
     }
+
+    pub fn snapshot(&self) -> PlayerSnapshot {
+        PlayerSnapshot {
+            id: self.id,
+            name: self.name.get_value(),
+            color: self.color,
+            balance: self.balance.get(),
+            position: self.position.get(),
+            is_in_jail: self.is_in_jail.get(),
+            has_lost: self.has_lost.get(),
+        }
+    }
+
+    pub fn apply_snapshot(&self, snapshot: &PlayerSnapshot) {
+        self.balance.set(snapshot.balance);
+        // Snapshots come from other clients, so they are not trusted to be valid.
+        if snapshot.position < CELLS_COUNT {
+            self.position.set(snapshot.position);
+        }
+        self.is_in_jail.set(snapshot.is_in_jail);
+        self.has_lost.set(snapshot.has_lost);
+    }
 }
 
 impl PartialEq for Player {
@@ -0,0 +1,119 @@
+use crate::{
+    cell::{Cell, Money, CELLS_COUNT, CHANCE_OUTCOMES, PASSED_START_REWARD, START_REWARD},
+    game_state::GameState,
+    player::Player,
+};
+
+/// What landing on a cell would do to the player, on top of passing Start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LandingEffect {
+    Nothing,
+    StartReward(Money),
+    GoToJail,
+    Tax(Money),
+    /// Random amount from the bank, or to it.
+    Chance {
+        min: Money,
+        max: Money,
+    },
+    Rent {
+        owner: Player,
+        rent: Money,
+    },
+    OwnProperty,
+    /// Property without an owner, which the player may buy.
+    CanBuy {
+        price: Money,
+    },
+}
+
+/// Outcome of moving the player to a cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LandingPreview {
+    /// Reward for passing Start on the way.
+    pub start_bonus: Money,
+    pub effect: LandingEffect,
+}
+
+impl LandingPreview {
+    /// Change of the player's balance that landing causes for sure. Chance counts as its average,
+    /// and buying as nothing, because the player may decline.
+    pub fn balance_change(&self) -> Money {
+        let effect = match self.effect {
+            LandingEffect::StartReward(reward) => reward,
+            LandingEffect::Tax(tax) => -tax,
+            LandingEffect::Chance { .. } => {
+                Money::new(CHANCE_OUTCOMES.iter().sum::<i64>() / CHANCE_OUTCOMES.len() as i64)
+            }
+            LandingEffect::Rent { rent, .. } => -rent,
+            LandingEffect::Nothing
+            | LandingEffect::GoToJail
+            | LandingEffect::OwnProperty
+            | LandingEffect::CanBuy { .. } => Money::default(),
+        };
+        self.start_bonus
+            .checked_add(effect)
+            .expect("Balance change should not overflow")
+    }
+}
+
+/// Chance of rolling the sum with two dice.
+pub fn roll_probability(dice_total: usize) -> f64 {
+    match dice_total {
+        2..=12 => (6 - dice_total.abs_diff(7)) as f64 / 36.,
+        _ => 0.,
+    }
+}
+
+impl GameState {
+    /// Outcome of the player moving `dice_total` cells forward from where they stand.
+    pub fn preview_roll(&self, player: &Player, dice_total: usize) -> LandingPreview {
+        let position = player.position();
+        let cell = (position + dice_total) % CELLS_COUNT;
+        let start_bonus = if position + dice_total >= CELLS_COUNT {
+            PASSED_START_REWARD.into()
+        } else {
+            Money::default()
+        };
+
+        LandingPreview {
+            start_bonus,
+            ..self.preview_landing(player, cell, dice_total)
+        }
+    }
+
+    /// Outcome of the player landing on the cell after rolling `dice_total`, which utilities'
+    /// rent depends on. Passing Start is not counted.
+    pub fn preview_landing(
+        &self,
+        player: &Player,
+        cell: usize,
+        dice_total: usize,
+    ) -> LandingPreview {
+        let effect = match self.get_cell(cell) {
+            Cell::Jail | Cell::FreeParking => LandingEffect::Nothing,
+            Cell::Start => LandingEffect::StartReward(START_REWARD.into()),
+            Cell::GoToJail => LandingEffect::GoToJail,
+            Cell::Tax(tax) => LandingEffect::Tax(tax),
+            Cell::Chance => LandingEffect::Chance {
+                min: CHANCE_OUTCOMES.into_iter().min().unwrap_or_default().into(),
+                max: CHANCE_OUTCOMES.into_iter().max().unwrap_or_default().into(),
+            },
+            Cell::Property(property) => match property.owner() {
+                Some(owner) if owner == *player => LandingEffect::OwnProperty,
+                Some(owner) => LandingEffect::Rent {
+                    owner,
+                    rent: property.rent_for_roll(self, dice_total).unwrap_or_default(),
+                },
+                None => LandingEffect::CanBuy {
+                    price: property.data.price,
+                },
+            },
+        };
+
+        LandingPreview {
+            start_bonus: Money::default(),
+            effect,
+        }
+    }
+}
@@ -1,37 +1,581 @@
-use std::convert::identity;
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::identity,
+    time::Duration,
+};
 
 use futures::{
     stream::{AbortHandle, Abortable},
     FutureExt, StreamExt,
 };
-use leptos::task::spawn_local;
-use shared::{backend::BackendClient, frontend::Frontend, spawn_two_way::spawn_two_way};
-use tarpc::{context, server::Channel};
+use gloo_timers::future::IntervalStream;
+use leptos::{prelude::*, task::spawn_local};
+use shared::{
+    backend::BackendClient,
+    chat::{ChatError, ChatMessage},
+    frontend::Frontend,
+    game::{BidRequest, DrawVote, GameError, GameSnapshot, LogEntry},
+    money::Money,
+    player::PlayerId,
+    room::{CreateRoomError, JoinRoomError, RematchError, RoomId, RoomInfo, RoomRole, RulesPreset},
+    spawn_two_way::{spawn_two_way, Heartbeat},
+    timer::{Countdown, CountdownError, CountdownId, TimerKind},
+};
+use snafu::prelude::*;
+use tarpc::{client::RpcError, context, server::Channel};
+
+use crate::utils::{clock, ws};
+
+/// Path of the server's WebSocket endpoint, relative to the page origin.
+const WS_PATH: &str = "/ws";
+const HEARTBEAT_INTERVAL_MS: u32 = 5_000;
+const MAX_MISSED_PONGS: usize = 3;
+/// Key in the local storage, under which the name of the player is saved to log in again after
+/// a reload.
+const PLAYER_NAME_KEY: &str = "rustopoly.player_name";
+
+#[derive(Debug, Snafu)]
+pub enum RequestError {
+    #[snafu(display("Not connected to the server"))]
+    NotConnected,
+    #[snafu(transparent)]
+    Rpc { source: RpcError },
+    #[snafu(transparent)]
+    CreateRoom { source: CreateRoomError },
+    #[snafu(transparent)]
+    JoinRoom { source: JoinRoomError },
+    #[snafu(transparent)]
+    Chat { source: ChatError },
+    #[snafu(transparent)]
+    Game { source: GameError },
+    #[snafu(transparent)]
+    Rematch { source: RematchError },
+    #[snafu(transparent)]
+    Countdown { source: CountdownError },
+}
+
+/// Countdown that is running in the joined room.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActiveCountdown {
+    pub countdown: Countdown,
+    /// Value of [`clock::now_ms`] at which the countdown expires.
+    pub deadline: f64,
+}
+
+impl ActiveCountdown {
+    fn new(countdown: Countdown) -> Self {
+        Self {
+            countdown,
+            deadline: clock::now_ms() + countdown.duration.as_secs_f64() * 1000.,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExpiredCountdown {
+    pub countdown: Countdown,
+    /// The player ran out of timeouts, so the server took them out of the game.
+    pub forfeit: bool,
+}
+
+/// Change of the game that the server decided and announced to the room.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RoomEvent {
+    Surrendered(PlayerId),
+    DrawVoted(DrawVote),
+    CountdownExpired(ExpiredCountdown),
+    /// The player running an auction asks the signed-in player to bid.
+    BidRequested(BidRequest),
+    /// Answer of a bidder to the auction the signed-in player runs.
+    BidPlaced {
+        bidder: PlayerId,
+        bid: Option<Money>,
+    },
+}
+
+/// Connection to the game server, shared through the whole app.
+#[derive(Debug, Clone, Copy)]
+pub struct ServerConnection {
+    client: RwSignal<Option<BackendClient>, LocalStorage>,
+    self_player: RwSignal<Option<(PlayerId, String)>>,
+    /// The connection tried to log in with the saved name, successfully or not.
+    is_auth_checked: RwSignal<bool>,
+    latencies: RwSignal<HashMap<PlayerId, Duration>>,
+    restarting: RwSignal<bool>,
+    rooms: RwSignal<Vec<RoomInfo>>,
+    room_id: RwSignal<Option<RoomId>>,
+    rules: RwSignal<RulesPreset>,
+    seats: RwSignal<Vec<(PlayerId, String)>>,
+    chat: RwSignal<Vec<ChatMessage>>,
+    game: RwSignal<Option<GameSnapshot>>,
+    game_log: RwSignal<Vec<LogEntry>>,
+    rematch: RwSignal<Option<RoomId>>,
+    /// Countdown of the turn, of the decision within it and of a bid in an auction, if any.
+    countdowns: RwSignal<Vec<ActiveCountdown>>,
+    /// Announced changes of the game, which the game page has not applied yet. Several can
+    /// arrive before it gets to them.
+    room_events: RwSignal<VecDeque<RoomEvent>>,
+    draw_vote: RwSignal<Option<DrawVote>>,
+}
+
+impl Default for ServerConnection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ServerConnection {
+    pub fn new() -> Self {
+        Self {
+            client: RwSignal::new_local(None),
+            self_player: RwSignal::new(None),
+            is_auth_checked: RwSignal::new(false),
+            latencies: RwSignal::new(HashMap::new()),
+            restarting: RwSignal::new(false),
+            rooms: RwSignal::new(Vec::new()),
+            room_id: RwSignal::new(None),
+            rules: RwSignal::new(RulesPreset::default()),
+            seats: RwSignal::new(Vec::new()),
+            chat: RwSignal::new(Vec::new()),
+            game: RwSignal::new(None),
+            game_log: RwSignal::new(Vec::new()),
+            rematch: RwSignal::new(None),
+            countdowns: RwSignal::new(Vec::new()),
+            room_events: RwSignal::new(VecDeque::new()),
+            draw_vote: RwSignal::new(None),
+        }
+    }
+
+    pub fn provide_context(&self) {
+        provide_context(*self);
+    }
+
+    pub fn use_context() -> Self {
+        expect_context::<Self>()
+    }
+
+    pub async fn connect(&self) {
+        match connect_to_server(*self).await {
+            Ok(client) => self.client.set(Some(client)),
+            Err(error) => tracing::error!("Unable to connect to the server: {}", error),
+        }
+
+        // Log in again after a reload, with the name that was used last time.
+        let saved_name =
+            local_storage().and_then(|storage| storage.get(PLAYER_NAME_KEY).ok().flatten());
+        if let Some(name) = saved_name {
+            if let Err(error) = self.authenticate(name).await {
+                tracing::warn!("Unable to log in with the saved name: {}", error);
+            }
+        }
+        self.is_auth_checked.set(true);
+    }
+
+    pub fn client(&self) -> Result<BackendClient, RequestError> {
+        self.client.get_untracked().context(NotConnectedSnafu)
+    }
+
+    pub async fn authenticate(&self, name: String) -> Result<PlayerId, RequestError> {
+        let player_id = self
+            .client()?
+            .authenticate(context::current(), name.clone())
+            .await?;
+        if let Some(storage) = local_storage() {
+            let _ = storage.set(PLAYER_NAME_KEY, &name);
+        }
+        self.self_player.set(Some((player_id, name)));
+        Ok(player_id)
+    }
+
+    /// Whether the saved name was tried after connecting, so `self_player` is final for now.
+    pub fn is_auth_checked(&self) -> bool {
+        self.is_auth_checked.get()
+    }
+
+    /// Enters the lobby, leaving the joined room.
+    pub async fn list_rooms(&self) -> Result<(), RequestError> {
+        let rooms = self.client()?.list_rooms(context::current()).await?;
+        self.room_id.set(None);
+        self.rooms.set(rooms);
+        Ok(())
+    }
+
+    pub async fn create_room(
+        &self,
+        name: String,
+        rules: RulesPreset,
+    ) -> Result<RoomId, RequestError> {
+        let room_id = self
+            .client()?
+            .create_room(context::current(), name, rules)
+            .await??;
+        Ok(room_id)
+    }
+
+    /// Rooms listed in the lobby, ordered by id.
+    pub fn rooms(&self) -> Vec<RoomInfo> {
+        self.rooms.get()
+    }
+
+    pub async fn join_room(&self, room_id: RoomId, role: RoomRole) -> Result<(), RequestError> {
+        let joined = self
+            .client()?
+            .join_room(context::current(), room_id, role)
+            .await??;
+        self.room_id.set(Some(room_id));
+        self.rules.set(joined.rules);
+        self.seats.set(joined.seats);
+        self.chat.set(joined.chat);
+        self.game.set(joined.game);
+        self.game_log.set(joined.game_log);
+        self.rematch.set(joined.rematch);
+        self.countdowns.set(
+            joined
+                .countdowns
+                .into_iter()
+                .map(ActiveCountdown::new)
+                .collect(),
+        );
+        self.room_events.update(VecDeque::clear);
+        self.draw_vote.set(None);
+        Ok(())
+    }
+
+    /// Rules of the joined room.
+    pub fn rules(&self) -> RulesPreset {
+        self.rules.get()
+    }
 
-use crate::utils::ws;
+    /// Players seated in the joined room, at the time it was joined.
+    pub fn seats(&self) -> Vec<(PlayerId, String)> {
+        self.seats.get()
+    }
+
+    pub async fn send_chat_message(&self, text: String) -> Result<(), RequestError> {
+        self.client()?
+            .send_chat_message(context::current(), text)
+            .await??;
+        Ok(())
+    }
+
+    /// Chat messages of the joined room, oldest first.
+    pub fn chat(&self) -> ReadSignal<Vec<ChatMessage>> {
+        self.chat.read_only()
+    }
+
+    pub async fn update_game(
+        &self,
+        snapshot: GameSnapshot,
+        events: Vec<LogEntry>,
+    ) -> Result<(), RequestError> {
+        self.client()?
+            .update_game(context::current(), snapshot, events)
+            .await??;
+        Ok(())
+    }
+
+    /// Latest state of the game in the joined room, as published by the seated players.
+    pub fn game(&self) -> Option<GameSnapshot> {
+        self.game.get()
+    }
+
+    /// Log of the game in the joined room, as published by the seated players.
+    pub fn game_log(&self) -> ReadSignal<Vec<LogEntry>> {
+        self.game_log.read_only()
+    }
+
+    /// Asks the server to let the player give up. The game changes once the server tells
+    /// everyone about it.
+    pub async fn surrender(&self) -> Result<(), RequestError> {
+        self.client()?.surrender(context::current()).await??;
+        Ok(())
+    }
+
+    pub async fn vote_draw(&self, agree: bool) -> Result<(), RequestError> {
+        self.client()?
+            .vote_draw(context::current(), agree)
+            .await??;
+        Ok(())
+    }
+
+    /// The last vote on a draw in the joined room.
+    pub fn draw_vote(&self) -> Option<DrawVote> {
+        self.draw_vote.get()
+    }
+
+    /// Asks the player to bid in the auction the signed-in player runs. The answer comes back as
+    /// a room event.
+    pub async fn request_bid(&self, request: BidRequest) -> Result<(), RequestError> {
+        self.client()?
+            .request_bid(context::current(), request)
+            .await??;
+        Ok(())
+    }
+
+    pub async fn place_bid(&self, bid: Option<Money>) -> Result<(), RequestError> {
+        self.client()?.place_bid(context::current(), bid).await??;
+        Ok(())
+    }
+
+    pub async fn rematch(&self) -> Result<RoomId, RequestError> {
+        let rematch_id = self.client()?.rematch(context::current()).await??;
+        self.rematch.set(Some(rematch_id));
+        Ok(rematch_id)
+    }
+
+    /// Room of the rematch of the joined room's game, if someone started it.
+    pub fn rematch_room(&self) -> Option<RoomId> {
+        self.rematch.get()
+    }
+
+    /// Starts the countdown of the player's decision. The server starts the turn countdowns.
+    pub async fn start_countdown(&self) -> Result<(), RequestError> {
+        self.client()?.start_countdown(context::current()).await??;
+        Ok(())
+    }
+
+    pub async fn stop_countdown(&self) -> Result<(), RequestError> {
+        self.client()?.stop_countdown(context::current()).await??;
+        Ok(())
+    }
+
+    /// Countdown of the player's bid, or else of their decision, or else of their turn.
+    pub fn countdown(&self, player_id: PlayerId) -> Option<ActiveCountdown> {
+        self.countdowns.with(|countdowns| {
+            [TimerKind::Bid, TimerKind::Decision, TimerKind::Turn]
+                .into_iter()
+                .find_map(|kind| {
+                    countdowns.iter().find(|active| {
+                        active.countdown.kind == kind && active.countdown.player_id == player_id
+                    })
+                })
+                .copied()
+        })
+    }
+
+    /// Takes the changes of the game announced in the joined room since the last call, oldest
+    /// first. Tracks the new ones.
+    pub fn take_room_events(&self) -> Vec<RoomEvent> {
+        if self.room_events.with(VecDeque::is_empty) {
+            return Vec::new();
+        }
+        self.room_events
+            .try_update(|events| events.drain(..).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn self_player(&self) -> Option<(PlayerId, String)> {
+        self.self_player.get()
+    }
+
+    /// Round-trip time of the player's connection, or `None` if they are offline.
+    pub fn latency(&self, player_id: PlayerId) -> Option<Duration> {
+        self.latencies
+            .with(|latencies| latencies.get(&player_id).copied())
+    }
+
+    /// Whether the server announced that it is shutting down.
+    pub fn is_restarting(&self) -> bool {
+        self.restarting.get()
+    }
+}
 
 #[derive(Clone)]
-pub struct FrontendServer {}
+pub struct FrontendServer {
+    connection: ServerConnection,
+}
 
 impl FrontendServer {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(connection: ServerConnection) -> Self {
+        Self { connection }
+    }
+
+    fn clear_countdown(&self, countdown_id: CountdownId) {
+        let is_running = self.connection.countdowns.with_untracked(|countdowns| {
+            countdowns
+                .iter()
+                .any(|active| active.countdown.id == countdown_id)
+        });
+        if is_running {
+            self.connection.countdowns.update(|countdowns| {
+                countdowns.retain(|active| active.countdown.id != countdown_id);
+            });
+        }
+    }
+
+    fn push_room_event(&self, event: RoomEvent) {
+        self.connection
+            .room_events
+            .update(|events| events.push_back(event));
     }
 }
 
 impl Frontend for FrontendServer {
     async fn event(self, _: context::Context) {}
+
+    async fn latency_updated(
+        self,
+        _: context::Context,
+        player_id: PlayerId,
+        latency: Option<Duration>,
+    ) {
+        self.connection.latencies.update(|latencies| match latency {
+            Some(latency) => {
+                latencies.insert(player_id, latency);
+            }
+            None => {
+                latencies.remove(&player_id);
+            }
+        });
+    }
+
+    async fn room_updated(self, _: context::Context, room: RoomInfo) {
+        self.connection.rooms.update(|rooms| {
+            match rooms.binary_search_by_key(&room.id, |listed| listed.id) {
+                Ok(index) => rooms[index] = room,
+                Err(index) => rooms.insert(index, room),
+            }
+        });
+    }
+
+    async fn chat_message(self, _: context::Context, room_id: RoomId, message: ChatMessage) {
+        if self.connection.room_id.get_untracked() == Some(room_id) {
+            self.connection.chat.update(|chat| chat.push(message));
+        }
+    }
+
+    async fn game_updated(
+        self,
+        _: context::Context,
+        room_id: RoomId,
+        snapshot: GameSnapshot,
+        events: Vec<LogEntry>,
+    ) {
+        if self.connection.room_id.get_untracked() == Some(room_id) {
+            self.connection.game.set(Some(snapshot));
+            self.connection
+                .game_log
+                .update(|game_log| game_log.extend(events));
+        }
+    }
+
+    async fn player_surrendered(self, _: context::Context, room_id: RoomId, player_id: PlayerId) {
+        if self.connection.room_id.get_untracked() == Some(room_id) {
+            // The server cancels the offer of a draw, once someone surrenders.
+            self.connection.draw_vote.set(None);
+            self.push_room_event(RoomEvent::Surrendered(player_id));
+        }
+    }
+
+    async fn draw_vote_updated(self, _: context::Context, room_id: RoomId, vote: DrawVote) {
+        if self.connection.room_id.get_untracked() == Some(room_id) {
+            self.connection.draw_vote.set(Some(vote.clone()));
+            self.push_room_event(RoomEvent::DrawVoted(vote));
+        }
+    }
+
+    async fn bid_requested(self, _: context::Context, room_id: RoomId, request: BidRequest) {
+        if self.connection.room_id.get_untracked() == Some(room_id) {
+            self.push_room_event(RoomEvent::BidRequested(request));
+        }
+    }
+
+    async fn bid_placed(
+        self,
+        _: context::Context,
+        room_id: RoomId,
+        bidder: PlayerId,
+        bid: Option<Money>,
+    ) {
+        if self.connection.room_id.get_untracked() == Some(room_id) {
+            self.push_room_event(RoomEvent::BidPlaced { bidder, bid });
+        }
+    }
+
+    async fn rematch_created(self, _: context::Context, room_id: RoomId, rematch_id: RoomId) {
+        if self.connection.room_id.get_untracked() == Some(room_id) {
+            self.connection.rematch.set(Some(rematch_id));
+        }
+    }
+
+    async fn countdown_started(self, _: context::Context, room_id: RoomId, countdown: Countdown) {
+        if self.connection.room_id.get_untracked() == Some(room_id) {
+            self.connection.countdowns.update(|countdowns| {
+                countdowns.retain(|active| active.countdown.kind != countdown.kind);
+                countdowns.push(ActiveCountdown::new(countdown));
+            });
+        }
+    }
+
+    async fn countdown_stopped(
+        self,
+        _: context::Context,
+        room_id: RoomId,
+        countdown_id: CountdownId,
+    ) {
+        if self.connection.room_id.get_untracked() == Some(room_id) {
+            self.clear_countdown(countdown_id);
+        }
+    }
+
+    async fn countdown_expired(
+        self,
+        _: context::Context,
+        room_id: RoomId,
+        countdown: Countdown,
+        forfeit: bool,
+    ) {
+        if self.connection.room_id.get_untracked() == Some(room_id) {
+            self.clear_countdown(countdown.id);
+            self.push_room_event(RoomEvent::CountdownExpired(ExpiredCountdown {
+                countdown,
+                forfeit,
+            }));
+        }
+    }
+
+    async fn server_restarting(self, _: context::Context) {
+        tracing::info!("Server is restarting");
+        self.connection.restarting.set(true);
+    }
 }
 
-pub async fn connect_to_server() -> Result<BackendClient, std::io::Error> {
-    let transport = ws::bind("").await?;
-    let (server_transport, client_transport) = spawn_two_way(transport);
+pub async fn connect_to_server(
+    connection: ServerConnection,
+) -> Result<BackendClient, std::io::Error> {
+    let transport = ws::bind(&server_url()).await?;
+    let (server_transport, client_transport, _) = spawn_two_way(
+        transport,
+        Heartbeat {
+            interval: IntervalStream::new(HEARTBEAT_INTERVAL_MS),
+            clock: || Duration::from_secs_f64(clock::now_ms() / 1000.),
+            max_missed_pongs: MAX_MISSED_PONGS,
+        },
+    );
     let (server_handle, registration) = AbortHandle::new_pair();
     let server_fut = tarpc::server::BaseChannel::with_defaults(server_transport)
-        .execute(FrontendServer::new().serve())
+        .execute(FrontendServer::new(connection).serve())
         .for_each_concurrent(None, identity);
 
     spawn_local(Abortable::new(server_fut, registration).map(|_| {}));
 
     Ok(BackendClient::new(client_transport, server_handle))
 }
+
+fn local_storage() -> Option<web_sys::Storage> {
+    window().local_storage().ok().flatten()
+}
+
+/// WebSocket URL of the server that served this page.
+fn server_url() -> String {
+    let location = window().location();
+    let protocol = match location.protocol() {
+        Ok(protocol) if protocol == "https:" => "wss",
+        _ => "ws",
+    };
+    let host = location
+        .host()
+        .expect("window.location.host should be available");
+
+    format!("{protocol}://{host}{WS_PATH}")
+}
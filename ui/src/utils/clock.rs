@@ -0,0 +1,24 @@
+use leptos::prelude::*;
+
+/// Milliseconds since the Unix epoch, for timestamps.
+#[cfg(target_arch = "wasm32")]
+pub fn unix_ms() -> u64 {
+    js_sys::Date::now() as u64
+}
+
+/// Milliseconds since the Unix epoch, for timestamps. Native builds only run the tests.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn unix_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Milliseconds since the page was loaded. Monotonic, unlike `Date.now()`.
+pub fn now_ms() -> f64 {
+    window()
+        .performance()
+        .expect("window.performance should be available")
+        .now()
+}
@@ -1,4 +1,5 @@
 pub mod callable_option;
+pub mod clock;
 pub mod fmt_panic;
 pub mod into_either_of;
 pub mod oneshot_event_emitter;